@@ -0,0 +1,2063 @@
+#![no_std]
+#![allow(clippy::too_many_arguments)]
+use juke_common::{
+    bump_instance, bump_persistent, derive_id, links, record_audit, require_sibling, ttl_policy, validation, Artist,
+    AuditEntry, AuditTarget, CommunityClient, HubClient, Links, PayoutsClient, PriceCurve, Track, TrackMintParams,
+    TtlPolicy, User, CONTRACT_VERSION, EVENT_SCHEMA_VERSION, PAUSE_ALL, PAUSE_MINTING,
+};
+use soroban_sdk::{contract, contractclient, contractimpl, contractmeta, contracttype, token, Address, BytesN, Env, Map, String, Symbol, Vec};
+
+contractmeta!(key = "binver", val = "0.1.0");
+
+// ----- Data Structures -----
+#[contracttype]
+#[derive(Clone)]
+pub struct FeatureFlag {
+    pub enabled: bool,
+    pub params: Vec<i128>,
+    pub updated_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct LegacyUser {
+    pub user: Address,
+    pub profile_nft: Address,
+    pub avatar_uri: String,
+    pub reputation: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct LegacyArtist {
+    pub user: Address,
+    pub artist_name: String,
+    pub verified: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct LegacyTrack {
+    pub track_id: BytesN<32>,
+    pub track_nft: Address,
+    pub title: String,
+    pub artist_id: Address,
+    pub collaborators: Vec<Address>,
+    pub play_count: u32,
+    pub base_price: i128,
+    pub licenses_remaining: u32,
+    pub metadata_uri: String,
+    pub royalty_split: Vec<(Address, u32)>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum ImportEntry {
+    User(LegacyUser),
+    Artist(LegacyArtist),
+    Track(LegacyTrack),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ImportState {
+    Open,
+    Paused,
+    Finished,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct ReputationChange {
+    delta: i32,
+    reason: Symbol,
+    reputation: u32,
+    at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SlashingConfig {
+    pub skip_rate_bps: u32,
+    pub min_requests: u32,
+    pub skip_slash: u32,
+    pub kick_slash: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PlatformStats {
+    pub total_tracks: u32,
+    pub total_tables: u32,
+    pub total_requests: u32,
+    pub total_users: u32,
+    pub total_artists: u32,
+    pub total_volume: i128,
+    pub total_royalties: i128,
+    pub total_fees: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct RequestOutcomes {
+    played: u32,
+    skipped: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct BondConfig {
+    pub amount: i128,
+    pub cooldown_secs: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct RegistrationBond {
+    amount: i128,
+    posted_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct OracleConfig {
+    pub oracle: Address,
+    pub max_staleness_secs: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct DailyStats {
+    pub day: u64,
+    pub requests: u32,
+    pub volume: i128,
+    pub fees: i128,
+    pub new_users: u32,
+    pub new_tracks: u32,
+}
+
+#[contracttype]
+enum DataKey {
+    Admin,
+    TokenStellar,
+    Users(Address),
+    Artists(Address),
+    Tracks(BytesN<32>),
+    TrackIdCounter,
+    ArtistTracks(Address, BytesN<32>),
+    FeatureFlags,
+    ImportState,
+}
+
+// Keys encode as the variant name plus its fields, without the enum name, so a
+// variant must not reuse a name and field types already taken by another key enum
+// here or in juke_common.
+#[contracttype]
+enum ConfigKey {
+    Paused,
+    PriceOracle,
+}
+
+#[contracttype]
+enum ProfileKey {
+    ProfileCounter,
+    Owners(u32),
+}
+
+#[contracttype]
+enum BondKey {
+    Config,
+    Bonds(Address),
+}
+
+#[contracttype]
+enum ReputationKey {
+    LastUpdate(Address),
+    History(Address),
+    Slashing,
+    Outcomes(Address),
+    Retained(Address),
+}
+
+#[contracttype]
+enum TrackKey {
+    NftWasm,
+    PendingCollaborators(BytesN<32>),
+    ArtistCatalog(Address),
+    TakenDown(BytesN<32>),
+    PublicAfter(BytesN<32>),
+    UsdPriced(BytesN<32>),
+    ContentHash(BytesN<32>),
+    ContentHashToTrack(BytesN<32>),
+    Tagged(Symbol),
+    MintedAt(BytesN<32>),
+}
+
+#[contracttype]
+enum TokenKey {
+    Allowed,
+    TableTokens(BytesN<32>),
+}
+
+#[contracttype]
+enum DirectoryKey {
+    ArtistDirectory,
+    VerifiedDirectory,
+}
+
+#[contracttype]
+enum FollowKey {
+    // A user's follows are persistent slots 0..FollowingCount; Slot maps each followed
+    // artist back to its slot so unfollowing can swap the last one into the gap
+    Following(Address, u32),
+    FollowingCount(Address),
+    Slot(Address, Address),
+}
+
+#[contracttype]
+enum FavoriteKey {
+    Tracks(Address),
+}
+
+#[contracttype]
+enum StatsKey {
+    Users,
+    Artists,
+    Volume,
+    Royalties,
+    Fees,
+    Daily(u64),
+}
+
+// Each mint deploys its own NFT contract, so batches stay small to fit the budget
+const MAX_BATCH_MINTS: u32 = 10;
+const MAX_BULK_READS: u32 = 50;
+const MAX_TRACK_TAGS: u32 = 5;
+// Each list is one persistent entry rewritten on every change, so it stays bounded
+const MAX_FAVORITES: u32 = 100;
+const MAX_STATS_RANGE_DAYS: u64 = 90;
+// Daily stats are temporary and kept for as far back as a range query reaches
+const DAILY_STATS_TTL: u32 = (MAX_STATS_RANGE_DAYS as u32 + 1) * 17_280;
+// Exponential curves stop compounding at this multiple of the base price
+const MAX_CURVE_MULTIPLIER: i128 = 1_000;
+
+const REPUTATION_BASELINE: u32 = 100;
+// Reputation above the baseline decays by this much per period without changes
+const REPUTATION_DECAY: u32 = 5;
+const REPUTATION_DECAY_PERIOD: u64 = 30 * 86400;
+const REPUTATION_HISTORY_LEN: u32 = 50;
+
+// Reflector-style price feed. Prices are quoted in USD with `decimals()` decimals.
+#[contracttype]
+#[derive(Clone)]
+pub enum OracleAsset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData>;
+    fn decimals(env: Env) -> u32;
+}
+
+// Per-track NFT contract (contracts/trackNft) deployed by create_track
+#[contractclient(name = "TrackNftClient")]
+pub trait TrackNft {
+    fn mint(env: Env, to: Address);
+}
+
+// Accounts, artists and the track catalog, with the platform-wide pause, flags
+// and stats
+#[contract]
+pub struct JukeCatalog;
+
+#[contractimpl]
+impl JukeCatalog {
+    pub fn initialize(env: Env, admin: Address, token_stellar: Address, links: Links) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::TokenStellar, &token_stellar);
+        env.storage().instance().set(&DataKey::TrackIdCounter, &0u32);
+        juke_common::set_links(&env, &links);
+        
+        env.events().publish(
+            (Symbol::new(&env, "initialized"), EVENT_SCHEMA_VERSION),
+            (admin, token_stellar)
+        );
+    }
+
+    // Emergency pause; scopes are PAUSE_* bits so withdrawals can stay open while
+    // minting and requests are halted
+    pub fn pause(env: Env, scopes: u32) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        let paused = Self::get_paused(env.clone()) | (scopes & PAUSE_ALL);
+        env.storage().instance().set(&ConfigKey::Paused, &paused);
+        record_audit(&env, &admin, "pause", AuditTarget::Platform);
+        
+        env.events().publish((Symbol::new(&env, "paused"), EVENT_SCHEMA_VERSION), paused);
+    }
+
+    pub fn unpause(env: Env, scopes: u32) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        let paused = Self::get_paused(env.clone()) & !scopes;
+        env.storage().instance().set(&ConfigKey::Paused, &paused);
+        record_audit(&env, &admin, "unpause", AuditTarget::Platform);
+        
+        env.events().publish((Symbol::new(&env, "unpaused"), EVENT_SCHEMA_VERSION), paused);
+    }
+
+    pub fn get_version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
+
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    pub fn get_paused(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&ConfigKey::Paused)
+            .unwrap_or(0)
+    }
+
+    fn require_not_paused(env: &Env, scope: u32) {
+        if Self::get_paused(env.clone()) & scope != 0 {
+            panic!("Paused");
+        }
+    }
+
+    // Upgrades go through the governance contract, which holds the admin-or-council
+    // gate for the whole deployment and records them in its audit log
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        links(&env).governance.require_auth();
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    pub fn get_audit_count(env: Env) -> u32 {
+        juke_common::audit_count(&env)
+    }
+
+    pub fn get_audit_log(env: Env, start: u32, limit: u32) -> Vec<AuditEntry> {
+        juke_common::audit_log(&env, start, limit)
+    }
+
+    pub fn register_user(env: Env, user: Address, avatar_uri: String, referrer: Option<Address>) {
+        user.require_auth();
+        
+        validation::require_uri(&env, &avatar_uri);
+        
+        if env.storage().persistent().has(&DataKey::Users(user.clone())) {
+            panic!("User already registered");
+        }
+        
+        // Referral state and rewards live with the treasury on the payouts contract
+        if let Some(referrer) = referrer {
+            if referrer == user {
+                panic!("Cannot refer yourself");
+            }
+            if !env.storage().persistent().has(&DataKey::Users(referrer.clone())) {
+                panic!("Referrer not registered");
+            }
+            PayoutsClient::new(&env, &links(&env).payouts).record_referral(
+                &env.current_contract_address(),
+                &user,
+                &referrer,
+            );
+        }
+        
+        // Coming back after deregistering picks up the old reputation
+        let reputation = env.storage().instance()
+            .get(&ReputationKey::Retained(user.clone()))
+            .unwrap_or(REPUTATION_BASELINE);
+        env.storage().instance().remove(&ReputationKey::Retained(user.clone()));
+        
+        let new_user = User {
+            profile_id: Self::mint_profile(&env, &user),
+            avatar_uri,
+            reputation,
+            is_active: true,
+        };
+        
+        Self::save_user(&env, &user, &new_user);
+        Self::add_stat(&env, StatsKey::Users, 1);
+        
+        let bond = Self::get_bond_config(env.clone());
+        if bond.amount > 0 {
+            let token_address: Address = env.storage().instance()
+                .get(&DataKey::TokenStellar)
+                .unwrap();
+            token::Client::new(&env, &token_address).transfer(
+                &user,
+                &env.current_contract_address(),
+                &bond.amount,
+            );
+            env.storage().instance().set(&BondKey::Bonds(user.clone()), &RegistrationBond {
+                amount: bond.amount,
+                posted_at: env.ledger().timestamp(),
+            });
+        }
+        
+        bump_instance(&env);
+    }
+
+    // Payment tokens
+    // The token set at initialize stays the default: it is always accepted and is
+    // the unit for credits, fee tiers and every spend-based aggregate
+    fn default_token(env: &Env) -> Address {
+        env.storage().instance().get(&DataKey::TokenStellar).unwrap()
+    }
+
+    pub fn set_token_allowed(env: Env, token: Address, allowed: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if token == Self::default_token(&env) {
+            panic!("Default token is always accepted");
+        }
+        
+        let mut tokens = Self::get_allowed_tokens(env.clone());
+        match (tokens.first_index_of(&token), allowed) {
+            (None, true) => tokens.push_back(token.clone()),
+            (Some(index), false) => {
+                tokens.remove(index);
+            }
+            _ => return,
+        }
+        env.storage().instance().set(&TokenKey::Allowed, &tokens);
+        
+        let action = if allowed { "token_allowed" } else { "token_removed" };
+        record_audit(&env, &admin, action, AuditTarget::Account(token.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "token_allowed"), EVENT_SCHEMA_VERSION, token),
+            allowed
+        );
+    }
+
+    pub fn get_allowed_tokens(env: Env) -> Vec<Address> {
+        env.storage().instance()
+            .get(&TokenKey::Allowed)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn set_table_tokens(env: Env, owner: Address, table_id: BytesN<32>, tokens: Vec<Address>) {
+        owner.require_auth();
+        
+        let table = HubClient::new(&env, &links(&env).hub).get_table(&table_id).unwrap();
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        let allowed = Self::get_allowed_tokens(env.clone());
+        for token in tokens.iter() {
+            if !allowed.contains(&token) {
+                panic!("Token not allowlisted");
+            }
+        }
+        
+        env.storage().instance().set(&TokenKey::TableTokens(table_id.clone()), &tokens);
+        
+        env.events().publish(
+            (Symbol::new(&env, "table_tokens_set"), EVENT_SCHEMA_VERSION, table_id),
+            tokens
+        );
+    }
+
+    pub fn get_table_tokens(env: Env, table_id: BytesN<32>) -> Vec<Address> {
+        env.storage().instance()
+            .get(&TokenKey::TableTokens(table_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // A token dropped from the allowlist stops being accepted everywhere at once,
+    // even by tables that still list it
+    pub fn accepts_token(env: Env, table_id: BytesN<32>, token: Address) -> bool {
+        if token == Self::default_token(&env) {
+            return true;
+        }
+        
+        Self::get_allowed_tokens(env.clone()).contains(&token)
+            && Self::get_table_tokens(env, table_id).contains(&token)
+    }
+
+    // Registration bond
+    pub fn set_bond_config(env: Env, config: BondConfig) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if config.amount < 0 {
+            panic!("Bond cannot be negative");
+        }
+        
+        env.storage().instance().set(&BondKey::Config, &config);
+        
+        env.events().publish(
+            (Symbol::new(&env, "bond_config_set"), EVENT_SCHEMA_VERSION),
+            config
+        );
+    }
+
+    pub fn get_bond_config(env: Env) -> BondConfig {
+        env.storage().instance()
+            .get(&BondKey::Config)
+            .unwrap_or(BondConfig {
+                amount: 0,
+                cooldown_secs: 0,
+            })
+    }
+
+    pub fn get_registration_bond(env: Env, user: Address) -> Option<RegistrationBond> {
+        env.storage().instance().get(&BondKey::Bonds(user))
+    }
+
+    // Closes a listener account and returns its bond once the cooldown has passed.
+    // The user leaves every table, and their reputation is kept for a return.
+    pub fn deregister_user(env: Env, user: Address) -> i128 {
+        user.require_auth();
+        
+        Self::check_active_user(&env, &user);
+        
+        if env.storage().instance().has(&DataKey::Artists(user.clone())) {
+            panic!("Artists cannot deregister");
+        }
+        
+        let bond: Option<RegistrationBond> = env.storage().instance()
+            .get(&BondKey::Bonds(user.clone()));
+        let cooldown = Self::get_bond_config(env.clone()).cooldown_secs;
+        if let Some(bond) = &bond {
+            if env.ledger().timestamp() < bond.posted_at + cooldown {
+                panic!("Bond cooldown not elapsed");
+            }
+        }
+        
+        let user_data: User = env.storage().persistent()
+            .get(&DataKey::Users(user.clone()))
+            .unwrap();
+        env.storage().persistent().remove(&DataKey::Users(user.clone()));
+        env.storage().instance().remove(&ProfileKey::Owners(user_data.profile_id));
+        env.storage().instance().set(&ReputationKey::Retained(user.clone()), &user_data.reputation);
+        
+        HubClient::new(&env, &links(&env).hub).leave_all_tables(&env.current_contract_address(), &user);
+        
+        let refunded = match bond {
+            Some(bond) => {
+                env.storage().instance().remove(&BondKey::Bonds(user.clone()));
+                let token_address: Address = env.storage().instance()
+                    .get(&DataKey::TokenStellar)
+                    .unwrap();
+                token::Client::new(&env, &token_address).transfer(
+                    &env.current_contract_address(),
+                    &user,
+                    &bond.amount,
+                );
+                bond.amount
+            }
+            None => 0,
+        };
+        
+        env.events().publish(
+            (Symbol::new(&env, "user_deregistered"), EVENT_SCHEMA_VERSION, user),
+            refunded
+        );
+        
+        refunded
+    }
+
+    pub fn suspend_user(env: Env, user: Address, reason_hash: BytesN<32>) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        let mut user_data: User = env.storage().persistent()
+            .get(&DataKey::Users(user.clone()))
+            .unwrap_or_else(|| panic!("User not registered"));
+        
+        user_data.is_active = false;
+        Self::save_user(&env, &user, &user_data);
+        record_audit(&env, &admin, "suspend", AuditTarget::Account(user.clone()));
+        
+        // Abuse forfeits the registration bond to the treasury
+        if let Some(bond) = env.storage().instance()
+            .get::<_, RegistrationBond>(&BondKey::Bonds(user.clone()))
+        {
+            env.storage().instance().remove(&BondKey::Bonds(user.clone()));
+            let payouts = links(&env).payouts;
+            token::Client::new(&env, &Self::default_token(&env)).transfer(
+                &env.current_contract_address(),
+                &payouts,
+                &bond.amount,
+            );
+            PayoutsClient::new(&env, &payouts).credit_treasury(&env.current_contract_address(), &bond.amount);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "user_suspended"), EVENT_SCHEMA_VERSION, user),
+            reason_hash
+        );
+    }
+
+    pub fn reinstate_user(env: Env, user: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        let mut user_data: User = env.storage().persistent()
+            .get(&DataKey::Users(user.clone()))
+            .unwrap_or_else(|| panic!("User not registered"));
+        
+        user_data.is_active = true;
+        Self::save_user(&env, &user, &user_data);
+        record_audit(&env, &admin, "reinstate", AuditTarget::Account(user.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "user_reinstated"), EVENT_SCHEMA_VERSION, user),
+            ()
+        );
+    }
+
+    // Profiles are persistent and extended to the users target whenever they change
+    fn save_user(env: &Env, user: &Address, user_data: &User) {
+        let key = DataKey::Users(user.clone());
+        env.storage().persistent().set(&key, user_data);
+        bump_persistent(env, &key, ttl_policy(env).users);
+    }
+
+    // Account checks for the other contracts: panics unless registered and active
+    pub fn require_active_user(env: Env, user: Address) {
+        Self::check_active_user(&env, &user);
+    }
+
+    fn check_active_user(env: &Env, user: &Address) {
+        let user_data: User = env.storage().persistent()
+            .get(&DataKey::Users(user.clone()))
+            .unwrap_or_else(|| panic!("User not registered"));
+        
+        if !user_data.is_active {
+            panic!("Account suspended");
+        }
+        bump_persistent(env, &DataKey::Users(user.clone()), ttl_policy(env).users);
+    }
+
+    // Followers
+    pub fn follow_artist(env: Env, user: Address, artist: Address) {
+        user.require_auth();
+        
+        Self::check_active_user(&env, &user);
+        
+        if user == artist {
+            panic!("Cannot follow yourself");
+        }
+        
+        let mut artist_data: Artist = env.storage().instance()
+            .get(&DataKey::Artists(artist.clone()))
+            .unwrap_or_else(|| panic!("Artist not found"));
+        
+        let slot_key = FollowKey::Slot(user.clone(), artist.clone());
+        if env.storage().persistent().has(&slot_key) {
+            panic!("Already following");
+        }
+        let count = Self::get_following_count(env.clone(), user.clone());
+        let entry_key = FollowKey::Following(user.clone(), count);
+        env.storage().persistent().set(&entry_key, &artist);
+        env.storage().persistent().set(&slot_key, &count);
+        env.storage().persistent().set(&FollowKey::FollowingCount(user.clone()), &(count + 1));
+        
+        let extend_to = ttl_policy(&env).users;
+        bump_persistent(&env, &entry_key, extend_to);
+        bump_persistent(&env, &slot_key, extend_to);
+        bump_persistent(&env, &FollowKey::FollowingCount(user.clone()), extend_to);
+        
+        artist_data.follower_count += 1;
+        env.storage().instance().set(&DataKey::Artists(artist.clone()), &artist_data);
+        
+        env.events().publish(
+            (Symbol::new(&env, "artist_followed"), EVENT_SCHEMA_VERSION, artist),
+            user
+        );
+    }
+
+    pub fn unfollow_artist(env: Env, user: Address, artist: Address) {
+        user.require_auth();
+        
+        let slot_key = FollowKey::Slot(user.clone(), artist.clone());
+        let slot: u32 = env.storage().persistent()
+            .get(&slot_key)
+            .unwrap_or_else(|| panic!("Not following"));
+        env.storage().persistent().remove(&slot_key);
+        
+        let last = Self::get_following_count(env.clone(), user.clone()) - 1;
+        if slot != last {
+            let moved: Address = env.storage().persistent()
+                .get(&FollowKey::Following(user.clone(), last))
+                .unwrap();
+            env.storage().persistent().set(&FollowKey::Following(user.clone(), slot), &moved);
+            env.storage().persistent().set(&FollowKey::Slot(user.clone(), moved), &slot);
+        }
+        env.storage().persistent().remove(&FollowKey::Following(user.clone(), last));
+        env.storage().persistent().set(&FollowKey::FollowingCount(user.clone()), &last);
+        
+        if let Some(mut artist_data) = env.storage().instance()
+            .get::<_, Artist>(&DataKey::Artists(artist.clone()))
+        {
+            artist_data.follower_count -= 1;
+            env.storage().instance().set(&DataKey::Artists(artist.clone()), &artist_data);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "artist_unfollowed"), EVENT_SCHEMA_VERSION, artist),
+            user
+        );
+    }
+
+    pub fn get_following_count(env: Env, user: Address) -> u32 {
+        env.storage().persistent().get(&FollowKey::FollowingCount(user)).unwrap_or(0)
+    }
+
+    pub fn get_following(env: Env, user: Address, start: u32, limit: u32) -> Vec<Address> {
+        let count = Self::get_following_count(env.clone(), user.clone());
+        
+        let mut following = Vec::new(&env);
+        let end = start.saturating_add(limit).min(count);
+        for i in start..end {
+            following.push_back(env.storage().persistent()
+                .get(&FollowKey::Following(user.clone(), i))
+                .unwrap());
+        }
+        following
+    }
+
+    pub fn is_following(env: Env, user: Address, artist: Address) -> bool {
+        env.storage().persistent().has(&FollowKey::Slot(user, artist))
+    }
+
+    // Favorites
+    pub fn favorite_track(env: Env, user: Address, track_id: BytesN<32>) {
+        user.require_auth();
+        
+        Self::check_active_user(&env, &user);
+        
+        let mut track: Track = env.storage().instance()
+            .get(&DataKey::Tracks(track_id.clone()))
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        let mut favorites = Self::favorites(&env, &FavoriteKey::Tracks(user.clone()));
+        if favorites.contains(&track_id) {
+            panic!("Already favorited");
+        }
+        if favorites.len() >= MAX_FAVORITES {
+            panic!("Too many favorites");
+        }
+        favorites.push_back(track_id.clone());
+        Self::save_favorites(&env, &FavoriteKey::Tracks(user.clone()), &favorites);
+        
+        track.favorite_count += 1;
+        env.storage().instance().set(&DataKey::Tracks(track_id.clone()), &track);
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_favorited"), EVENT_SCHEMA_VERSION, track_id, user.clone()),
+            user
+        );
+    }
+
+    pub fn unfavorite_track(env: Env, user: Address, track_id: BytesN<32>) {
+        user.require_auth();
+        
+        let mut favorites = Self::favorites(&env, &FavoriteKey::Tracks(user.clone()));
+        let index = favorites.first_index_of(&track_id)
+            .unwrap_or_else(|| panic!("Not favorited"));
+        favorites.remove(index);
+        Self::save_favorites(&env, &FavoriteKey::Tracks(user.clone()), &favorites);
+        
+        if let Some(mut track) = env.storage().instance()
+            .get::<_, Track>(&DataKey::Tracks(track_id.clone()))
+        {
+            track.favorite_count -= 1;
+            env.storage().instance().set(&DataKey::Tracks(track_id.clone()), &track);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_unfavorited"), EVENT_SCHEMA_VERSION, track_id, user.clone()),
+            user
+        );
+    }
+
+    pub fn get_favorite_tracks(env: Env, user: Address, start: u32, limit: u32) -> Vec<BytesN<32>> {
+        let favorites = Self::favorites(&env, &FavoriteKey::Tracks(user));
+        let end = start.saturating_add(limit).min(favorites.len());
+        favorites.slice(start.min(end)..end)
+    }
+
+    fn favorites(env: &Env, key: &FavoriteKey) -> Vec<BytesN<32>> {
+        env.storage().persistent().get(key).unwrap_or(Vec::new(env))
+    }
+
+    fn save_favorites(env: &Env, key: &FavoriteKey, favorites: &Vec<BytesN<32>>) {
+        env.storage().persistent().set(key, favorites);
+        bump_persistent(env, key, ttl_policy(env).users);
+    }
+
+    // Reputation
+    pub fn get_reputation(env: Env, user: Address) -> u32 {
+        let user_data: User = env.storage().persistent()
+            .get(&DataKey::Users(user.clone()))
+            .unwrap_or_else(|| panic!("User not registered"));
+        Self::decayed_reputation(&env, &user, user_data.reputation)
+    }
+
+    pub fn get_reputation_history(env: Env, user: Address) -> Vec<ReputationChange> {
+        env.storage().instance()
+            .get(&ReputationKey::History(user))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    fn decayed_reputation(env: &Env, user: &Address, reputation: u32) -> u32 {
+        if reputation <= REPUTATION_BASELINE {
+            return reputation;
+        }
+        
+        let last_update: u64 = env.storage().instance()
+            .get(&ReputationKey::LastUpdate(user.clone()))
+            .unwrap_or(env.ledger().timestamp());
+        let periods = (env.ledger().timestamp().saturating_sub(last_update) / REPUTATION_DECAY_PERIOD)
+            .min(u32::MAX as u64) as u32;
+        
+        reputation
+            .saturating_sub(periods.saturating_mul(REPUTATION_DECAY))
+            .max(REPUTATION_BASELINE)
+    }
+
+    // Reputation changes and request outcomes reported by the hub and governance
+    pub fn adjust_reputation(env: Env, caller: Address, user: Address, delta: i32, reason: Symbol) {
+        require_sibling(&env, &caller);
+        Self::apply_reputation(&env, &user, delta, reason);
+    }
+
+    pub fn record_request_outcome(env: Env, caller: Address, user: Address, played: bool) {
+        require_sibling(&env, &caller);
+        Self::tally_request_outcome(&env, &user, played);
+    }
+
+    // A member kicked from a table loses the configured kick slash
+    pub fn slash_kicked(env: Env, caller: Address, user: Address) {
+        require_sibling(&env, &caller);
+        
+        let slash = Self::get_slashing_config(env.clone()).kick_slash;
+        if slash > 0 {
+            Self::apply_reputation(&env, &user, -(slash.min(i32::MAX as u32) as i32), Symbol::new(&env, "kicked"));
+        }
+    }
+
+    fn apply_reputation(env: &Env, user: &Address, delta: i32, reason: Symbol) {
+        let Some(mut user_data) = env.storage().persistent()
+            .get::<_, User>(&DataKey::Users(user.clone()))
+        else {
+            return;
+        };
+        
+        let current = Self::decayed_reputation(env, user, user_data.reputation);
+        user_data.reputation = current.saturating_add_signed(delta);
+        Self::save_user(env, user, &user_data);
+        env.storage().instance().set(&ReputationKey::LastUpdate(user.clone()), &env.ledger().timestamp());
+        
+        let mut history = Self::get_reputation_history(env.clone(), user.clone());
+        if history.len() >= REPUTATION_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(ReputationChange {
+            delta,
+            reason,
+            reputation: user_data.reputation,
+            at: env.ledger().timestamp(),
+        });
+        env.storage().instance().set(&ReputationKey::History(user.clone()), &history);
+    }
+
+    pub fn set_slashing_config(env: Env, config: SlashingConfig) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if config.skip_rate_bps > 10000 {
+            panic!("Invalid skip rate");
+        }
+        
+        env.storage().instance().set(&ReputationKey::Slashing, &config);
+        
+        env.events().publish(
+            (Symbol::new(&env, "slashing_config_set"), EVENT_SCHEMA_VERSION),
+            config
+        );
+    }
+
+    pub fn get_slashing_config(env: Env) -> SlashingConfig {
+        env.storage().instance()
+            .get(&ReputationKey::Slashing)
+            .unwrap_or(SlashingConfig {
+                skip_rate_bps: 10000,
+                min_requests: 0,
+                skip_slash: 0,
+                kick_slash: 0,
+            })
+    }
+
+    pub fn get_request_outcomes(env: Env, user: Address) -> RequestOutcomes {
+        env.storage().instance()
+            .get(&ReputationKey::Outcomes(user))
+            .unwrap_or(RequestOutcomes { played: 0, skipped: 0 })
+    }
+
+    // Users whose skip rate crosses the configured threshold are slashed on every
+    // further skip until their rate comes back down
+    fn tally_request_outcome(env: &Env, user: &Address, played: bool) {
+        let mut outcomes = Self::get_request_outcomes(env.clone(), user.clone());
+        if played {
+            outcomes.played += 1;
+        } else {
+            outcomes.skipped += 1;
+        }
+        env.storage().instance().set(&ReputationKey::Outcomes(user.clone()), &outcomes);
+        
+        if played {
+            return;
+        }
+        
+        let config = Self::get_slashing_config(env.clone());
+        let total = outcomes.played + outcomes.skipped;
+        if config.skip_slash == 0 || total < config.min_requests {
+            return;
+        }
+        
+        let skip_rate = outcomes.skipped as u64 * 10000 / total as u64;
+        if skip_rate >= config.skip_rate_bps as u64 {
+            let slash = -(config.skip_slash.min(i32::MAX as u32) as i32);
+            Self::apply_reputation(env, user, slash, Symbol::new(env, "spam_slash"));
+        }
+    }
+
+    pub fn register_artist(env: Env, user: Address, artist_name: String) {
+        user.require_auth();
+        
+        validation::require_name(&env, &artist_name);
+        
+        if !env.storage().persistent().has(&DataKey::Users(user.clone())) {
+            panic!("User not registered");
+        }
+        
+        if env.storage().instance().has(&DataKey::Artists(user.clone())) {
+            panic!("Already registered as artist");
+        }
+        
+        let new_artist = Artist {
+            user_id: user.clone(),
+            artist_name,
+            verified: false,
+            follower_count: 0,
+        };
+        
+        env.storage().instance().set(&DataKey::Artists(user.clone()), &new_artist);
+        Self::add_stat(&env, StatsKey::Artists, 1);
+        Self::index_directory(&env, DirectoryKey::ArtistDirectory, &user, true);
+        
+        env.events().publish(
+            (Symbol::new(&env, "artist_registered"), EVENT_SCHEMA_VERSION, user),
+            new_artist.artist_name
+        );
+        
+        bump_instance(&env);
+    }
+
+    pub fn update_user_profile(env: Env, user: Address, avatar_uri: String) {
+        user.require_auth();
+        
+        validation::require_uri(&env, &avatar_uri);
+        
+        let mut user_data: User = env.storage().persistent()
+            .get(&DataKey::Users(user.clone()))
+            .unwrap();
+        
+        user_data.avatar_uri = avatar_uri;
+        Self::save_user(&env, &user, &user_data);
+        
+        env.events().publish(
+            (Symbol::new(&env, "profile_updated"), EVENT_SCHEMA_VERSION, user),
+            user_data.avatar_uri
+        );
+    }
+
+    pub fn mint_track(
+        env: Env,
+        artist: Address,
+        title: String,
+        base_price: i128,
+        licenses: u32,
+        metadata_uri: String,
+        collaborators: Vec<Address>,
+        royalty_split: Vec<(Address, u32)>,
+        public_after: Option<u64>,
+        content_hash: Option<BytesN<32>>,
+        tags: Vec<Symbol>,
+    ) -> BytesN<32> {
+        artist.require_auth();
+        
+        if !env.storage().instance().has(&DataKey::Artists(artist.clone())) {
+            panic!("Not registered as artist");
+        }
+        
+        if let Some(content_hash) = &content_hash {
+            if env.storage().instance().has(&TrackKey::ContentHashToTrack(content_hash.clone())) {
+                panic!("Content already registered");
+            }
+        }
+        
+        let track_id = Self::create_track(
+            &env,
+            &artist,
+            title,
+            base_price,
+            licenses,
+            metadata_uri,
+            collaborators,
+            royalty_split,
+        );
+        
+        if let Some(public_after) = public_after {
+            if public_after > env.ledger().timestamp() {
+                env.storage().instance().set(&TrackKey::PublicAfter(track_id.clone()), &public_after);
+            }
+        }
+        
+        if let Some(content_hash) = content_hash {
+            env.storage().instance().set(&TrackKey::ContentHash(track_id.clone()), &content_hash);
+            env.storage().instance().set(&TrackKey::ContentHashToTrack(content_hash), &track_id);
+        }
+        
+        if !tags.is_empty() {
+            Self::retag_track(&env, &track_id, tags);
+        }
+        
+        track_id
+    }
+
+    // Audio fingerprints let clients spot a re-upload before paying to mint it
+    pub fn find_track_by_content_hash(env: Env, content_hash: BytesN<32>) -> Option<BytesN<32>> {
+        env.storage().instance().get(&TrackKey::ContentHashToTrack(content_hash))
+    }
+
+    pub fn get_content_hash(env: Env, track_id: BytesN<32>) -> Option<BytesN<32>> {
+        env.storage().instance().get(&TrackKey::ContentHash(track_id))
+    }
+
+    pub fn get_public_after(env: Env, track_id: BytesN<32>) -> Option<u64> {
+        env.storage().instance().get(&TrackKey::PublicAfter(track_id))
+    }
+
+    pub fn get_minted_at(env: Env, track_id: BytesN<32>) -> Option<u64> {
+        env.storage().instance().get(&TrackKey::MintedAt(track_id))
+    }
+
+    pub fn get_artist_tracks(env: Env, artist: Address) -> Vec<BytesN<32>> {
+        env.storage().instance()
+            .get(&TrackKey::ArtistCatalog(artist))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Tags
+    pub fn set_track_tags(env: Env, artist: Address, track_id: BytesN<32>, tags: Vec<Symbol>) {
+        artist.require_auth();
+        
+        let track: Track = env.storage().instance()
+            .get(&DataKey::Tracks(track_id.clone()))
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track artist");
+        }
+        
+        Self::retag_track(&env, &track_id, tags);
+    }
+
+    // Replaces the track's tags and moves it between the per-tag indexes
+    fn retag_track(env: &Env, track_id: &BytesN<32>, tags: Vec<Symbol>) {
+        if tags.len() > MAX_TRACK_TAGS {
+            panic!("Too many tags");
+        }
+        
+        let mut unique: Vec<Symbol> = Vec::new(env);
+        for tag in tags.iter() {
+            if !unique.contains(&tag) {
+                unique.push_back(tag);
+            }
+        }
+        
+        let mut track: Track = env.storage().instance().get(&DataKey::Tracks(track_id.clone())).unwrap();
+        
+        for tag in track.tags.iter() {
+            if unique.contains(&tag) {
+                continue;
+            }
+            let mut tagged: Vec<BytesN<32>> = env.storage().instance()
+                .get(&TrackKey::Tagged(tag.clone()))
+                .unwrap_or(Vec::new(env));
+            if let Some(index) = tagged.first_index_of(track_id) {
+                tagged.remove(index);
+            }
+            env.storage().instance().set(&TrackKey::Tagged(tag), &tagged);
+        }
+        
+        for tag in unique.iter() {
+            if track.tags.contains(&tag) {
+                continue;
+            }
+            let mut tagged: Vec<BytesN<32>> = env.storage().instance()
+                .get(&TrackKey::Tagged(tag.clone()))
+                .unwrap_or(Vec::new(env));
+            tagged.push_back(track_id.clone());
+            env.storage().instance().set(&TrackKey::Tagged(tag), &tagged);
+        }
+        
+        track.tags = unique;
+        env.storage().instance().set(&DataKey::Tracks(track_id.clone()), &track);
+    }
+
+    pub fn list_tracks_by_tag(env: Env, tag: Symbol, start: u32, limit: u32) -> Vec<BytesN<32>> {
+        let tagged: Vec<BytesN<32>> = env.storage().instance()
+            .get(&TrackKey::Tagged(tag))
+            .unwrap_or(Vec::new(&env));
+        
+        let mut page = Vec::new(&env);
+        let end = start.saturating_add(limit).min(tagged.len());
+        for i in start..end {
+            page.push_back(tagged.get(i).unwrap());
+        }
+        page
+    }
+
+    // Entries with zero licenses or an empty split fall back to the shared defaults
+    pub fn mint_tracks(
+        env: Env,
+        artist: Address,
+        tracks: Vec<TrackMintParams>,
+        default_licenses: u32,
+        default_royalty_split: Vec<(Address, u32)>,
+    ) -> Vec<BytesN<32>> {
+        artist.require_auth();
+        
+        if !env.storage().instance().has(&DataKey::Artists(artist.clone())) {
+            panic!("Not registered as artist");
+        }
+        
+        if tracks.is_empty() || tracks.len() > MAX_BATCH_MINTS {
+            panic!("Invalid batch size");
+        }
+        
+        let mut track_ids = Vec::new(&env);
+        for params in tracks.iter() {
+            let licenses = if params.licenses == 0 { default_licenses } else { params.licenses };
+            let royalty_split = if params.royalty_split.is_empty() {
+                default_royalty_split.clone()
+            } else {
+                params.royalty_split
+            };
+            
+            track_ids.push_back(Self::create_track(
+                &env,
+                &artist,
+                params.title,
+                params.base_price,
+                licenses,
+                params.metadata_uri,
+                params.collaborators,
+                royalty_split,
+            ));
+        }
+        
+        track_ids
+    }
+
+    // Mints on behalf of the venue contract's albums and campaigns, which have
+    // already checked the artist. Campaign backers bought their share, so
+    // `cosign` is false there and nobody else on the split has to approve.
+    pub fn mint_for(env: Env, caller: Address, artist: Address, params: TrackMintParams, cosign: bool) -> BytesN<32> {
+        require_sibling(&env, &caller);
+        
+        let track_id = Self::create_track(
+            &env,
+            &artist,
+            params.title,
+            params.base_price,
+            params.licenses,
+            params.metadata_uri,
+            params.collaborators,
+            params.royalty_split,
+        );
+        if !cosign {
+            env.storage().instance().remove(&TrackKey::PendingCollaborators(track_id.clone()));
+        }
+        track_id
+    }
+
+    fn create_track(
+        env: &Env,
+        artist: &Address,
+        title: String,
+        base_price: i128,
+        licenses: u32,
+        metadata_uri: String,
+        collaborators: Vec<Address>,
+        royalty_split: Vec<(Address, u32)>,
+    ) -> BytesN<32> {
+        Self::require_not_paused(env, PAUSE_MINTING);
+        Self::check_active_user(env, artist);
+        
+        validation::require_name(env, &title);
+        validation::require_uri(env, &metadata_uri);
+        
+        let mut total_split = 0;
+        for (_, percentage) in royalty_split.iter() {
+            total_split += percentage;
+        }
+        if total_split != 100 {
+            panic!("Royalty splits must total 100%");
+        }
+        
+        let mut track_counter: u32 = env.storage().instance()
+            .get(&DataKey::TrackIdCounter)
+            .unwrap();
+        track_counter += 1;
+        
+        let track_id = derive_id(env, b"track_", track_counter, artist);
+        
+        // Each track gets its own NFT contract, deployed at an address derived from
+        // the track id, with the single token minted straight to the artist
+        let nft_wasm: BytesN<32> = env.storage().instance()
+            .get(&TrackKey::NftWasm)
+            .unwrap_or_else(|| panic!("Track NFT wasm not configured"));
+        let track_nft = env.deployer()
+            .with_current_contract(track_id.clone())
+            .deploy_v2(nft_wasm, (env.current_contract_address(), title.clone(), metadata_uri.clone()));
+        TrackNftClient::new(env, &track_nft).mint(artist);
+        
+        let new_track = Track {
+            track_id: track_id.clone(),
+            track_nft,
+            title,
+            artist_id: artist.clone(),
+            collaborators,
+            play_count: 0,
+            base_price,
+            licenses_remaining: licenses,
+            metadata_uri,
+            royalty_split,
+            price_curve: PriceCurve::Fixed,
+            licenses_sold: 0,
+            split_history: Vec::new(env),
+            favorite_count: 0,
+            tags: Vec::new(env),
+            charity: None,
+            charity_bps: 0,
+        };
+        
+        env.storage().instance().set(&DataKey::Tracks(track_id.clone()), &new_track);
+        Self::index_artist_track(env, artist, &track_id);
+        env.storage().instance().set(&DataKey::TrackIdCounter, &track_counter);
+        env.storage().instance().set(&TrackKey::MintedAt(track_id.clone()), &env.ledger().timestamp());
+        Self::update_daily_stats(env, |stats| stats.new_tracks += 1);
+        CommunityClient::new(env, &links(env).community).unlock_achievement(
+            &env.current_contract_address(),
+            artist,
+            &Symbol::new(env, "first_mint"),
+        );
+        
+        // Everyone named on the track other than the minting artist has to co-sign
+        let mut pending: Vec<Address> = Vec::new(env);
+        for collaborator in new_track.collaborators.iter() {
+            if &collaborator != artist && !pending.contains(&collaborator) {
+                pending.push_back(collaborator);
+            }
+        }
+        for (recipient, _) in new_track.royalty_split.iter() {
+            if &recipient != artist && !pending.contains(&recipient) {
+                pending.push_back(recipient);
+            }
+        }
+        if !pending.is_empty() {
+            env.storage().instance().set(&TrackKey::PendingCollaborators(track_id.clone()), &pending);
+        }
+        
+        env.events().publish(
+            (Symbol::new(env, "track_minted"), EVENT_SCHEMA_VERSION, track_id.clone(), artist.clone()),
+            (artist.clone(), new_track.title, new_track.base_price, new_track.licenses_remaining)
+        );
+        
+        track_id
+    }
+
+    fn index_artist_track(env: &Env, artist: &Address, track_id: &BytesN<32>) {
+        env.storage().instance().set(&DataKey::ArtistTracks(artist.clone(), track_id.clone()), &true);
+        
+        let mut catalog: Vec<BytesN<32>> = env.storage().instance()
+            .get(&TrackKey::ArtistCatalog(artist.clone()))
+            .unwrap_or(Vec::new(env));
+        catalog.push_back(track_id.clone());
+        env.storage().instance().set(&TrackKey::ArtistCatalog(artist.clone()), &catalog);
+    }
+
+    pub fn set_track_nft_wasm(env: Env, wasm_hash: BytesN<32>) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        env.storage().instance().set(&TrackKey::NftWasm, &wasm_hash);
+        record_audit(&env, &admin, "track_nft_wasm", AuditTarget::Platform);
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_nft_wasm_set"), EVENT_SCHEMA_VERSION),
+            wasm_hash
+        );
+    }
+
+    pub fn accept_collaboration(env: Env, collaborator: Address, track_id: BytesN<32>) {
+        collaborator.require_auth();
+        
+        let pending: Vec<Address> = env.storage().instance()
+            .get(&TrackKey::PendingCollaborators(track_id.clone()))
+            .unwrap_or_else(|| panic!("No pending collaboration"));
+        
+        let index = pending.first_index_of(&collaborator)
+            .unwrap_or_else(|| panic!("Not a pending collaborator"));
+        
+        let mut pending = pending;
+        pending.remove(index);
+        
+        env.events().publish(
+            (Symbol::new(&env, "collaboration_accepted"), EVENT_SCHEMA_VERSION, track_id.clone(), collaborator.clone()),
+            collaborator
+        );
+        
+        if pending.is_empty() {
+            env.storage().instance().remove(&TrackKey::PendingCollaborators(track_id.clone()));
+            env.events().publish(
+                (Symbol::new(&env, "track_activated"), EVENT_SCHEMA_VERSION, track_id),
+                ()
+            );
+        } else {
+            env.storage().instance().set(&TrackKey::PendingCollaborators(track_id), &pending);
+        }
+    }
+
+    pub fn get_pending_collaborators(env: Env, track_id: BytesN<32>) -> Vec<Address> {
+        env.storage().instance()
+            .get(&TrackKey::PendingCollaborators(track_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn takedown_track(env: Env, admin: Address, track_id: BytesN<32>, reason_hash: BytesN<32>) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Not admin");
+        }
+        admin.require_auth();
+        
+        if !env.storage().instance().has(&DataKey::Tracks(track_id.clone())) {
+            panic!("Track not found");
+        }
+        
+        if env.storage().instance().has(&TrackKey::TakenDown(track_id.clone())) {
+            panic!("Track already taken down");
+        }
+        
+        env.storage().instance().set(&TrackKey::TakenDown(track_id.clone()), &reason_hash);
+        record_audit(&env, &admin, "takedown", AuditTarget::Track(track_id.clone()));
+        
+        HubClient::new(&env, &links(&env).hub).purge_track(&env.current_contract_address(), &track_id);
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_taken_down"), EVENT_SCHEMA_VERSION, track_id),
+            reason_hash
+        );
+    }
+
+    pub fn is_track_taken_down(env: Env, track_id: BytesN<32>) -> bool {
+        env.storage().instance().has(&TrackKey::TakenDown(track_id))
+    }
+
+    pub fn require_track_live(env: Env, track_id: BytesN<32>) {
+        if env.storage().instance().has(&TrackKey::TakenDown(track_id.clone())) {
+            panic!("Track taken down");
+        }
+        if env.storage().instance().has(&TrackKey::PendingCollaborators(track_id)) {
+            panic!("Track awaiting collaborator approval");
+        }
+    }
+
+    pub fn update_track(
+        env: Env,
+        artist: Address,
+        track_id: BytesN<32>,
+        new_base_price: i128,
+        new_licenses: u32,
+        new_metadata_uri: String,
+    ) {
+        artist.require_auth();
+        
+        validation::require_uri(&env, &new_metadata_uri);
+        
+        let mut track: Track = env.storage().instance()
+            .get(&DataKey::Tracks(track_id.clone()))
+            .unwrap();
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        track.base_price = new_base_price;
+        track.licenses_remaining = new_licenses;
+        track.metadata_uri = new_metadata_uri;
+        
+        env.storage().instance().set(&DataKey::Tracks(track_id.clone()), &track);
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_updated"), EVENT_SCHEMA_VERSION, track_id),
+            (track.base_price, track.licenses_remaining, track.metadata_uri)
+        );
+    }
+
+    pub fn set_price_curve(env: Env, artist: Address, track_id: BytesN<32>, curve: PriceCurve) {
+        artist.require_auth();
+        Self::require_flag(&env, "bonding_curve");
+        
+        let mut track: Track = env.storage().instance()
+            .get(&DataKey::Tracks(track_id.clone()))
+            .unwrap();
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        match curve {
+            PriceCurve::Linear(increment) if increment < 0 => panic!("Increment cannot be negative"),
+            PriceCurve::Exponential(growth_bps) if growth_bps == 0 || growth_bps > 10000 => {
+                panic!("Invalid growth rate")
+            }
+            _ => {}
+        }
+        
+        track.price_curve = curve;
+        env.storage().instance().set(&DataKey::Tracks(track_id.clone()), &track);
+        
+        env.events().publish(
+            (Symbol::new(&env, "price_curve_set"), EVENT_SCHEMA_VERSION, track_id),
+            track.price_curve
+        );
+    }
+
+    // Base price of the next license, after applying the track's price curve
+    // USD-priced tracks keep base_price and the curve in cents and are converted
+    // to the default token at the oracle's latest price
+    fn current_base_price(env: &Env, track: &Track) -> i128 {
+        let price = Self::curve_price(track);
+        if env.storage().instance().has(&TrackKey::UsdPriced(track.track_id.clone())) {
+            Self::usd_cents_to_token(env, price)
+        } else {
+            price
+        }
+    }
+
+    fn curve_price(track: &Track) -> i128 {
+        let price = match track.price_curve {
+            PriceCurve::Fixed => Some(track.base_price),
+            PriceCurve::Linear(increment) => increment
+                .checked_mul(track.licenses_sold as i128)
+                .and_then(|step| track.base_price.checked_add(step)),
+            PriceCurve::Exponential(growth_bps) => {
+                // Clamping both terms at the cap keeps every product small; once
+                // either reaches it the price has hit the ceiling anyway
+                let cap = MAX_CURVE_MULTIPLIER * 10000;
+                let mut factor: i128 = 10000 + growth_bps as i128;
+                let mut scale: i128 = 10000;
+                let mut remaining = track.licenses_sold;
+                while remaining > 0 {
+                    if remaining & 1 == 1 {
+                        scale = (scale * factor / 10000).min(cap);
+                    }
+                    remaining >>= 1;
+                    if remaining > 0 {
+                        factor = (factor * factor / 10000).min(cap);
+                    }
+                }
+                track.base_price.checked_mul(scale).map(|scaled| scaled / 10000)
+            }
+        };
+        price.unwrap_or_else(|| panic!("Price overflow"))
+    }
+
+    // With `usd` set, `base_price` (and any linear curve increment) is in USD cents
+    pub fn set_track_pricing(env: Env, artist: Address, track_id: BytesN<32>, base_price: i128, usd: bool) {
+        artist.require_auth();
+        
+        let mut track: Track = env.storage().instance()
+            .get(&DataKey::Tracks(track_id.clone()))
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        if base_price < 0 {
+            panic!("Price cannot be negative");
+        }
+        
+        if usd {
+            Self::get_price_oracle(env.clone()).unwrap_or_else(|| panic!("No price oracle configured"));
+            env.storage().instance().set(&TrackKey::UsdPriced(track_id.clone()), &true);
+        } else {
+            env.storage().instance().remove(&TrackKey::UsdPriced(track_id.clone()));
+        }
+        
+        track.base_price = base_price;
+        env.storage().instance().set(&DataKey::Tracks(track_id.clone()), &track);
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_repriced"), EVENT_SCHEMA_VERSION, track_id),
+            (base_price, usd)
+        );
+    }
+
+    pub fn is_usd_priced(env: Env, track_id: BytesN<32>) -> bool {
+        env.storage().instance().has(&TrackKey::UsdPriced(track_id))
+    }
+
+    pub fn set_price_oracle(env: Env, config: OracleConfig) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        env.storage().instance().set(&ConfigKey::PriceOracle, &config);
+        record_audit(&env, &admin, "price_oracle", AuditTarget::Account(config.oracle.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "price_oracle_set"), EVENT_SCHEMA_VERSION),
+            config
+        );
+    }
+
+    pub fn get_price_oracle(env: Env) -> Option<OracleConfig> {
+        env.storage().instance().get(&ConfigKey::PriceOracle)
+    }
+
+    // Rounds up so a request never pays less than its USD price
+    fn usd_cents_to_token(env: &Env, cents: i128) -> i128 {
+        let config = Self::get_price_oracle(env.clone())
+            .unwrap_or_else(|| panic!("No price oracle configured"));
+        let oracle = PriceOracleClient::new(env, &config.oracle);
+        let token = Self::default_token(env);
+        let price = Self::oracle_price(env, &oracle, &config, &token);
+        
+        let scale = 10i128.pow(oracle.decimals() + token::Client::new(env, &token).decimals());
+        let numerator = cents * scale;
+        let denominator = price * 100;
+        (numerator + denominator - 1) / denominator
+    }
+
+    // Converts a default-token amount into `token` at oracle prices, rounding up
+    // for the same reason
+    pub fn default_to_token(env: Env, amount: i128, token: Address) -> i128 {
+        let config = Self::get_price_oracle(env.clone())
+            .unwrap_or_else(|| panic!("No price oracle configured"));
+        let oracle = PriceOracleClient::new(&env, &config.oracle);
+        let default_token = Self::default_token(&env);
+        let default_price = Self::oracle_price(&env, &oracle, &config, &default_token);
+        let token_price = Self::oracle_price(&env, &oracle, &config, &token);
+        
+        let numerator = amount * default_price * 10i128.pow(token::Client::new(&env, &token).decimals());
+        let denominator = token_price * 10i128.pow(token::Client::new(&env, &default_token).decimals());
+        (numerator + denominator - 1) / denominator
+    }
+
+    fn oracle_price(env: &Env, oracle: &PriceOracleClient, config: &OracleConfig, token: &Address) -> i128 {
+        let quote = oracle.lastprice(&OracleAsset::Stellar(token.clone()))
+            .unwrap_or_else(|| panic!("No oracle price"));
+        if quote.price <= 0 || env.ledger().timestamp() > quote.timestamp + config.max_staleness_secs {
+            panic!("Oracle price stale");
+        }
+        quote.price
+    }
+
+    pub fn get_current_price(env: Env, track_id: BytesN<32>) -> i128 {
+        let track: Track = env.storage().instance()
+            .get(&DataKey::Tracks(track_id))
+            .unwrap_or_else(|| panic!("Track not found"));
+        Self::current_base_price(&env, &track)
+    }
+
+    // Profiles are soulbound: minted once per user on this contract's own ledger and
+    // never transferable, only burned when the account is deregistered
+    fn mint_profile(env: &Env, user: &Address) -> u32 {
+        let profile_id: u32 = env.storage().instance()
+            .get(&ProfileKey::ProfileCounter)
+            .unwrap_or(0) + 1;
+        env.storage().instance().set(&ProfileKey::ProfileCounter, &profile_id);
+        env.storage().instance().set(&ProfileKey::Owners(profile_id), user);
+        
+        env.events().publish(
+            (Symbol::new(env, "profile_minted"), EVENT_SCHEMA_VERSION, user.clone()),
+            profile_id
+        );
+        
+        profile_id
+    }
+
+    pub fn get_profile_owner(env: Env, profile_id: u32) -> Option<Address> {
+        env.storage().instance().get(&ProfileKey::Owners(profile_id))
+    }
+
+    // View functions
+    pub fn get_user(env: Env, user: Address) -> Option<User> {
+        env.storage().persistent().get(&DataKey::Users(user))
+    }
+
+    pub fn get_artist(env: Env, artist: Address) -> Option<Artist> {
+        env.storage().instance().get(&DataKey::Artists(artist))
+    }
+
+    pub fn get_track(env: Env, track_id: BytesN<32>) -> Option<Track> {
+        env.storage().instance().get(&DataKey::Tracks(track_id))
+    }
+
+    // Write-back for the track fields other contracts maintain: play and license
+    // counts from the hub and market, the split and charity from payouts
+    pub fn save_track(env: Env, caller: Address, track: Track) {
+        require_sibling(&env, &caller);
+        
+        if !env.storage().instance().has(&DataKey::Tracks(track.track_id.clone())) {
+            panic!("Track not found");
+        }
+        env.storage().instance().set(&DataKey::Tracks(track.track_id.clone()), &track);
+    }
+
+    // Bulk reads, positionally aligned with the input; missing entries come back as None
+    pub fn get_users(env: Env, users: Vec<Address>) -> Vec<Option<User>> {
+        if users.len() > MAX_BULK_READS {
+            panic!("Too many ids");
+        }
+        
+        let mut result = Vec::new(&env);
+        for user in users.iter() {
+            result.push_back(env.storage().persistent().get(&DataKey::Users(user)));
+        }
+        result
+    }
+
+    pub fn get_tracks(env: Env, track_ids: Vec<BytesN<32>>) -> Vec<Option<Track>> {
+        if track_ids.len() > MAX_BULK_READS {
+            panic!("Too many ids");
+        }
+        
+        let mut result = Vec::new(&env);
+        for track_id in track_ids.iter() {
+            result.push_back(env.storage().instance().get(&DataKey::Tracks(track_id)));
+        }
+        result
+    }
+
+    pub fn get_total_tracks(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::TrackIdCounter).unwrap_or(0)
+    }
+
+    pub fn get_platform_stats(env: Env) -> (u32, u32, u32) {
+        let total_tracks = Self::get_total_tracks(env.clone());
+        let hub = HubClient::new(&env, &links(&env).hub);
+        let total_tables = hub.get_total_tables();
+        let total_requests = hub.get_total_requests();
+        
+        (total_tracks, total_tables, total_requests)
+    }
+
+    // Running totals kept since this version; volume, royalties and fees are in
+    // the default token
+    pub fn get_extended_stats(env: Env) -> PlatformStats {
+        let (total_tracks, total_tables, total_requests) = Self::get_platform_stats(env.clone());
+        
+        PlatformStats {
+            total_tracks,
+            total_tables,
+            total_requests,
+            total_users: Self::get_stat(&env, StatsKey::Users) as u32,
+            total_artists: Self::get_stat(&env, StatsKey::Artists) as u32,
+            total_volume: Self::get_stat(&env, StatsKey::Volume),
+            total_royalties: Self::get_stat(&env, StatsKey::Royalties),
+            total_fees: Self::get_stat(&env, StatsKey::Fees),
+        }
+    }
+
+    fn get_stat(env: &Env, key: StatsKey) -> i128 {
+        env.storage().instance().get(&key).unwrap_or(0)
+    }
+
+    fn add_stat(env: &Env, key: StatsKey, amount: i128) {
+        if amount == 0 {
+            return;
+        }
+        match key {
+            StatsKey::Users => Self::update_daily_stats(env, |stats| stats.new_users += amount as u32),
+            StatsKey::Volume => Self::update_daily_stats(env, |stats| stats.volume += amount),
+            StatsKey::Fees => Self::update_daily_stats(env, |stats| stats.fees += amount),
+            _ => {}
+        }
+        let total: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(total + amount));
+    }
+
+    // Request volume from the hub and royalties and fees from the payouts and
+    // community contracts, all in the default token
+    pub fn record_stats(env: Env, caller: Address, requests: u32, volume: i128, royalties: i128, fees: i128) {
+        require_sibling(&env, &caller);
+        
+        if requests > 0 {
+            Self::update_daily_stats(&env, |stats| stats.requests += requests);
+        }
+        Self::add_stat(&env, StatsKey::Volume, volume);
+        Self::add_stat(&env, StatsKey::Royalties, royalties);
+        Self::add_stat(&env, StatsKey::Fees, fees);
+    }
+
+    // Daily buckets are keyed by epoch day (UTC) and only ever touched for today
+    fn update_daily_stats(env: &Env, update: impl FnOnce(&mut DailyStats)) {
+        let day = env.ledger().timestamp() / 86400;
+        let mut stats = Self::get_daily_stats(env.clone(), day);
+        update(&mut stats);
+        env.storage().temporary().set(&StatsKey::Daily(day), &stats);
+        env.storage().temporary().extend_ttl(&StatsKey::Daily(day), DAILY_STATS_TTL / 2, DAILY_STATS_TTL);
+    }
+
+    pub fn get_daily_stats(env: Env, day: u64) -> DailyStats {
+        env.storage().temporary()
+            .get(&StatsKey::Daily(day))
+            .unwrap_or(DailyStats {
+                day,
+                requests: 0,
+                volume: 0,
+                fees: 0,
+                new_users: 0,
+                new_tracks: 0,
+            })
+    }
+
+    // One bucket per day from `from_day` through `to_day`, empty days included
+    pub fn get_stats_range(env: Env, from_day: u64, to_day: u64) -> Vec<DailyStats> {
+        if to_day < from_day || to_day - from_day >= MAX_STATS_RANGE_DAYS {
+            panic!("Invalid day range");
+        }
+        
+        let mut range = Vec::new(&env);
+        for day in from_day..=to_day {
+            range.push_back(Self::get_daily_stats(env.clone(), day));
+        }
+        range
+    }
+
+    // Feature flags
+    pub fn set_feature_flag(env: Env, flag: Symbol, enabled: bool, params: Vec<i128>) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        record_audit(&env, &admin, "feature_flag", AuditTarget::Platform);
+        
+        let mut flags: Map<Symbol, FeatureFlag> = env.storage().instance()
+            .get(&DataKey::FeatureFlags)
+            .unwrap_or(Map::new(&env));
+        
+        flags.set(flag.clone(), FeatureFlag {
+            enabled,
+            params,
+            updated_at: env.ledger().timestamp(),
+        });
+        env.storage().instance().set(&DataKey::FeatureFlags, &flags);
+        
+        env.events().publish(
+            (Symbol::new(&env, "feature_flag_changed"), EVENT_SCHEMA_VERSION, flag),
+            enabled
+        );
+    }
+
+    pub fn remove_feature_flag(env: Env, flag: Symbol) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        let mut flags: Map<Symbol, FeatureFlag> = env.storage().instance()
+            .get(&DataKey::FeatureFlags)
+            .unwrap_or(Map::new(&env));
+        
+        if flags.remove(flag.clone()).is_none() {
+            panic!("Feature flag not found");
+        }
+        env.storage().instance().set(&DataKey::FeatureFlags, &flags);
+        
+        env.events().publish(
+            (Symbol::new(&env, "feature_flag_changed"), EVENT_SCHEMA_VERSION, flag),
+            false
+        );
+    }
+
+    pub fn get_feature_flag(env: Env, flag: Symbol) -> Option<FeatureFlag> {
+        let flags: Map<Symbol, FeatureFlag> = env.storage().instance()
+            .get(&DataKey::FeatureFlags)
+            .unwrap_or(Map::new(&env));
+        flags.get(flag)
+    }
+
+    pub fn get_feature_flags(env: Env) -> Map<Symbol, FeatureFlag> {
+        env.storage().instance()
+            .get(&DataKey::FeatureFlags)
+            .unwrap_or(Map::new(&env))
+    }
+
+    pub fn is_flag_enabled(env: Env, flag: Symbol) -> bool {
+        Self::get_feature_flag(env, flag)
+            .map(|f| f.enabled)
+            .unwrap_or(false)
+    }
+
+    fn require_flag(env: &Env, flag: &str) {
+        if !Self::is_flag_enabled(env.clone(), Symbol::new(env, flag)) {
+            panic!("Feature not rolled out");
+        }
+    }
+
+    // Legacy data import
+    pub fn set_import_paused(env: Env, paused: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if Self::get_import_state(env.clone()) == ImportState::Finished {
+            panic!("Import already finished");
+        }
+        
+        let state = if paused { ImportState::Paused } else { ImportState::Open };
+        env.storage().instance().set(&DataKey::ImportState, &state);
+        
+        env.events().publish(
+            (Symbol::new(&env, "import_state_changed"), EVENT_SCHEMA_VERSION),
+            state
+        );
+    }
+
+    pub fn finish_import(env: Env) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        env.storage().instance().set(&DataKey::ImportState, &ImportState::Finished);
+        
+        env.events().publish(
+            (Symbol::new(&env, "import_finished"), EVENT_SCHEMA_VERSION),
+            env.ledger().timestamp()
+        );
+    }
+
+    pub fn get_import_state(env: Env) -> ImportState {
+        env.storage().instance()
+            .get(&DataKey::ImportState)
+            .unwrap_or(ImportState::Open)
+    }
+
+    pub fn import_legacy(env: Env, entries: Vec<ImportEntry>) -> u32 {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        match Self::get_import_state(env.clone()) {
+            ImportState::Open => {}
+            ImportState::Paused => panic!("Import paused"),
+            ImportState::Finished => panic!("Import already finished"),
+        }
+        
+        let mut imported = 0;
+        for entry in entries.iter() {
+            // Entries already present are skipped so a batch can be safely resubmitted
+            let (kind, was_imported) = match entry {
+                ImportEntry::User(legacy) => (Symbol::new(&env, "user"), Self::import_user(&env, legacy)),
+                ImportEntry::Artist(legacy) => (Symbol::new(&env, "artist"), Self::import_artist(&env, legacy)),
+                ImportEntry::Track(legacy) => (Symbol::new(&env, "track"), Self::import_track(&env, legacy)),
+            };
+            
+            if was_imported {
+                imported += 1;
+            }
+            
+            env.events().publish(
+                (Symbol::new(&env, "legacy_import"), EVENT_SCHEMA_VERSION, kind),
+                was_imported
+            );
+        }
+        
+        imported
+    }
+
+    fn import_user(env: &Env, legacy: LegacyUser) -> bool {
+        if env.storage().persistent().has(&DataKey::Users(legacy.user.clone())) {
+            return false;
+        }
+        
+        // Imported users get a fresh internal profile; the legacy profile_nft is not carried over
+        let user = User {
+            profile_id: Self::mint_profile(env, &legacy.user),
+            avatar_uri: legacy.avatar_uri,
+            reputation: legacy.reputation,
+            is_active: true,
+        };
+        
+        Self::save_user(env, &legacy.user, &user);
+        true
+    }
+
+    fn import_artist(env: &Env, legacy: LegacyArtist) -> bool {
+        if !env.storage().persistent().has(&DataKey::Users(legacy.user.clone()))
+            || env.storage().instance().has(&DataKey::Artists(legacy.user.clone()))
+        {
+            return false;
+        }
+        
+        let artist = Artist {
+            user_id: legacy.user.clone(),
+            artist_name: legacy.artist_name,
+            verified: legacy.verified,
+            follower_count: 0,
+        };
+        
+        env.storage().instance().set(&DataKey::Artists(legacy.user.clone()), &artist);
+        Self::index_directory(env, DirectoryKey::ArtistDirectory, &legacy.user, true);
+        if legacy.verified {
+            Self::index_directory(env, DirectoryKey::VerifiedDirectory, &legacy.user, true);
+        }
+        true
+    }
+
+    fn import_track(env: &Env, legacy: LegacyTrack) -> bool {
+        if !env.storage().instance().has(&DataKey::Artists(legacy.artist_id.clone()))
+            || env.storage().instance().has(&DataKey::Tracks(legacy.track_id.clone()))
+        {
+            return false;
+        }
+        
+        let mut total_split = 0;
+        for (_, percentage) in legacy.royalty_split.iter() {
+            total_split += percentage;
+        }
+        if total_split != 100 {
+            return false;
+        }
+        
+        let track = Track {
+            track_id: legacy.track_id.clone(),
+            track_nft: legacy.track_nft,
+            title: legacy.title,
+            artist_id: legacy.artist_id.clone(),
+            collaborators: legacy.collaborators,
+            play_count: legacy.play_count,
+            base_price: legacy.base_price,
+            licenses_remaining: legacy.licenses_remaining,
+            metadata_uri: legacy.metadata_uri,
+            royalty_split: legacy.royalty_split,
+            price_curve: PriceCurve::Fixed,
+            licenses_sold: 0,
+            split_history: Vec::new(env),
+            favorite_count: 0,
+            tags: Vec::new(env),
+            charity: None,
+            charity_bps: 0,
+        };
+        
+        env.storage().instance().set(&DataKey::Tracks(legacy.track_id.clone()), &track);
+        Self::index_artist_track(env, &legacy.artist_id, &legacy.track_id);
+        
+        let track_counter: u32 = env.storage().instance()
+            .get(&DataKey::TrackIdCounter)
+            .unwrap_or(0);
+        env.storage().instance().set(&DataKey::TrackIdCounter, &(track_counter + 1));
+        true
+    }
+
+    // Storage TTL
+    // Profiles, spend and receipts follow the users target and queue entries the
+    // requests target; play and request history sit in temporary storage for the
+    // history target
+    pub fn set_ttl_policy(env: Env, policy: TtlPolicy) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        juke_common::set_ttl_policy(&env, &policy);
+        
+        env.events().publish(
+            (Symbol::new(&env, "ttl_policy_set"), EVENT_SCHEMA_VERSION),
+            policy
+        );
+    }
+
+    pub fn get_ttl_policy(env: Env) -> TtlPolicy {
+        ttl_policy(&env)
+    }
+
+    // Verification is gated by the admin or council on the governance contract,
+    // which audits it and then calls in here
+    pub fn set_artist_verified(env: Env, artist: Address, verified: bool) {
+        links(&env).governance.require_auth();
+        
+        let mut artist_data: Artist = env.storage().instance()
+            .get(&DataKey::Artists(artist.clone()))
+            .unwrap_or_else(|| panic!("Not registered as artist"));
+        
+        artist_data.verified = verified;
+        env.storage().instance().set(&DataKey::Artists(artist.clone()), &artist_data);
+        Self::index_directory(&env, DirectoryKey::VerifiedDirectory, &artist, verified);
+        
+        env.events().publish(
+            (Symbol::new(&env, "artist_verified"), EVENT_SCHEMA_VERSION, artist),
+            verified
+        );
+    }
+
+    // Artist directory, in registration (or verification) order
+    fn index_directory(env: &Env, key: DirectoryKey, artist: &Address, listed: bool) {
+        let mut artists: Vec<Address> = env.storage().instance().get(&key).unwrap_or(Vec::new(env));
+        
+        match artists.first_index_of(artist) {
+            Some(index) if !listed => {
+                artists.remove(index);
+            }
+            None if listed => artists.push_back(artist.clone()),
+            _ => return,
+        }
+        
+        env.storage().instance().set(&key, &artists);
+    }
+
+    pub fn list_artists(env: Env, start: u32, limit: u32) -> Vec<Address> {
+        Self::page_directory(&env, DirectoryKey::ArtistDirectory, start, limit)
+    }
+
+    pub fn list_verified_artists(env: Env, start: u32, limit: u32) -> Vec<Address> {
+        Self::page_directory(&env, DirectoryKey::VerifiedDirectory, start, limit)
+    }
+
+    fn page_directory(env: &Env, key: DirectoryKey, start: u32, limit: u32) -> Vec<Address> {
+        let artists: Vec<Address> = env.storage().instance().get(&key).unwrap_or(Vec::new(env));
+        
+        let mut page = Vec::new(env);
+        let end = start.saturating_add(limit).min(artists.len());
+        for i in start..end {
+            page.push_back(artists.get(i).unwrap());
+        }
+        page
+    }
+}
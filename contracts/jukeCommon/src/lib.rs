@@ -0,0 +1,488 @@
+#![no_std]
+#![allow(clippy::too_many_arguments)]
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{contractclient, contracttype, Address, Bytes, BytesN, Env, IntoVal, String, Symbol, Val, Vec};
+
+pub mod validation;
+pub use validation::ValidationError;
+
+// Juke is deployed as a set of linked contracts, each under the network's wasm
+// size limit: the catalog (users, artists, tracks), the hub (tables, requests and
+// queues), payouts (fees, royalties and the treasury), the market (licenses and
+// subscriptions), community (charts, sessions, ads), venue (kiosks, vouchers,
+// albums and campaigns) and governance (council, timelock, moderation and
+// disputes). This crate holds what they share.
+
+// ----- Shared Data Structures -----
+#[contracttype]
+#[derive(Clone)]
+pub struct User {
+    pub profile_id: u32,
+    pub avatar_uri: String,
+    pub reputation: u32,
+    pub is_active: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Artist {
+    pub user_id: Address,
+    pub artist_name: String,
+    pub verified: bool,
+    pub follower_count: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct JukeboxTable {
+    pub table_id: BytesN<32>,
+    pub name: String,
+    pub owner: Address,
+    pub skip_threshold: u32,
+    pub price_multiplier: u32,
+    pub member_count: u32,
+    pub is_active: bool,
+    pub free_requests_per_day: u32,
+    pub skip_fee: i128,
+    pub skip_fee_requester_bps: u32,
+    pub category: Symbol,
+    pub tags: Vec<Symbol>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Track {
+    pub track_id: BytesN<32>,
+    pub track_nft: Address,
+    pub title: String,
+    pub artist_id: Address,
+    pub collaborators: Vec<Address>,
+    pub play_count: u32,
+    pub base_price: i128,
+    pub licenses_remaining: u32,
+    pub metadata_uri: String,
+    pub royalty_split: Vec<(Address, u32)>,
+    pub price_curve: PriceCurve,
+    pub licenses_sold: u32,
+    pub split_history: Vec<SplitChange>,
+    pub favorite_count: u32,
+    pub tags: Vec<Symbol>,
+    // Allowlisted cause and its bps of the royalties, taken before the split
+    pub charity: Option<Address>,
+    pub charity_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum PriceCurve {
+    Fixed,
+    Linear(i128),
+    Exponential(u32),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SplitChange {
+    pub split: Vec<(Address, u32)>,
+    pub play_count: u32,
+    pub replaced_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TrackRequest {
+    pub request_id: BytesN<32>,
+    pub requester: Address,
+    pub track_id: BytesN<32>,
+    pub table_id: BytesN<32>,
+    pub timestamp: u64,
+    pub amount_paid: i128,
+    pub payment_token: Address,
+}
+
+// What settling a request credited: accruals, vesting locks and the treasury
+// fee. A dispute claws back exactly these amounts.
+#[contracttype]
+#[derive(Clone)]
+pub struct Settlement {
+    pub shares: Vec<(Address, i128)>,
+    pub vested: Vec<(Address, i128)>,
+    pub fee: i128,
+    pub settled_at: u64,
+}
+
+// Fee parameters governance queues behind its timelock and payouts applies
+#[contracttype]
+#[derive(Clone)]
+pub enum ParamChange {
+    PlatformFee(u32),
+    FeeTiers(Vec<FeeTier>),
+    FeeOverride(FeeClass, Option<u32>),
+    TimelockDelay(u64),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum TreasuryTarget {
+    Wallet(Address),
+    GrantPool,
+    Burn,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct FeeTier {
+    pub min_volume: u32,
+    pub fee_bps: u32,
+}
+
+// Table classes that can carry their own platform fee
+#[contracttype]
+#[derive(Clone)]
+pub enum FeeClass {
+    Category(Symbol),
+    VerifiedOwnTable,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct DeploymentProfile {
+    pub branding: Symbol,
+    pub default_free_requests: u32,
+    pub default_skip_fee: i128,
+    pub max_queue_length: u32,
+    pub features: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TrackMintParams {
+    pub title: String,
+    pub base_price: i128,
+    pub licenses: u32,
+    pub metadata_uri: String,
+    pub collaborators: Vec<Address>,
+    pub royalty_split: Vec<(Address, u32)>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TtlPolicy {
+    pub users: u32,
+    pub requests: u32,
+    pub history: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuditTarget {
+    Platform,
+    Account(Address),
+    Track(BytesN<32>),
+    Report(u32),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct AuditEntry {
+    pub entry_id: u32,
+    pub actor: Address,
+    pub action: Symbol,
+    pub target: AuditTarget,
+    pub timestamp: u64,
+}
+
+// Addresses of every contract in a deployment, set once at initialize
+#[contracttype]
+#[derive(Clone)]
+pub struct Links {
+    pub catalog: Address,
+    pub hub: Address,
+    pub payouts: Address,
+    pub market: Address,
+    pub community: Address,
+    pub venue: Address,
+    pub governance: Address,
+}
+
+// Keys every contract keeps in its own instance storage. Variant names must not
+// be reused by a contract's own key enums.
+#[contracttype]
+enum CommonKey {
+    Links,
+    TtlPolicy,
+    AuditCount,
+    AuditLog(u32),
+}
+
+// TTL extension targets in ledgers (~5s each); the per-entry ones can be
+// overridden through each contract's TTL policy
+pub const INSTANCE_TTL: u32 = 535_680;
+pub const DEFAULT_TTL_USERS: u32 = 518_400;
+pub const DEFAULT_TTL_REQUESTS: u32 = 17_280;
+pub const DEFAULT_TTL_HISTORY: u32 = 120_960;
+// Keep in step with each contract's binver contractmeta
+pub const CONTRACT_VERSION: (u32, u32, u32) = (0, 1, 0);
+// Second topic of every event; bumped whenever any event's topics or payload change shape
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+pub const LEDGER_SECS: u64 = 5;
+
+pub const FEATURE_FREE_REQUESTS: u32 = 1 << 0;
+pub const FEATURE_SKIP_FEES: u32 = 1 << 1;
+pub const FEATURE_PROMOS: u32 = 1 << 2;
+pub const FEATURE_HAPPY_HOUR: u32 = 1 << 3;
+
+pub const PAUSE_MINTING: u32 = 1 << 0;
+pub const PAUSE_REQUESTS: u32 = 1 << 1;
+pub const PAUSE_JOINS: u32 = 1 << 2;
+pub const PAUSE_WITHDRAWALS: u32 = 1 << 3;
+pub const PAUSE_ALL: u32 = PAUSE_MINTING | PAUSE_REQUESTS | PAUSE_JOINS | PAUSE_WITHDRAWALS;
+
+// ----- Links -----
+pub fn set_links(env: &Env, links: &Links) {
+    env.storage().instance().set(&CommonKey::Links, links);
+}
+
+pub fn links(env: &Env) -> Links {
+    env.storage().instance()
+        .get(&CommonKey::Links)
+        .unwrap_or_else(|| panic!("Contract not initialized"))
+}
+
+// Entry points only a linked contract may call take the caller's address, which
+// must be one of the links and authorize the call (automatic for the invoker)
+pub fn require_sibling(env: &Env, caller: &Address) {
+    let links = links(env);
+    let linked = [
+        &links.catalog,
+        &links.hub,
+        &links.payouts,
+        &links.market,
+        &links.community,
+        &links.venue,
+        &links.governance,
+    ];
+    if !linked.contains(&caller) {
+        panic!("Not a linked contract");
+    }
+    caller.require_auth();
+}
+
+// Pause scopes and accounts live on the catalog; the other contracts ask it
+pub fn require_not_paused(env: &Env, scope: u32) {
+    if CatalogClient::new(env, &links(env).catalog).get_paused() & scope != 0 {
+        panic!("Paused");
+    }
+}
+
+pub fn require_active_user(env: &Env, user: &Address) {
+    CatalogClient::new(env, &links(env).catalog).require_active_user(user);
+}
+
+pub fn require_flag(env: &Env, flag: &str) {
+    if !CatalogClient::new(env, &links(env).catalog).is_flag_enabled(&Symbol::new(env, flag)) {
+        panic!("Feature not rolled out");
+    }
+}
+
+// ----- Storage lifetimes -----
+// Instance entries share one TTL, so extend it once it has used up half the target
+pub fn bump_instance(env: &Env) {
+    env.storage().instance().extend_ttl(INSTANCE_TTL / 2, INSTANCE_TTL);
+}
+
+// Persistent entries each carry their own TTL, on the same half-spent rule
+pub fn bump_persistent<K: IntoVal<Env, Val>>(env: &Env, key: &K, extend_to: u32) {
+    env.storage().persistent().extend_ttl(key, extend_to / 2, extend_to);
+}
+
+pub fn bump_temporary<K: IntoVal<Env, Val>>(env: &Env, key: &K, extend_to: u32) {
+    env.storage().temporary().extend_ttl(key, extend_to / 2, extend_to);
+}
+
+pub fn set_ttl_policy(env: &Env, policy: &TtlPolicy) {
+    let max_ttl = env.storage().max_ttl();
+    for ledgers in [policy.users, policy.requests, policy.history] {
+        if ledgers == 0 || ledgers > max_ttl {
+            panic!("Invalid TTL target");
+        }
+    }
+    env.storage().instance().set(&CommonKey::TtlPolicy, policy);
+}
+
+pub fn ttl_policy(env: &Env) -> TtlPolicy {
+    env.storage().instance()
+        .get(&CommonKey::TtlPolicy)
+        .unwrap_or(TtlPolicy {
+            users: DEFAULT_TTL_USERS,
+            requests: DEFAULT_TTL_REQUESTS,
+            history: DEFAULT_TTL_HISTORY,
+        })
+}
+
+// sha256(tag || ledger sequence || timestamp || counter || caller XDR). The
+// per-kind counter keeps ids unique; the tag keeps kinds apart
+pub fn derive_id(env: &Env, tag: &[u8], counter: u32, caller: &Address) -> BytesN<32> {
+    let mut bytes = Bytes::from_slice(env, tag);
+    bytes.extend_from_array(&env.ledger().sequence().to_be_bytes());
+    bytes.extend_from_array(&env.ledger().timestamp().to_be_bytes());
+    bytes.extend_from_array(&counter.to_be_bytes());
+    bytes.append(&caller.clone().to_xdr(env));
+    env.crypto().sha256(&bytes).into()
+}
+
+// ----- Audit log -----
+// Each contract logs its own privileged actions. Entries are persistent and kept
+// for the history target; only the counter lives in instance storage
+pub fn record_audit(env: &Env, actor: &Address, action: &str, target: AuditTarget) {
+    let entry_id = audit_count(env);
+    let entry = AuditEntry {
+        entry_id,
+        actor: actor.clone(),
+        action: Symbol::new(env, action),
+        target,
+        timestamp: env.ledger().timestamp(),
+    };
+    let entry_key = CommonKey::AuditLog(entry_id);
+    env.storage().persistent().set(&entry_key, &entry);
+    bump_persistent(env, &entry_key, ttl_policy(env).history);
+    env.storage().instance().set(&CommonKey::AuditCount, &(entry_id + 1));
+}
+
+pub fn audit_count(env: &Env) -> u32 {
+    env.storage().instance().get(&CommonKey::AuditCount).unwrap_or(0)
+}
+
+pub fn audit_log(env: &Env, start: u32, limit: u32) -> Vec<AuditEntry> {
+    let mut entries = Vec::new(env);
+    let end = start.saturating_add(limit).min(audit_count(env));
+    for entry_id in start..end {
+        entries.push_back(env.storage().persistent().get(&CommonKey::AuditLog(entry_id)).unwrap());
+    }
+    entries
+}
+
+// ----- Sibling interfaces -----
+// Only the entry points the contracts call on each other; `caller` arguments are
+// checked with require_sibling on the receiving side
+#[contractclient(name = "CatalogClient")]
+pub trait Catalog {
+    fn get_paused(env: Env) -> u32;
+    fn require_active_user(env: Env, user: Address);
+    fn is_flag_enabled(env: Env, flag: Symbol) -> bool;
+    fn get_user(env: Env, user: Address) -> Option<User>;
+    fn get_artist(env: Env, artist: Address) -> Option<Artist>;
+    fn get_track(env: Env, track_id: BytesN<32>) -> Option<Track>;
+    fn get_reputation(env: Env, user: Address) -> u32;
+    fn get_public_after(env: Env, track_id: BytesN<32>) -> Option<u64>;
+    fn get_minted_at(env: Env, track_id: BytesN<32>) -> Option<u64>;
+    fn get_artist_tracks(env: Env, artist: Address) -> Vec<BytesN<32>>;
+    fn is_track_taken_down(env: Env, track_id: BytesN<32>) -> bool;
+    fn require_track_live(env: Env, track_id: BytesN<32>);
+    fn get_current_price(env: Env, track_id: BytesN<32>) -> i128;
+    fn default_to_token(env: Env, amount: i128, token: Address) -> i128;
+    fn accepts_token(env: Env, table_id: BytesN<32>, token: Address) -> bool;
+    fn set_artist_verified(env: Env, artist: Address, verified: bool);
+    fn upgrade(env: Env, new_wasm_hash: BytesN<32>);
+    fn adjust_reputation(env: Env, caller: Address, user: Address, delta: i32, reason: Symbol);
+    fn record_request_outcome(env: Env, caller: Address, user: Address, played: bool);
+    fn slash_kicked(env: Env, caller: Address, user: Address);
+    fn save_track(env: Env, caller: Address, track: Track);
+    fn record_stats(env: Env, caller: Address, requests: u32, volume: i128, royalties: i128, fees: i128);
+    fn mint_for(env: Env, caller: Address, artist: Address, params: TrackMintParams, cosign: bool) -> BytesN<32>;
+}
+
+#[contractclient(name = "HubClient")]
+pub trait Hub {
+    fn get_total_tables(env: Env) -> u32;
+    fn get_total_requests(env: Env) -> u32;
+    fn get_table(env: Env, table_id: BytesN<32>) -> Option<JukeboxTable>;
+    fn get_request(env: Env, request_id: BytesN<32>) -> Option<TrackRequest>;
+    fn is_table_member(env: Env, user: Address, table_id: BytesN<32>) -> bool;
+    fn leave_all_tables(env: Env, caller: Address, user: Address);
+    fn purge_track(env: Env, caller: Address, track_id: BytesN<32>);
+    fn get_request_price(env: Env, track_id: BytesN<32>, table_id: BytesN<32>) -> i128;
+    fn get_wallet_balance(env: Env, user: Address) -> i128;
+    fn get_token_credit(env: Env, user: Address) -> i128;
+    fn get_queue(env: Env, table_id: BytesN<32>) -> Vec<BytesN<32>>;
+    fn get_profile(env: Env) -> DeploymentProfile;
+    fn place_request_for(
+        env: Env,
+        caller: Address,
+        payer: Address,
+        requester: Address,
+        track_id: BytesN<32>,
+        table_id: BytesN<32>,
+        discount_bps: u32,
+    ) -> BytesN<32>;
+    fn advance_for(env: Env, caller: Address, table_id: BytesN<32>) -> Option<BytesN<32>>;
+    fn credit_token(env: Env, caller: Address, user: Address, amount: i128);
+    fn credit_requests(env: Env, caller: Address, user: Address, count: u32);
+}
+
+#[contractclient(name = "PayoutsClient")]
+pub trait Payouts {
+    fn get_platform_fee(env: Env) -> u32;
+    fn upgrade(env: Env, new_wasm_hash: BytesN<32>);
+    fn apply_param_change(env: Env, caller: Address, change: ParamChange);
+    fn apply_treasury_targets(env: Env, caller: Address, targets: Vec<(TreasuryTarget, u32)>);
+    fn hold_settlement(env: Env, caller: Address, request_id: BytesN<32>, track_id: BytesN<32>) -> Settlement;
+    fn release_settlement(env: Env, caller: Address, track_id: BytesN<32>, held: Settlement, refund_to: Option<Address>);
+    fn get_settlement(env: Env, request_id: BytesN<32>) -> Option<Settlement>;
+    fn requires_default_token(env: Env, track_id: BytesN<32>) -> bool;
+    fn get_swap_router(env: Env) -> Option<Address>;
+    fn settle_request(env: Env, caller: Address, request: TrackRequest, table: JukeboxTable) -> Option<Settlement>;
+    fn settle_sale(env: Env, caller: Address, track_id: BytesN<32>, amount: i128);
+    fn accrue_royalty(env: Env, caller: Address, recipient: Address, track_id: BytesN<32>, amount: i128);
+    fn collect_artist_payment(env: Env, caller: Address, artist: Address, amount: i128);
+    fn credit_treasury(env: Env, caller: Address, amount: i128);
+    fn pay_from_treasury(env: Env, caller: Address, recipient: Address, amount: i128) -> i128;
+    fn credit_table_treasury(env: Env, caller: Address, table_id: BytesN<32>, amount: i128);
+    fn record_referral(env: Env, caller: Address, user: Address, referrer: Address);
+}
+
+#[contractclient(name = "CommunityClient")]
+pub trait Community {
+    fn meets_entry_badges(env: Env, table_id: BytesN<32>, user: Address) -> bool;
+    fn record_request(env: Env, caller: Address, request: TrackRequest, artist: Address, play_count: u32, default_paid: i128);
+    fn record_play(env: Env, caller: Address, request: TrackRequest, skipped: bool);
+    fn start_track(env: Env, caller: Address, table: JukeboxTable, request: TrackRequest);
+    fn record_join(env: Env, caller: Address, table_id: BytesN<32>, user: Address, member_count: u32, first_visit: bool);
+    fn check_in_for(env: Env, caller: Address, user: Address, table_id: BytesN<32>, session_id: u32);
+    fn unlock_achievement(env: Env, caller: Address, user: Address, achievement: Symbol);
+    fn record_top_artist(env: Env, caller: Address, artist: Address, gross_revenue: i128);
+}
+
+#[contractclient(name = "MarketClient")]
+pub trait Market {
+    fn has_early_access(env: Env, user: Address, track_id: BytesN<32>) -> bool;
+    fn get_exclusive_holder(env: Env, track_id: BytesN<32>) -> Option<Address>;
+    fn is_license_valid(env: Env, holder: Address, track_id: BytesN<32>) -> bool;
+    fn get_auction_licenses(env: Env, holder: Address, track_id: BytesN<32>) -> u32;
+    fn get_takeover_price(env: Env, table_id: BytesN<32>, artist: Address) -> Option<i128>;
+    fn record_request(env: Env, caller: Address, request: TrackRequest, artist: Address, default_paid: i128, insured: bool);
+    fn close_coverage(env: Env, caller: Address, request: TrackRequest, claim: bool);
+    fn award_loyalty_points(env: Env, caller: Address, user: Address, amount_paid: i128);
+    fn spend_loyalty_points(env: Env, caller: Address, user: Address, table_id: BytesN<32>) -> u64;
+}
+
+// Whitelisted AMM router. It pulls `amount_in` of `token_in` from `from` using an
+// allowance, swaps it, and sends at least `min_amount_out` of `token_out` to `to`.
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    fn swap_exact_in(
+        env: Env,
+        from: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        to: Address,
+    ) -> i128;
+}
+
+// Every contract but governance takes its upgrades from governance's council
+#[contractclient(name = "UpgradeableClient")]
+pub trait Upgradeable {
+    fn upgrade(env: Env, new_wasm_hash: BytesN<32>);
+}
@@ -0,0 +1,60 @@
+use soroban_sdk::{contracterror, panic_with_error, Env, String};
+
+// Input checks for the user-supplied strings clients render. Failures surface as
+// contract errors so callers can tell them apart from other panics.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ValidationError {
+    EmptyName = 1,
+    NameTooLong = 2,
+    EmptyUri = 3,
+    UriTooLong = 4,
+    UnsupportedUriScheme = 5,
+}
+
+pub const MAX_NAME_LEN: u32 = 64;
+pub const MAX_URI_LEN: u32 = 256;
+
+const URI_SCHEMES: [&[u8]; 3] = [b"ipfs://", b"ar://", b"https://"];
+
+// Names and titles
+pub fn check_name(name: &String) -> Result<(), ValidationError> {
+    if name.is_empty() {
+        return Err(ValidationError::EmptyName);
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(ValidationError::NameTooLong);
+    }
+    Ok(())
+}
+
+// Metadata and avatar URIs
+pub fn check_uri(uri: &String) -> Result<(), ValidationError> {
+    if uri.is_empty() {
+        return Err(ValidationError::EmptyUri);
+    }
+    if uri.len() > MAX_URI_LEN {
+        return Err(ValidationError::UriTooLong);
+    }
+
+    let mut buf = [0u8; MAX_URI_LEN as usize];
+    let bytes = &mut buf[..uri.len() as usize];
+    uri.copy_into_slice(bytes);
+    if !URI_SCHEMES.iter().any(|scheme| bytes.starts_with(scheme)) {
+        return Err(ValidationError::UnsupportedUriScheme);
+    }
+    Ok(())
+}
+
+pub fn require_name(env: &Env, name: &String) {
+    if let Err(err) = check_name(name) {
+        panic_with_error!(env, err);
+    }
+}
+
+pub fn require_uri(env: &Env, uri: &String) {
+    if let Err(err) = check_uri(uri) {
+        panic_with_error!(env, err);
+    }
+}
@@ -0,0 +1,1804 @@
+#![no_std]
+#![allow(clippy::too_many_arguments)]
+use juke_common::{
+    bump_persistent, bump_temporary, links, require_active_user, require_sibling, ttl_policy, validation, CatalogClient,
+    HubClient, JukeboxTable, Links, PayoutsClient, TrackRequest, TtlPolicy, CONTRACT_VERSION, EVENT_SCHEMA_VERSION,
+};
+use soroban_sdk::{contract, contractimpl, contractmeta, contracttype, token, Address, Bytes, BytesN, Env, Map, String, Symbol, Vec};
+
+contractmeta!(key = "binver", val = "0.1.0");
+
+// ----- Data Structures -----
+#[contracttype]
+#[derive(Clone)]
+pub struct Badge {
+    badge: Symbol,
+    issuer: Address,
+    awarded_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum OutboxMessage {
+    TrackStarted(BytesN<32>, BytesN<32>),
+    MemberMilestone(u32),
+    Custom(Symbol, Bytes),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct OutboxEntry {
+    pub seq: u32,
+    pub table_id: BytesN<32>,
+    pub message: OutboxMessage,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Receipt {
+    pub receipt_id: u32,
+    pub owner: Address,
+    pub request_id: BytesN<32>,
+    pub track_id: BytesN<32>,
+    pub table_id: BytesN<32>,
+    pub played_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TableSession {
+    pub session_id: u32,
+    pub table_id: BytesN<32>,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub attendee_count: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct AttendanceBadge {
+    pub table_id: BytesN<32>,
+    pub session_id: u32,
+    pub checked_in_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq)]
+pub enum LeaderboardKind {
+    TopRequesters,
+    TopTracks,
+}
+
+#[contracttype]
+#[derive(Clone, PartialEq)]
+pub enum LeaderboardSubject {
+    Requester(Address),
+    Track(BytesN<32>),
+    Artist(Address),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct LeaderboardEntry {
+    pub subject: LeaderboardSubject,
+    pub score: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TrendingTrack {
+    pub track_id: BytesN<32>,
+    pub plays: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PlayHistoryEntry {
+    pub track_id: BytesN<32>,
+    pub request_id: BytesN<32>,
+    pub played_at: u64,
+    pub skipped: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompetitionMetric {
+    NewTrackPlays,
+    TopRequester,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Competition {
+    pub competition_id: u32,
+    pub organizer: Address,
+    pub platform_wide: bool,
+    pub table_id: BytesN<32>,
+    pub metric: CompetitionMetric,
+    pub prize_pool: i128,
+    pub prize_shares: Vec<u32>,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub finalized: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TableAdConfig {
+    pub cadence: u32,
+    pub slot_price: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct AdBooking {
+    pub booking_id: u32,
+    pub sponsor: Address,
+    pub table_id: BytesN<32>,
+    pub content_hash: BytesN<32>,
+    pub uri: String,
+    pub slot_price: i128,
+    pub slots_remaining: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SponsorshipStatus {
+    Proposed,
+    Active,
+    Ended,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Sponsorship {
+    sponsorship_id: u32,
+    sponsor: Address,
+    table_id: BytesN<32>,
+    amount: i128,
+    duration_secs: u64,
+    branding_uri: String,
+    penalty_bps: u32,
+    starts_at: u64,
+    claimed: i128,
+    status: SponsorshipStatus,
+}
+
+// ----- Storage Keys -----
+// Keys encode as the variant name plus its fields, without the enum name, so a
+// variant must not reuse a name and field types already taken by another key enum
+// here or in juke_common.
+#[contracttype]
+enum DataKey {
+    Admin,
+    TokenStellar,
+    Badges(Address, Symbol),
+    BadgeIssuers(Address),
+    EntryBadges(BytesN<32>),
+}
+
+#[contracttype]
+enum FavoriteKey {
+    Tables(Address),
+}
+
+#[contracttype]
+enum DiscoveryKey {
+    TopTracks,
+    TopArtists,
+}
+
+#[contracttype]
+enum HistoryKey {
+    Count(BytesN<32>),
+    Plays(BytesN<32>, u32),
+    UserRequestCount(Address),
+    UserRequests(Address, u32),
+    UserSpend(Address),
+}
+
+#[contracttype]
+enum TrendingKey {
+    Buckets(u64),
+}
+
+#[contracttype]
+enum LeaderboardKey {
+    Epoch(BytesN<32>),
+    Boards(BytesN<32>, u32, LeaderboardKind),
+    Scores(BytesN<32>, u32, LeaderboardSubject),
+}
+
+#[contracttype]
+enum AchievementKey {
+    Unlocked(Address),
+    Requests(Address),
+    TablesJoined(Address),
+}
+
+#[contracttype]
+enum SessionKey {
+    Counter(BytesN<32>),
+    Sessions(BytesN<32>, u32),
+    // Attendance is persistent, one entry per check-in: the session's attendee_count
+    // and the user's AttendedCount index them
+    Attendee(BytesN<32>, u32, u32),
+    CheckedIn(BytesN<32>, u32, Address),
+    Attended(Address, u32),
+    AttendedCount(Address),
+}
+
+#[contracttype]
+enum ReceiptKey {
+    ReceiptCounter,
+    Receipts(u32),
+    OwnedCount(Address),
+    Owned(Address, u32),
+}
+
+#[contracttype]
+enum OutboxKey {
+    Entries(BytesN<32>, u32),
+    Head(BytesN<32>),
+    Tail(BytesN<32>),
+    Relayers(BytesN<32>, Address),
+}
+
+#[contracttype]
+enum CompetitionKey {
+    CompetitionCounter,
+    Competitions(u32),
+    ActiveCompetitions,
+    Standings(u32),
+    Entries(u32, LeaderboardSubject),
+}
+
+#[contracttype]
+enum AdKey {
+    AdConfig(BytesN<32>),
+    BookingCounter,
+    Bookings(u32),
+    TableBookings(BytesN<32>),
+    TracksSinceAd(BytesN<32>),
+    CurrentAd(BytesN<32>),
+}
+
+#[contracttype]
+enum SponsorshipKey {
+    SponsorshipCounter,
+    Sponsorships(u32),
+    TableSponsorship(BytesN<32>),
+}
+
+const OUTBOX_CAPACITY: u32 = 200;
+const LEADERBOARD_SIZE: u32 = 20;
+const TRENDING_WINDOW_DAYS: u64 = 7;
+const TRENDING_BUCKET_CAPACITY: u32 = 200;
+// Buckets are temporary and outlive the window by a day (17,280 ledgers)
+const TRENDING_BUCKET_TTL: u32 = (TRENDING_WINDOW_DAYS as u32 + 1) * 17_280;
+// Each list is one persistent entry rewritten on every change, so it stays bounded
+const MAX_FAVORITES: u32 = 100;
+// Every request updates each running competition, so both stay small
+const MAX_ACTIVE_COMPETITIONS: u32 = 10;
+const MAX_PRIZE_RANKS: u32 = 10;
+const MAX_AD_BOOKINGS: u32 = 10;
+
+// Charts, history, achievements, sessions, badges, ads, sponsorships and the
+// relayer outbox
+#[contract]
+pub struct JukeCommunity;
+
+#[contractimpl]
+impl JukeCommunity {
+    pub fn initialize(env: Env, admin: Address, token_stellar: Address, links: Links) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::TokenStellar, &token_stellar);
+        juke_common::set_links(&env, &links);
+        
+        env.events().publish(
+            (Symbol::new(&env, "initialized"), EVENT_SCHEMA_VERSION),
+            (admin, token_stellar)
+        );
+    }
+
+    pub fn get_version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
+
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        links(&env).governance.require_auth();
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    // Play and request history sit in temporary storage for the history target;
+    // spend, attendance and receipts follow the users target
+    pub fn set_ttl_policy(env: Env, policy: TtlPolicy) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        juke_common::set_ttl_policy(&env, &policy);
+        
+        env.events().publish(
+            (Symbol::new(&env, "ttl_policy_set"), EVENT_SCHEMA_VERSION),
+            policy
+        );
+    }
+
+    pub fn get_ttl_policy(env: Env) -> TtlPolicy {
+        ttl_policy(&env)
+    }
+
+    // Linked contracts
+    // The hub reports every request, play and join here as it happens
+    pub fn record_request(
+        env: Env,
+        caller: Address,
+        request: TrackRequest,
+        artist: Address,
+        play_count: u32,
+        default_paid: i128,
+    ) {
+        require_sibling(&env, &caller);
+        
+        let requester = &request.requester;
+        Self::index_user_request(&env, requester, &request.request_id, default_paid);
+        Self::record_platform_top(&env, DiscoveryKey::TopTracks, LeaderboardSubject::Track(request.track_id.clone()), play_count as i128);
+        
+        let requests = Self::bump_achievement_counter(&env, AchievementKey::Requests(requester.clone()));
+        match requests {
+            1 => Self::grant_achievement(&env, requester, Symbol::new(&env, "first_request")),
+            100 => Self::grant_achievement(&env, requester, Symbol::new(&env, "hundred_requests")),
+            _ => {}
+        }
+        if play_count == 100 {
+            Self::grant_achievement(&env, &artist, Symbol::new(&env, "hundred_plays"));
+        }
+        
+        Self::record_leaderboard(&env, &request.table_id, LeaderboardKind::TopRequesters, LeaderboardSubject::Requester(requester.clone()), default_paid);
+        Self::record_leaderboard(&env, &request.table_id, LeaderboardKind::TopTracks, LeaderboardSubject::Track(request.track_id.clone()), 1);
+        Self::record_trending_play(&env, &request.track_id);
+        // Free plays would let anyone farm a prize pool
+        if request.amount_paid > 0 {
+            Self::record_competitions(&env, &request.table_id, requester, &request.track_id);
+        }
+    }
+
+    // A request that played through gets a receipt; a skipped one only history
+    pub fn record_play(env: Env, caller: Address, request: TrackRequest, skipped: bool) {
+        require_sibling(&env, &caller);
+        
+        Self::record_play_history(&env, &request.table_id, &request, skipped);
+        if !skipped {
+            Self::mint_receipt(&env, &request);
+        }
+    }
+
+    pub fn start_track(env: Env, caller: Address, table: JukeboxTable, request: TrackRequest) {
+        require_sibling(&env, &caller);
+        
+        Self::play_due_ad(&env, &table);
+        Self::enqueue_outbox(&env, &table.table_id, OutboxMessage::TrackStarted(request.track_id, request.request_id));
+    }
+
+    pub fn record_join(env: Env, caller: Address, table_id: BytesN<32>, user: Address, member_count: u32, first_visit: bool) {
+        require_sibling(&env, &caller);
+        
+        if matches!(member_count, 10 | 50 | 100 | 500 | 1000 | 5000) {
+            Self::enqueue_outbox(&env, &table_id, OutboxMessage::MemberMilestone(member_count));
+        }
+        
+        // Rejoining a table doesn't count towards ten_tables again
+        if first_visit && Self::bump_achievement_counter(&env, AchievementKey::TablesJoined(user.clone())) == 10 {
+            Self::grant_achievement(&env, &user, Symbol::new(&env, "ten_tables"));
+        }
+    }
+
+    pub fn unlock_achievement(env: Env, caller: Address, user: Address, achievement: Symbol) {
+        require_sibling(&env, &caller);
+        Self::grant_achievement(&env, &user, achievement);
+    }
+
+    pub fn record_top_artist(env: Env, caller: Address, artist: Address, gross_revenue: i128) {
+        require_sibling(&env, &caller);
+        Self::record_platform_top(&env, DiscoveryKey::TopArtists, LeaderboardSubject::Artist(artist), gross_revenue);
+    }
+
+    // Leaderboards
+    pub fn get_table_leaderboard(env: Env, table_id: BytesN<32>, kind: LeaderboardKind, limit: u32) -> Vec<LeaderboardEntry> {
+        let epoch = Self::get_leaderboard_epoch(env.clone(), table_id.clone());
+        let board: Vec<LeaderboardEntry> = env.storage().instance()
+            .get(&LeaderboardKey::Boards(table_id, epoch, kind))
+            .unwrap_or(Vec::new(&env));
+        
+        board.slice(0..limit.min(board.len()))
+    }
+
+    pub fn get_leaderboard_epoch(env: Env, table_id: BytesN<32>) -> u32 {
+        env.storage().instance().get(&LeaderboardKey::Epoch(table_id)).unwrap_or(0)
+    }
+
+    // Starts a fresh round of aggregates, e.g. for a weekly competition. Earlier
+    // rounds are left in storage rather than cleared entry by entry.
+    pub fn reset_table_leaderboard(env: Env, owner: Address, table_id: BytesN<32>) -> u32 {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&table_id.clone())
+            .unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        let epoch = Self::get_leaderboard_epoch(env.clone(), table_id.clone()) + 1;
+        env.storage().instance().set(&LeaderboardKey::Epoch(table_id.clone()), &epoch);
+        
+        env.events().publish(
+            (Symbol::new(&env, "leaderboard_reset"), EVENT_SCHEMA_VERSION, table_id),
+            epoch
+        );
+        
+        epoch
+    }
+
+    // Keeps only the top LEADERBOARD_SIZE entries sorted by score, so each update
+    // costs one bounded pass over the board
+    fn record_leaderboard(env: &Env, table_id: &BytesN<32>, kind: LeaderboardKind, subject: LeaderboardSubject, amount: i128) {
+        let epoch = Self::get_leaderboard_epoch(env.clone(), table_id.clone());
+        
+        let score_key = LeaderboardKey::Scores(table_id.clone(), epoch, subject.clone());
+        let score: i128 = env.storage().instance().get::<_, i128>(&score_key).unwrap_or(0) + amount;
+        env.storage().instance().set(&score_key, &score);
+        
+        let board_key = LeaderboardKey::Boards(table_id.clone(), epoch, kind);
+        let mut board: Vec<LeaderboardEntry> = env.storage().instance()
+            .get(&board_key)
+            .unwrap_or(Vec::new(env));
+        
+        if Self::rank_entry(&mut board, subject, score, LEADERBOARD_SIZE) {
+            env.storage().instance().set(&board_key, &board);
+        }
+    }
+
+    // Moves `subject` to its place for `score` on a board sorted best first and capped
+    // at `size`; returns false when it doesn't make the cut, in which case the caller
+    // should leave the stored board alone
+    fn rank_entry(board: &mut Vec<LeaderboardEntry>, subject: LeaderboardSubject, score: i128, size: u32) -> bool {
+        if let Some(index) = board.iter().position(|entry| entry.subject == subject) {
+            board.remove(index as u32);
+        }
+        
+        let position = board.iter()
+            .position(|entry| entry.score < score)
+            .map(|index| index as u32)
+            .unwrap_or(board.len());
+        if position >= size {
+            return false;
+        }
+        
+        board.insert(position, LeaderboardEntry { subject, score });
+        if board.len() > size {
+            board.pop_back();
+        }
+        true
+    }
+
+    // Platform-wide boards for the discovery homepage: tracks by plays, artists by gross revenue
+    pub fn get_top_tracks(env: Env, limit: u32) -> Vec<LeaderboardEntry> {
+        let board: Vec<LeaderboardEntry> = env.storage().instance()
+            .get(&DiscoveryKey::TopTracks)
+            .unwrap_or(Vec::new(&env));
+        board.slice(0..limit.min(board.len()))
+    }
+
+    pub fn get_top_artists(env: Env, limit: u32) -> Vec<LeaderboardEntry> {
+        let board: Vec<LeaderboardEntry> = env.storage().instance()
+            .get(&DiscoveryKey::TopArtists)
+            .unwrap_or(Vec::new(&env));
+        board.slice(0..limit.min(board.len()))
+    }
+
+    fn record_platform_top(env: &Env, key: DiscoveryKey, subject: LeaderboardSubject, score: i128) {
+        let mut board: Vec<LeaderboardEntry> = env.storage().instance()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        if Self::rank_entry(&mut board, subject, score, LEADERBOARD_SIZE) {
+            env.storage().instance().set(&key, &board);
+        }
+    }
+
+    // Competitions
+    // The organizer escrows the prize pool up front. Without a table the competition
+    // is platform-wide and only the admin can run it; otherwise the organizer must
+    // own the table. prize_shares are basis points per rank, best rank first.
+    pub fn open_competition(
+        env: Env,
+        organizer: Address,
+        table_id: Option<BytesN<32>>,
+        metric: CompetitionMetric,
+        prize_pool: i128,
+        prize_shares: Vec<u32>,
+        starts_at: u64,
+        ends_at: u64,
+    ) -> u32 {
+        organizer.require_auth();
+        
+        match &table_id {
+            Some(table_id) => {
+                let table: JukeboxTable = Self::hub(&env)
+                    .get_table(&table_id.clone())
+                    .unwrap_or_else(|| panic!("Table not found"));
+                if table.owner != organizer {
+                    panic!("Not table owner");
+                }
+            }
+            None => {
+                let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+                if organizer != admin {
+                    panic!("Only admin can run platform-wide competitions");
+                }
+            }
+        }
+        
+        if prize_pool <= 0 || ends_at <= starts_at || ends_at <= env.ledger().timestamp() {
+            panic!("Invalid competition terms");
+        }
+        
+        if prize_shares.is_empty() || prize_shares.len() > MAX_PRIZE_RANKS {
+            panic!("Invalid prize ranks");
+        }
+        let mut total_shares = 0;
+        for share in prize_shares.iter() {
+            total_shares += share;
+        }
+        if total_shares != 10000 {
+            panic!("Prize shares must total 10000 bps");
+        }
+        
+        let mut active = Self::get_active_competitions(env.clone());
+        if active.len() >= MAX_ACTIVE_COMPETITIONS {
+            panic!("Too many active competitions");
+        }
+        
+        token::Client::new(&env, &Self::default_token(&env)).transfer(
+            &organizer,
+            &env.current_contract_address(),
+            &prize_pool,
+        );
+        
+        let competition_id: u32 = env.storage().instance().get(&CompetitionKey::CompetitionCounter).unwrap_or(0) + 1;
+        env.storage().instance().set(&CompetitionKey::CompetitionCounter, &competition_id);
+        
+        let competition = Competition {
+            competition_id,
+            organizer: organizer.clone(),
+            platform_wide: table_id.is_none(),
+            table_id: table_id.unwrap_or(BytesN::from_array(&env, &[0; 32])),
+            metric,
+            prize_pool,
+            prize_shares,
+            starts_at,
+            ends_at,
+            finalized: false,
+        };
+        env.storage().instance().set(&CompetitionKey::Competitions(competition_id), &competition);
+        
+        active.push_back(competition_id);
+        env.storage().instance().set(&CompetitionKey::ActiveCompetitions, &active);
+        
+        env.events().publish(
+            (Symbol::new(&env, "competition_opened"), EVENT_SCHEMA_VERSION, organizer),
+            (competition_id, prize_pool, ends_at)
+        );
+        
+        competition_id
+    }
+
+    // NewTrackPlays counts requests for tracks minted inside the window, credited
+    // to the track; TopRequester counts requests per requester
+    fn record_competitions(env: &Env, table_id: &BytesN<32>, requester: &Address, track_id: &BytesN<32>) {
+        let active = Self::get_active_competitions(env.clone());
+        let now = env.ledger().timestamp();
+        
+        for competition_id in active.iter() {
+            let competition: Competition = env.storage().instance()
+                .get(&CompetitionKey::Competitions(competition_id))
+                .unwrap();
+            
+            if now < competition.starts_at || now >= competition.ends_at {
+                continue;
+            }
+            if !competition.platform_wide && competition.table_id != *table_id {
+                continue;
+            }
+            
+            let subject = match competition.metric {
+                CompetitionMetric::NewTrackPlays => {
+                    match Self::catalog(env).get_minted_at(track_id) {
+                        Some(minted_at) if minted_at >= competition.starts_at => LeaderboardSubject::Track(track_id.clone()),
+                        _ => continue,
+                    }
+                }
+                CompetitionMetric::TopRequester => LeaderboardSubject::Requester(requester.clone()),
+            };
+            
+            let entry_key = CompetitionKey::Entries(competition_id, subject.clone());
+            let score: i128 = env.storage().instance().get::<_, i128>(&entry_key).unwrap_or(0) + 1;
+            env.storage().instance().set(&entry_key, &score);
+            
+            let mut standings = Self::get_competition_standings(env.clone(), competition_id);
+            if Self::rank_entry(&mut standings, subject, score, competition.prize_shares.len()) {
+                env.storage().instance().set(&CompetitionKey::Standings(competition_id), &standings);
+            }
+        }
+    }
+
+    // Anyone can finalize once the window has closed. Track winners are paid to the
+    // track's artist; shares for empty ranks and rounding dust go back to the organizer.
+    pub fn finalize_competition(env: Env, competition_id: u32) {
+        let mut competition = Self::get_competition(env.clone(), competition_id)
+            .unwrap_or_else(|| panic!("Competition not found"));
+        
+        if competition.finalized {
+            panic!("Competition already finalized");
+        }
+        if env.ledger().timestamp() < competition.ends_at {
+            panic!("Competition still running");
+        }
+        
+        let token_client = token::Client::new(&env, &Self::default_token(&env));
+        let standings = Self::get_competition_standings(env.clone(), competition_id);
+        
+        let mut paid = 0;
+        for (rank, entry) in standings.iter().enumerate() {
+            let share = competition.prize_shares.get(rank as u32).unwrap();
+            let prize = (competition.prize_pool * share as i128) / 10000;
+            if prize == 0 {
+                continue;
+            }
+            
+            let winner = match entry.subject {
+                LeaderboardSubject::Requester(requester) | LeaderboardSubject::Artist(requester) => requester,
+                LeaderboardSubject::Track(track_id) => {
+                    Self::catalog(&env).get_track(&track_id).unwrap().artist_id
+                }
+            };
+            token_client.transfer(&env.current_contract_address(), &winner, &prize);
+            paid += prize;
+        }
+        
+        if competition.prize_pool > paid {
+            token_client.transfer(&env.current_contract_address(), &competition.organizer, &(competition.prize_pool - paid));
+        }
+        
+        competition.finalized = true;
+        env.storage().instance().set(&CompetitionKey::Competitions(competition_id), &competition);
+        
+        let mut active = Self::get_active_competitions(env.clone());
+        if let Some(index) = active.first_index_of(competition_id) {
+            active.remove(index);
+        }
+        env.storage().instance().set(&CompetitionKey::ActiveCompetitions, &active);
+        
+        env.events().publish(
+            (Symbol::new(&env, "competition_finalized"), EVENT_SCHEMA_VERSION, competition_id),
+            paid
+        );
+    }
+
+    pub fn get_competition(env: Env, competition_id: u32) -> Option<Competition> {
+        env.storage().instance().get(&CompetitionKey::Competitions(competition_id))
+    }
+
+    pub fn get_competition_standings(env: Env, competition_id: u32) -> Vec<LeaderboardEntry> {
+        env.storage().instance()
+            .get(&CompetitionKey::Standings(competition_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn get_active_competitions(env: Env) -> Vec<u32> {
+        env.storage().instance()
+            .get(&CompetitionKey::ActiveCompetitions)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn favorite_table(env: Env, user: Address, table_id: BytesN<32>) {
+        user.require_auth();
+        
+        require_active_user(&env, &user);
+        
+        if Self::hub(&env).get_table(&table_id).is_none() {
+            panic!("Table not found");
+        }
+        
+        let mut favorites = Self::favorites(&env, &FavoriteKey::Tables(user.clone()));
+        if favorites.contains(&table_id) {
+            panic!("Already favorited");
+        }
+        if favorites.len() >= MAX_FAVORITES {
+            panic!("Too many favorites");
+        }
+        favorites.push_back(table_id.clone());
+        Self::save_favorites(&env, &FavoriteKey::Tables(user.clone()), &favorites);
+        
+        env.events().publish(
+            (Symbol::new(&env, "table_favorited"), EVENT_SCHEMA_VERSION, table_id, user.clone()),
+            user
+        );
+    }
+
+    pub fn unfavorite_table(env: Env, user: Address, table_id: BytesN<32>) {
+        user.require_auth();
+        
+        let mut favorites = Self::favorites(&env, &FavoriteKey::Tables(user.clone()));
+        let index = favorites.first_index_of(&table_id)
+            .unwrap_or_else(|| panic!("Not favorited"));
+        favorites.remove(index);
+        Self::save_favorites(&env, &FavoriteKey::Tables(user.clone()), &favorites);
+        
+        env.events().publish(
+            (Symbol::new(&env, "table_unfavorited"), EVENT_SCHEMA_VERSION, table_id, user.clone()),
+            user
+        );
+    }
+
+    pub fn get_favorite_tables(env: Env, user: Address, start: u32, limit: u32) -> Vec<BytesN<32>> {
+        let favorites = Self::favorites(&env, &FavoriteKey::Tables(user));
+        let end = start.saturating_add(limit).min(favorites.len());
+        favorites.slice(start.min(end)..end)
+    }
+
+    // Play history
+    pub fn get_play_history_count(env: Env, table_id: BytesN<32>) -> u32 {
+        env.storage().temporary().get(&HistoryKey::Count(table_id)).unwrap_or(0)
+    }
+
+    // Newest first: start = 0 is the most recently finished track
+    pub fn get_play_history(env: Env, table_id: BytesN<32>, start: u32, limit: u32) -> Vec<PlayHistoryEntry> {
+        let count = Self::get_play_history_count(env.clone(), table_id.clone());
+        
+        let mut entries = Vec::new(&env);
+        let end = start.saturating_add(limit).min(count);
+        for i in start..end {
+            // Older entries may already have lapsed
+            if let Some(entry) = env.storage().temporary().get(&HistoryKey::Plays(table_id.clone(), count - 1 - i)) {
+                entries.push_back(entry);
+            }
+        }
+        entries
+    }
+
+    fn record_play_history(env: &Env, table_id: &BytesN<32>, request: &TrackRequest, skipped: bool) {
+        let count = Self::get_play_history_count(env.clone(), table_id.clone());
+        
+        let entry_key = HistoryKey::Plays(table_id.clone(), count);
+        env.storage().temporary().set(
+            &entry_key,
+            &PlayHistoryEntry {
+                track_id: request.track_id.clone(),
+                request_id: request.request_id.clone(),
+                played_at: env.ledger().timestamp(),
+                skipped,
+            },
+        );
+        env.storage().temporary().set(&HistoryKey::Count(table_id.clone()), &(count + 1));
+        
+        let extend_to = ttl_policy(env).history;
+        bump_temporary(env, &entry_key, extend_to);
+        bump_temporary(env, &HistoryKey::Count(table_id.clone()), extend_to);
+    }
+
+    pub fn get_user_requests(env: Env, user: Address, start: u32, limit: u32) -> Vec<TrackRequest> {
+        let count = Self::get_user_request_count(env.clone(), user.clone());
+        
+        let mut requests = Vec::new(&env);
+        let end = start.saturating_add(limit).min(count);
+        for i in start..end {
+            let Some(request_id) = env.storage().temporary()
+                .get::<_, BytesN<32>>(&HistoryKey::UserRequests(user.clone(), i))
+            else {
+                continue;
+            };
+            requests.push_back(Self::hub(&env).get_request(&request_id).unwrap());
+        }
+        requests
+    }
+
+    pub fn get_user_request_count(env: Env, user: Address) -> u32 {
+        env.storage().temporary().get(&HistoryKey::UserRequestCount(user)).unwrap_or(0)
+    }
+
+    pub fn get_user_spend(env: Env, user: Address) -> i128 {
+        env.storage().persistent().get(&HistoryKey::UserSpend(user)).unwrap_or(0)
+    }
+
+    fn index_user_request(env: &Env, user: &Address, request_id: &BytesN<32>, amount_paid: i128) {
+        let count = Self::get_user_request_count(env.clone(), user.clone());
+        let entry_key = HistoryKey::UserRequests(user.clone(), count);
+        env.storage().temporary().set(&entry_key, request_id);
+        env.storage().temporary().set(&HistoryKey::UserRequestCount(user.clone()), &(count + 1));
+        
+        let policy = ttl_policy(env);
+        bump_temporary(env, &entry_key, policy.history);
+        bump_temporary(env, &HistoryKey::UserRequestCount(user.clone()), policy.history);
+        
+        let spend = Self::get_user_spend(env.clone(), user.clone()) + amount_paid;
+        env.storage().persistent().set(&HistoryKey::UserSpend(user.clone()), &spend);
+        bump_persistent(env, &HistoryKey::UserSpend(user.clone()), policy.users);
+    }
+
+    // Trending
+    pub fn get_trending(env: Env, limit: u32) -> Vec<TrendingTrack> {
+        let today = env.ledger().timestamp() / 86400;
+        
+        let mut totals: Map<BytesN<32>, u32> = Map::new(&env);
+        for day in today.saturating_sub(TRENDING_WINDOW_DAYS - 1)..=today {
+            let bucket: Map<BytesN<32>, u32> = env.storage().temporary()
+                .get(&TrendingKey::Buckets(day))
+                .unwrap_or(Map::new(&env));
+            
+            for (track_id, plays) in bucket.iter() {
+                totals.set(track_id.clone(), totals.get(track_id).unwrap_or(0) + plays);
+            }
+        }
+        
+        // Only tracks that would make the board are checked against the catalog
+        let catalog = Self::catalog(&env);
+        let mut trending: Vec<TrendingTrack> = Vec::new(&env);
+        for (track_id, plays) in totals.iter() {
+            let position = trending.iter()
+                .position(|entry| entry.plays < plays)
+                .map(|index| index as u32)
+                .unwrap_or(trending.len());
+            
+            if position >= limit || catalog.is_track_taken_down(&track_id) {
+                continue;
+            }
+            
+            trending.insert(position, TrendingTrack { track_id, plays });
+            if trending.len() > limit {
+                trending.pop_back();
+            }
+        }
+        
+        trending
+    }
+
+    // Plays are bucketed per day and old buckets lapse on their own; a full bucket
+    // evicts its least-played track to admit a new one
+    fn record_trending_play(env: &Env, track_id: &BytesN<32>) {
+        let key = TrendingKey::Buckets(env.ledger().timestamp() / 86400);
+        let mut bucket: Map<BytesN<32>, u32> = env.storage().temporary()
+            .get(&key)
+            .unwrap_or(Map::new(env));
+        
+        let plays = bucket.get(track_id.clone()).unwrap_or(0);
+        if plays == 0 && bucket.len() >= TRENDING_BUCKET_CAPACITY {
+            let mut lowest: Option<(BytesN<32>, u32)> = None;
+            for (candidate, count) in bucket.iter() {
+                if lowest.as_ref().is_none_or(|(_, least)| count < *least) {
+                    lowest = Some((candidate, count));
+                }
+            }
+            if let Some((evicted, _)) = lowest {
+                bucket.remove(evicted);
+            }
+        }
+        
+        bucket.set(track_id.clone(), plays + 1);
+        env.storage().temporary().set(&key, &bucket);
+        env.storage().temporary().extend_ttl(&key, TRENDING_BUCKET_TTL / 2, TRENDING_BUCKET_TTL);
+    }
+
+    // Achievements
+    pub fn get_achievements(env: Env, user: Address) -> Vec<Symbol> {
+        env.storage().instance()
+            .get(&AchievementKey::Unlocked(user))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Achievements are also granted as badges issued by the contract itself, so
+    // tables can gate entry on them like any other badge
+    fn grant_achievement(env: &Env, user: &Address, achievement: Symbol) {
+        let mut unlocked = Self::get_achievements(env.clone(), user.clone());
+        if unlocked.contains(&achievement) {
+            return;
+        }
+        unlocked.push_back(achievement.clone());
+        env.storage().instance().set(&AchievementKey::Unlocked(user.clone()), &unlocked);
+        
+        Self::grant_badge(env, &env.current_contract_address(), user, &achievement);
+        
+        env.events().publish(
+            (Symbol::new(env, "achievement_unlocked"), EVENT_SCHEMA_VERSION, user.clone()),
+            achievement
+        );
+    }
+
+    fn bump_achievement_counter(env: &Env, key: AchievementKey) -> u32 {
+        let count: u32 = env.storage().instance().get(&key).unwrap_or(0) + 1;
+        env.storage().instance().set(&key, &count);
+        count
+    }
+
+    // Sessions
+    pub fn start_session(env: Env, owner: Address, table_id: BytesN<32>, check_in_secs: u64) -> u32 {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&table_id.clone())
+            .unwrap_or_else(|| panic!("Table not found"));
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        if check_in_secs == 0 {
+            panic!("Check-in window must be positive");
+        }
+        
+        let session_id: u32 = env.storage().instance()
+            .get(&SessionKey::Counter(table_id.clone()))
+            .unwrap_or(0) + 1;
+        env.storage().instance().set(&SessionKey::Counter(table_id.clone()), &session_id);
+        
+        let now = env.ledger().timestamp();
+        let session = TableSession {
+            session_id,
+            table_id: table_id.clone(),
+            starts_at: now,
+            ends_at: now + check_in_secs,
+            attendee_count: 0,
+        };
+        env.storage().instance().set(&SessionKey::Sessions(table_id.clone(), session_id), &session);
+        
+        env.events().publish(
+            (Symbol::new(&env, "session_started"), EVENT_SCHEMA_VERSION, table_id),
+            (session_id, session.ends_at)
+        );
+        
+        session_id
+    }
+
+    // Attendance badges are bound to the member who checked in and can't be moved
+    pub fn check_in(env: Env, user: Address, table_id: BytesN<32>, session_id: u32) {
+        user.require_auth();
+        
+        Self::record_check_in(&env, &user, &table_id, session_id);
+    }
+
+    // Check-ins a venue device attests for the member
+    pub fn check_in_for(env: Env, caller: Address, user: Address, table_id: BytesN<32>, session_id: u32) {
+        require_sibling(&env, &caller);
+        Self::record_check_in(&env, &user, &table_id, session_id);
+    }
+
+    fn record_check_in(env: &Env, user: &Address, table_id: &BytesN<32>, session_id: u32) {
+        require_active_user(env, user);
+        
+        if !Self::hub(env).is_table_member(user, table_id) {
+            panic!("Not a member of this table");
+        }
+        
+        let mut session: TableSession = env.storage().instance()
+            .get(&SessionKey::Sessions(table_id.clone(), session_id))
+            .unwrap_or_else(|| panic!("Session not found"));
+        
+        let now = env.ledger().timestamp();
+        if now >= session.ends_at {
+            panic!("Check-in closed");
+        }
+        
+        let checked_in_key = SessionKey::CheckedIn(table_id.clone(), session_id, user.clone());
+        if env.storage().persistent().has(&checked_in_key) {
+            panic!("Already checked in");
+        }
+        let attendee_key = SessionKey::Attendee(table_id.clone(), session_id, session.attendee_count);
+        env.storage().persistent().set(&checked_in_key, &true);
+        env.storage().persistent().set(&attendee_key, user);
+        
+        session.attendee_count += 1;
+        env.storage().instance().set(&SessionKey::Sessions(table_id.clone(), session_id), &session);
+        
+        let attended = Self::get_attendance_count(env.clone(), user.clone());
+        let badge_key = SessionKey::Attended(user.clone(), attended);
+        env.storage().persistent().set(
+            &badge_key,
+            &AttendanceBadge {
+                table_id: table_id.clone(),
+                session_id,
+                checked_in_at: now,
+            },
+        );
+        env.storage().persistent().set(&SessionKey::AttendedCount(user.clone()), &(attended + 1));
+        
+        let extend_to = ttl_policy(env).users;
+        bump_persistent(env, &checked_in_key, extend_to);
+        bump_persistent(env, &attendee_key, extend_to);
+        bump_persistent(env, &badge_key, extend_to);
+        bump_persistent(env, &SessionKey::AttendedCount(user.clone()), extend_to);
+        
+        env.events().publish(
+            (Symbol::new(env, "checked_in"), EVENT_SCHEMA_VERSION, table_id.clone()),
+            (session_id, user.clone())
+        );
+    }
+
+    pub fn get_session(env: Env, table_id: BytesN<32>, session_id: u32) -> Option<TableSession> {
+        env.storage().instance().get(&SessionKey::Sessions(table_id, session_id))
+    }
+
+    pub fn get_session_attendees(env: Env, table_id: BytesN<32>, session_id: u32, start: u32, limit: u32) -> Vec<Address> {
+        let count = Self::get_session(env.clone(), table_id.clone(), session_id)
+            .map(|session| session.attendee_count)
+            .unwrap_or(0);
+        
+        let mut attendees = Vec::new(&env);
+        let end = start.saturating_add(limit).min(count);
+        for i in start..end {
+            attendees.push_back(env.storage().persistent()
+                .get(&SessionKey::Attendee(table_id.clone(), session_id, i))
+                .unwrap());
+        }
+        attendees
+    }
+
+    pub fn get_attendance_count(env: Env, user: Address) -> u32 {
+        env.storage().persistent().get(&SessionKey::AttendedCount(user)).unwrap_or(0)
+    }
+
+    pub fn get_attendance_badges(env: Env, user: Address, start: u32, limit: u32) -> Vec<AttendanceBadge> {
+        let count = Self::get_attendance_count(env.clone(), user.clone());
+        
+        let mut badges = Vec::new(&env);
+        let end = start.saturating_add(limit).min(count);
+        for i in start..end {
+            badges.push_back(env.storage().persistent()
+                .get(&SessionKey::Attended(user.clone(), i))
+                .unwrap());
+        }
+        badges
+    }
+
+    // Receipts
+    fn mint_receipt(env: &Env, request: &TrackRequest) {
+        let receipt_id: u32 = env.storage().instance()
+            .get(&ReceiptKey::ReceiptCounter)
+            .unwrap_or(0) + 1;
+        env.storage().instance().set(&ReceiptKey::ReceiptCounter, &receipt_id);
+        
+        let receipt = Receipt {
+            receipt_id,
+            owner: request.requester.clone(),
+            request_id: request.request_id.clone(),
+            track_id: request.track_id.clone(),
+            table_id: request.table_id.clone(),
+            played_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&ReceiptKey::Receipts(receipt_id), &receipt);
+        
+        let owner = request.requester.clone();
+        let owned = Self::get_receipt_count(env.clone(), owner.clone());
+        env.storage().persistent().set(&ReceiptKey::Owned(owner.clone(), owned), &receipt_id);
+        env.storage().persistent().set(&ReceiptKey::OwnedCount(owner.clone()), &(owned + 1));
+        
+        let extend_to = ttl_policy(env).users;
+        bump_persistent(env, &ReceiptKey::Receipts(receipt_id), extend_to);
+        bump_persistent(env, &ReceiptKey::Owned(owner.clone(), owned), extend_to);
+        bump_persistent(env, &ReceiptKey::OwnedCount(owner), extend_to);
+        
+        env.events().publish(
+            (Symbol::new(env, "receipt_minted"), EVENT_SCHEMA_VERSION, request.requester.clone()),
+            receipt_id
+        );
+    }
+
+    pub fn get_receipt(env: Env, receipt_id: u32) -> Option<Receipt> {
+        env.storage().persistent().get(&ReceiptKey::Receipts(receipt_id))
+    }
+
+    pub fn get_receipt_count(env: Env, user: Address) -> u32 {
+        env.storage().persistent().get(&ReceiptKey::OwnedCount(user)).unwrap_or(0)
+    }
+
+    pub fn get_receipts(env: Env, user: Address, start: u32, limit: u32) -> Vec<Receipt> {
+        let count = Self::get_receipt_count(env.clone(), user.clone());
+        
+        let mut receipts = Vec::new(&env);
+        let end = start.saturating_add(limit).min(count);
+        for i in start..end {
+            let receipt_id: u32 = env.storage().persistent()
+                .get(&ReceiptKey::Owned(user.clone(), i))
+                .unwrap();
+            receipts.push_back(Self::get_receipt(env.clone(), receipt_id).unwrap());
+        }
+        receipts
+    }
+
+    // Ad slots
+    // Owners opt in by setting a cadence (tracks between ads) and a per-slot price;
+    // a cadence of zero stops new ads without touching existing bookings
+    pub fn set_table_ads(env: Env, owner: Address, table_id: BytesN<32>, config: TableAdConfig) {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&table_id.clone())
+            .unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        if config.slot_price < 0 {
+            panic!("Invalid slot price");
+        }
+        
+        env.storage().instance().set(&AdKey::AdConfig(table_id.clone()), &config);
+        
+        env.events().publish(
+            (Symbol::new(&env, "table_ads_set"), EVENT_SCHEMA_VERSION, table_id),
+            config
+        );
+    }
+
+    pub fn get_table_ads(env: Env, table_id: BytesN<32>) -> Option<TableAdConfig> {
+        env.storage().instance().get(&AdKey::AdConfig(table_id))
+    }
+
+    // The sponsor escrows every slot at the current price; bookings play in order
+    pub fn book_ad_slots(
+        env: Env,
+        sponsor: Address,
+        table_id: BytesN<32>,
+        content_hash: BytesN<32>,
+        uri: String,
+        slots: u32,
+    ) -> u32 {
+        sponsor.require_auth();
+        
+        validation::require_uri(&env, &uri);
+        
+        let config = match Self::get_table_ads(env.clone(), table_id.clone()) {
+            Some(config) if config.cadence > 0 => config,
+            _ => panic!("Table does not sell ad slots"),
+        };
+        
+        if slots == 0 {
+            panic!("Must book at least one slot");
+        }
+        
+        let mut bookings = Self::get_ad_bookings(env.clone(), table_id.clone());
+        if bookings.len() >= MAX_AD_BOOKINGS {
+            panic!("Too many pending ad bookings");
+        }
+        
+        token::Client::new(&env, &Self::default_token(&env)).transfer(
+            &sponsor,
+            &env.current_contract_address(),
+            &(config.slot_price * slots as i128),
+        );
+        
+        let booking_id: u32 = env.storage().instance().get(&AdKey::BookingCounter).unwrap_or(0) + 1;
+        env.storage().instance().set(&AdKey::BookingCounter, &booking_id);
+        
+        let booking = AdBooking {
+            booking_id,
+            sponsor: sponsor.clone(),
+            table_id: table_id.clone(),
+            content_hash,
+            uri,
+            slot_price: config.slot_price,
+            slots_remaining: slots,
+        };
+        env.storage().instance().set(&AdKey::Bookings(booking_id), &booking);
+        
+        bookings.push_back(booking_id);
+        env.storage().instance().set(&AdKey::TableBookings(table_id.clone()), &bookings);
+        
+        env.events().publish(
+            (Symbol::new(&env, "ad_booked"), EVENT_SCHEMA_VERSION, table_id, sponsor),
+            (booking_id, slots)
+        );
+        
+        booking_id
+    }
+
+    // Refunds the slots that have not played yet
+    pub fn cancel_ad_booking(env: Env, sponsor: Address, booking_id: u32) -> i128 {
+        sponsor.require_auth();
+        
+        let mut booking = Self::get_ad_booking(env.clone(), booking_id)
+            .unwrap_or_else(|| panic!("Booking not found"));
+        
+        if booking.sponsor != sponsor {
+            panic!("Not booking sponsor");
+        }
+        
+        let refund = booking.slot_price * booking.slots_remaining as i128;
+        booking.slots_remaining = 0;
+        env.storage().instance().set(&AdKey::Bookings(booking_id), &booking);
+        Self::remove_ad_booking(&env, &booking.table_id, booking_id);
+        
+        if refund > 0 {
+            token::Client::new(&env, &Self::default_token(&env)).transfer(
+                &env.current_contract_address(),
+                &sponsor,
+                &refund,
+            );
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "ad_booking_cancelled"), EVENT_SCHEMA_VERSION, booking_id),
+            (sponsor, refund)
+        );
+        
+        refund
+    }
+
+    pub fn get_ad_booking(env: Env, booking_id: u32) -> Option<AdBooking> {
+        env.storage().instance().get(&AdKey::Bookings(booking_id))
+    }
+
+    pub fn get_ad_bookings(env: Env, table_id: BytesN<32>) -> Vec<u32> {
+        env.storage().instance()
+            .get(&AdKey::TableBookings(table_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // The interstitial clients should show before the current track, if any
+    pub fn get_current_ad(env: Env, table_id: BytesN<32>) -> Option<AdBooking> {
+        env.storage().instance()
+            .get::<_, u32>(&AdKey::CurrentAd(table_id))
+            .and_then(|booking_id| Self::get_ad_booking(env.clone(), booking_id))
+    }
+
+    fn remove_ad_booking(env: &Env, table_id: &BytesN<32>, booking_id: u32) {
+        let mut bookings = Self::get_ad_bookings(env.clone(), table_id.clone());
+        if let Some(index) = bookings.first_index_of(booking_id) {
+            bookings.remove(index);
+            env.storage().instance().set(&AdKey::TableBookings(table_id.clone()), &bookings);
+        }
+    }
+
+    // Called as the next track starts. Once `cadence` tracks have played since the
+    // last ad, the oldest booking plays ahead of it and its slot price is split
+    // between the table owner and the treasury at the platform fee.
+    fn play_due_ad(env: &Env, table: &JukeboxTable) {
+        let table_id = &table.table_id;
+        env.storage().instance().remove(&AdKey::CurrentAd(table_id.clone()));
+        
+        let config = match Self::get_table_ads(env.clone(), table_id.clone()) {
+            Some(config) if config.cadence > 0 => config,
+            _ => return,
+        };
+        
+        let played: u32 = env.storage().instance().get(&AdKey::TracksSinceAd(table_id.clone())).unwrap_or(0);
+        let bookings = Self::get_ad_bookings(env.clone(), table_id.clone());
+        if played < config.cadence || bookings.is_empty() {
+            env.storage().instance().set(&AdKey::TracksSinceAd(table_id.clone()), &(played + 1));
+            return;
+        }
+        
+        let booking_id = bookings.get(0).unwrap();
+        let mut booking = Self::get_ad_booking(env.clone(), booking_id).unwrap();
+        booking.slots_remaining -= 1;
+        env.storage().instance().set(&AdKey::Bookings(booking_id), &booking);
+        if booking.slots_remaining == 0 {
+            Self::remove_ad_booking(env, table_id, booking_id);
+        }
+        
+        env.storage().instance().set(&AdKey::TracksSinceAd(table_id.clone()), &1u32);
+        env.storage().instance().set(&AdKey::CurrentAd(table_id.clone()), &booking_id);
+        
+        let links = links(env);
+        let payouts = PayoutsClient::new(env, &links.payouts);
+        let fee_amount = (booking.slot_price * payouts.get_platform_fee() as i128) / 10000;
+        let owner_share = booking.slot_price - fee_amount;
+        
+        let token_client = token::Client::new(env, &Self::default_token(env));
+        if fee_amount > 0 {
+            token_client.transfer(&env.current_contract_address(), &links.payouts, &fee_amount);
+            payouts.credit_treasury(&env.current_contract_address(), &fee_amount);
+            Self::catalog(env).record_stats(&env.current_contract_address(), &0, &0, &0, &fee_amount);
+        }
+        if owner_share > 0 {
+            token_client.transfer(&env.current_contract_address(), &table.owner, &owner_share);
+        }
+        
+        env.events().publish(
+            (Symbol::new(env, "ad_played"), EVENT_SCHEMA_VERSION, table_id.clone(), booking.sponsor),
+            (booking_id, booking.content_hash)
+        );
+    }
+
+    // Table sponsorships
+    // The sponsor escrows the full amount up front; it streams to the table owner
+    // linearly from acceptance until duration_secs have passed
+    pub fn propose_sponsorship(
+        env: Env,
+        sponsor: Address,
+        table_id: BytesN<32>,
+        amount: i128,
+        duration_secs: u64,
+        branding_uri: String,
+        penalty_bps: u32,
+    ) -> u32 {
+        sponsor.require_auth();
+        
+        validation::require_uri(&env, &branding_uri);
+        
+        if Self::hub(&env).get_table(&table_id).is_none() {
+            panic!("Table not found");
+        }
+        
+        if amount <= 0 || duration_secs == 0 || penalty_bps > 10000 {
+            panic!("Invalid sponsorship terms");
+        }
+        
+        token::Client::new(&env, &Self::default_token(&env)).transfer(
+            &sponsor,
+            &env.current_contract_address(),
+            &amount,
+        );
+        
+        let sponsorship_id: u32 = env.storage().instance().get(&SponsorshipKey::SponsorshipCounter).unwrap_or(0) + 1;
+        env.storage().instance().set(&SponsorshipKey::SponsorshipCounter, &sponsorship_id);
+        
+        let sponsorship = Sponsorship {
+            sponsorship_id,
+            sponsor: sponsor.clone(),
+            table_id: table_id.clone(),
+            amount,
+            duration_secs,
+            branding_uri,
+            penalty_bps,
+            starts_at: 0,
+            claimed: 0,
+            status: SponsorshipStatus::Proposed,
+        };
+        env.storage().instance().set(&SponsorshipKey::Sponsorships(sponsorship_id), &sponsorship);
+        
+        env.events().publish(
+            (Symbol::new(&env, "sponsorship_proposed"), EVENT_SCHEMA_VERSION, table_id, sponsor),
+            (sponsorship_id, amount, duration_secs)
+        );
+        
+        sponsorship_id
+    }
+
+    // A table carries one active sponsorship at a time
+    pub fn accept_sponsorship(env: Env, owner: Address, sponsorship_id: u32) {
+        owner.require_auth();
+        
+        let mut sponsorship = Self::get_sponsorship(env.clone(), sponsorship_id)
+            .unwrap_or_else(|| panic!("Sponsorship not found"));
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&sponsorship.table_id.clone())
+            .unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        if sponsorship.status != SponsorshipStatus::Proposed {
+            panic!("Sponsorship not pending");
+        }
+        
+        if env.storage().instance().has(&SponsorshipKey::TableSponsorship(sponsorship.table_id.clone())) {
+            panic!("Table already sponsored");
+        }
+        
+        sponsorship.status = SponsorshipStatus::Active;
+        sponsorship.starts_at = env.ledger().timestamp();
+        env.storage().instance().set(&SponsorshipKey::Sponsorships(sponsorship_id), &sponsorship);
+        env.storage().instance().set(&SponsorshipKey::TableSponsorship(sponsorship.table_id.clone()), &sponsorship_id);
+        
+        env.events().publish(
+            (Symbol::new(&env, "sponsorship_accepted"), EVENT_SCHEMA_VERSION, sponsorship.table_id, sponsorship.sponsor),
+            sponsorship_id
+        );
+    }
+
+    // The penalty the owner would owe for walking away now stays unclaimable, so
+    // claiming first can't dodge it
+    fn sponsorship_claimable(env: &Env, sponsorship: &Sponsorship) -> i128 {
+        let vested = Self::sponsorship_vested(env, sponsorship);
+        let reserve = ((sponsorship.amount - vested) * sponsorship.penalty_bps as i128) / 10000;
+        (vested - sponsorship.claimed - reserve).max(0)
+    }
+
+    // Table treasuries are kept by payouts, so the escrowed funds move there too
+    fn credit_table_treasury(env: &Env, table_id: &BytesN<32>, amount: i128) {
+        let payouts = links(env).payouts;
+        token::Client::new(env, &Self::default_token(env)).transfer(&env.current_contract_address(), &payouts, &amount);
+        PayoutsClient::new(env, &payouts).credit_table_treasury(&env.current_contract_address(), table_id, &amount);
+    }
+
+    // Streamed so far, whether or not the owner has claimed it
+    fn sponsorship_vested(env: &Env, sponsorship: &Sponsorship) -> i128 {
+        if sponsorship.status != SponsorshipStatus::Active {
+            return sponsorship.claimed;
+        }
+        
+        let elapsed = env.ledger().timestamp().saturating_sub(sponsorship.starts_at).min(sponsorship.duration_secs);
+        (sponsorship.amount * elapsed as i128) / sponsorship.duration_secs as i128
+    }
+
+    // Claims land in the table treasury rather than with whoever owns the table today
+    pub fn claim_sponsorship(env: Env, owner: Address, sponsorship_id: u32) -> i128 {
+        owner.require_auth();
+        
+        let mut sponsorship = Self::get_sponsorship(env.clone(), sponsorship_id)
+            .unwrap_or_else(|| panic!("Sponsorship not found"));
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&sponsorship.table_id.clone())
+            .unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        let amount = Self::sponsorship_claimable(&env, &sponsorship);
+        if amount <= 0 {
+            return 0;
+        }
+        
+        sponsorship.claimed += amount;
+        if sponsorship.claimed == sponsorship.amount {
+            sponsorship.status = SponsorshipStatus::Ended;
+            env.storage().instance().remove(&SponsorshipKey::TableSponsorship(sponsorship.table_id.clone()));
+        }
+        env.storage().instance().set(&SponsorshipKey::Sponsorships(sponsorship_id), &sponsorship);
+        Self::credit_table_treasury(&env, &sponsorship.table_id, amount);
+        
+        env.events().publish(
+            (Symbol::new(&env, "sponsorship_claimed"), EVENT_SCHEMA_VERSION, sponsorship_id),
+            (owner, amount)
+        );
+        
+        amount
+    }
+
+    // Either side can end the deal early. A pending proposal is simply refunded.
+    // Otherwise the table treasury keeps what has streamed, and the party walking
+    // away owes the other penalty_bps of the unstreamed remainder: taken from the
+    // sponsor's refund, or from the unclaimed stream, which claims never dip into.
+    pub fn terminate_sponsorship(env: Env, caller: Address, sponsorship_id: u32) {
+        caller.require_auth();
+        
+        let mut sponsorship = Self::get_sponsorship(env.clone(), sponsorship_id)
+            .unwrap_or_else(|| panic!("Sponsorship not found"));
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&sponsorship.table_id.clone())
+            .unwrap();
+        
+        let by_sponsor = caller == sponsorship.sponsor;
+        if !by_sponsor && caller != table.owner {
+            panic!("Not a party to the sponsorship");
+        }
+        
+        let unclaimed = Self::sponsorship_vested(&env, &sponsorship) - sponsorship.claimed;
+        let remainder = sponsorship.amount - sponsorship.claimed - unclaimed;
+        
+        let (to_owner, to_sponsor) = match sponsorship.status {
+            SponsorshipStatus::Ended => panic!("Sponsorship already ended"),
+            SponsorshipStatus::Proposed => (0, remainder),
+            SponsorshipStatus::Active => {
+                let penalty = (remainder * sponsorship.penalty_bps as i128) / 10000;
+                if by_sponsor {
+                    (unclaimed + penalty, remainder - penalty)
+                } else {
+                    (unclaimed - penalty, remainder + penalty)
+                }
+            }
+        };
+        
+        if sponsorship.status == SponsorshipStatus::Active {
+            env.storage().instance().remove(&SponsorshipKey::TableSponsorship(sponsorship.table_id.clone()));
+        }
+        sponsorship.claimed = sponsorship.amount;
+        sponsorship.status = SponsorshipStatus::Ended;
+        env.storage().instance().set(&SponsorshipKey::Sponsorships(sponsorship_id), &sponsorship);
+        
+        if to_owner > 0 {
+            Self::credit_table_treasury(&env, &sponsorship.table_id, to_owner);
+        }
+        if to_sponsor > 0 {
+            token::Client::new(&env, &Self::default_token(&env))
+                .transfer(&env.current_contract_address(), &sponsorship.sponsor, &to_sponsor);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "sponsorship_terminated"), EVENT_SCHEMA_VERSION, sponsorship.table_id, caller),
+            (sponsorship_id, to_owner, to_sponsor)
+        );
+    }
+
+    pub fn get_sponsorship(env: Env, sponsorship_id: u32) -> Option<Sponsorship> {
+        env.storage().instance().get(&SponsorshipKey::Sponsorships(sponsorship_id))
+    }
+
+    pub fn get_table_sponsorship(env: Env, table_id: BytesN<32>) -> Option<Sponsorship> {
+        env.storage().instance()
+            .get::<_, u32>(&SponsorshipKey::TableSponsorship(table_id))
+            .and_then(|sponsorship_id| Self::get_sponsorship(env.clone(), sponsorship_id))
+    }
+
+    // Badges
+    pub fn set_badge_issuer(env: Env, issuer: Address, enabled: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if enabled {
+            env.storage().instance().set(&DataKey::BadgeIssuers(issuer.clone()), &true);
+        } else {
+            env.storage().instance().remove(&DataKey::BadgeIssuers(issuer.clone()));
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "badge_issuer_set"), EVENT_SCHEMA_VERSION, issuer),
+            enabled
+        );
+    }
+
+    fn require_badge_issuer(env: &Env, issuer: &Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if issuer != &admin && !env.storage().instance().has(&DataKey::BadgeIssuers(issuer.clone())) {
+            panic!("Not a badge issuer");
+        }
+    }
+
+    pub fn award_badge(env: Env, issuer: Address, user: Address, badge: Symbol) {
+        issuer.require_auth();
+        Self::require_badge_issuer(&env, &issuer);
+        
+        if Self::catalog(&env).get_user(&user).is_none() {
+            panic!("User not registered");
+        }
+        
+        Self::grant_badge(&env, &issuer, &user, &badge);
+    }
+
+    fn grant_badge(env: &Env, issuer: &Address, user: &Address, badge: &Symbol) {
+        let key = DataKey::Badges(user.clone(), badge.clone());
+        if env.storage().instance().has(&key) {
+            return;
+        }
+        
+        let record = Badge {
+            badge: badge.clone(),
+            issuer: issuer.clone(),
+            awarded_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&key, &record);
+        
+        env.events().publish(
+            (Symbol::new(env, "badge_awarded"), EVENT_SCHEMA_VERSION, user.clone()),
+            badge.clone()
+        );
+    }
+
+    pub fn revoke_badge(env: Env, issuer: Address, user: Address, badge: Symbol) {
+        issuer.require_auth();
+        Self::require_badge_issuer(&env, &issuer);
+        
+        env.storage().instance().remove(&DataKey::Badges(user, badge));
+    }
+
+    pub fn has_badge(env: Env, user: Address, badge: Symbol) -> bool {
+        env.storage().instance().has(&DataKey::Badges(user, badge))
+    }
+
+    pub fn get_badge(env: Env, user: Address, badge: Symbol) -> Option<Badge> {
+        env.storage().instance().get(&DataKey::Badges(user, badge))
+    }
+
+    pub fn set_entry_badges(env: Env, owner: Address, table_id: BytesN<32>, badges: Vec<Symbol>) {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&table_id.clone())
+            .unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        if badges.len() > 10 {
+            panic!("Too many entry badges");
+        }
+        
+        if badges.is_empty() {
+            env.storage().instance().remove(&DataKey::EntryBadges(table_id.clone()));
+        } else {
+            env.storage().instance().set(&DataKey::EntryBadges(table_id.clone()), &badges);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "entry_badges_set"), EVENT_SCHEMA_VERSION, table_id),
+            badges
+        );
+    }
+
+    pub fn get_entry_badges(env: Env, table_id: BytesN<32>) -> Vec<Symbol> {
+        env.storage().instance()
+            .get(&DataKey::EntryBadges(table_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Tables without entry badges are open; otherwise any one accepted badge admits the user
+    pub fn meets_entry_badges(env: Env, table_id: BytesN<32>, user: Address) -> bool {
+        let badges: Vec<Symbol> = match env.storage().instance()
+            .get(&DataKey::EntryBadges(table_id))
+        {
+            Some(badges) => badges,
+            None => return true,
+        };
+        
+        badges.iter().any(|badge| env.storage().instance().has(&DataKey::Badges(user.clone(), badge)))
+    }
+
+    // Relayer outbox
+    pub fn set_outbox_relayer(env: Env, owner: Address, table_id: BytesN<32>, relayer: Address, enabled: bool) {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&table_id.clone())
+            .unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        if enabled {
+            env.storage().instance().set(&OutboxKey::Relayers(table_id.clone(), relayer.clone()), &true);
+        } else {
+            env.storage().instance().remove(&OutboxKey::Relayers(table_id.clone(), relayer.clone()));
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "outbox_relayer_set"), EVENT_SCHEMA_VERSION, table_id, relayer),
+            enabled
+        );
+    }
+
+    pub fn is_outbox_relayer(env: Env, table_id: BytesN<32>, relayer: Address) -> bool {
+        env.storage().instance().has(&OutboxKey::Relayers(table_id, relayer))
+    }
+
+    pub fn post_outbox(env: Env, owner: Address, table_id: BytesN<32>, kind: Symbol, data: Bytes) -> u32 {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env)
+            .get_table(&table_id.clone())
+            .unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        Self::enqueue_outbox(&env, &table_id, OutboxMessage::Custom(kind, data))
+    }
+
+    fn enqueue_outbox(env: &Env, table_id: &BytesN<32>, message: OutboxMessage) -> u32 {
+        let mut head: u32 = env.storage().instance()
+            .get(&OutboxKey::Head(table_id.clone()))
+            .unwrap_or(0);
+        let tail: u32 = env.storage().instance()
+            .get(&OutboxKey::Tail(table_id.clone()))
+            .unwrap_or(0);
+        
+        // Keep storage bounded when nobody is acknowledging: drop the oldest entry
+        if tail - head >= OUTBOX_CAPACITY {
+            env.storage().instance().remove(&OutboxKey::Entries(table_id.clone(), head));
+            head += 1;
+            env.storage().instance().set(&OutboxKey::Head(table_id.clone()), &head);
+        }
+        
+        let entry = OutboxEntry {
+            seq: tail,
+            table_id: table_id.clone(),
+            message,
+            created_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&OutboxKey::Entries(table_id.clone(), tail), &entry);
+        env.storage().instance().set(&OutboxKey::Tail(table_id.clone()), &(tail + 1));
+        
+        env.events().publish(
+            (Symbol::new(env, "outbox_enqueued"), EVENT_SCHEMA_VERSION, table_id.clone()),
+            tail
+        );
+        
+        tail
+    }
+
+    // Pending entries stay here until a relayer acknowledges them, giving at-least-once delivery
+    pub fn get_outbox(env: Env, table_id: BytesN<32>, limit: u32) -> Vec<OutboxEntry> {
+        let head: u32 = env.storage().instance()
+            .get(&OutboxKey::Head(table_id.clone()))
+            .unwrap_or(0);
+        let tail: u32 = env.storage().instance()
+            .get(&OutboxKey::Tail(table_id.clone()))
+            .unwrap_or(0);
+        
+        let mut entries = Vec::new(&env);
+        let mut seq = head;
+        while seq < tail && entries.len() < limit {
+            if let Some(entry) = env.storage().instance().get(&OutboxKey::Entries(table_id.clone(), seq)) {
+                entries.push_back(entry);
+            }
+            seq += 1;
+        }
+        
+        entries
+    }
+
+    pub fn ack_outbox(env: Env, relayer: Address, table_id: BytesN<32>, through_seq: u32) {
+        relayer.require_auth();
+        
+        if !env.storage().instance().has(&OutboxKey::Relayers(table_id.clone(), relayer.clone())) {
+            panic!("Not an outbox relayer");
+        }
+        
+        let head: u32 = env.storage().instance()
+            .get(&OutboxKey::Head(table_id.clone()))
+            .unwrap_or(0);
+        let tail: u32 = env.storage().instance()
+            .get(&OutboxKey::Tail(table_id.clone()))
+            .unwrap_or(0);
+        
+        if through_seq >= tail {
+            panic!("Cannot acknowledge unsent entries");
+        }
+        
+        let mut seq = head;
+        while seq <= through_seq {
+            env.storage().instance().remove(&OutboxKey::Entries(table_id.clone(), seq));
+            seq += 1;
+        }
+        
+        if seq > head {
+            env.storage().instance().set(&OutboxKey::Head(table_id.clone()), &seq);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "outbox_acked"), EVENT_SCHEMA_VERSION, table_id),
+            (relayer, through_seq)
+        );
+    }
+
+    fn default_token(env: &Env) -> Address {
+        env.storage().instance().get(&DataKey::TokenStellar).unwrap()
+    }
+
+    fn catalog(env: &Env) -> CatalogClient<'_> {
+        CatalogClient::new(env, &links(env).catalog)
+    }
+
+    fn hub(env: &Env) -> HubClient<'_> {
+        HubClient::new(env, &links(env).hub)
+    }
+
+    fn favorites(env: &Env, key: &FavoriteKey) -> Vec<BytesN<32>> {
+        env.storage().persistent().get(key).unwrap_or(Vec::new(env))
+    }
+
+    fn save_favorites(env: &Env, key: &FavoriteKey, favorites: &Vec<BytesN<32>>) {
+        env.storage().persistent().set(key, favorites);
+        bump_persistent(env, key, ttl_policy(env).users);
+    }
+}
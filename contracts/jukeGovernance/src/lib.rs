@@ -0,0 +1,806 @@
+#![no_std]
+use juke_common::{
+    links, record_audit, require_active_user, AuditEntry, AuditTarget, CatalogClient, FeeClass, FeeTier, HubClient,
+    Links, ParamChange, PayoutsClient, Settlement, TreasuryTarget, UpgradeableClient, CONTRACT_VERSION,
+    EVENT_SCHEMA_VERSION,
+};
+use soroban_sdk::{contract, contractimpl, contractmeta, contracttype, Address, BytesN, Env, String, Symbol, Vec};
+
+contractmeta!(key = "binver", val = "0.1.0");
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AdminAction {
+    UpdatePlatformFee(u32),
+    SetArtistVerified(Address, bool),
+    SetFeeTiers(Vec<FeeTier>),
+    SetTreasuryTargets(Vec<(TreasuryTarget, u32)>),
+    SetCouncil(Council),
+    Upgrade(BytesN<32>),
+    UpgradeContract(Address, BytesN<32>),
+    SetTimelockDelay(u64),
+    CancelParamChange(u32),
+    SetFeeOverride(FeeClass, Option<u32>),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Council {
+    pub members: Vec<Address>,
+    pub threshold: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct AdminProposal {
+    pub proposal_id: u32,
+    pub action: AdminAction,
+    pub proposer: Address,
+    pub approvals: Vec<Address>,
+    pub created_at: u64,
+    pub executed: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct QueuedChange {
+    change_id: u32,
+    change: ParamChange,
+    eta: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReportCategory {
+    Copyright,
+    Offensive,
+    Spam,
+    Other,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReportStatus {
+    Open,
+    Accepted,
+    Dismissed,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Report {
+    pub report_id: u32,
+    pub reporter: Address,
+    pub track_id: BytesN<32>,
+    pub category: ReportCategory,
+    pub evidence_uri: String,
+    pub status: ReportStatus,
+    pub filed_at: u64,
+    pub resolved_by: Option<Address>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DisputeStatus {
+    Open,
+    Released,
+    Refunded,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Dispute {
+    request_id: BytesN<32>,
+    opened_by: Address,
+    reason_hash: BytesN<32>,
+    held: Vec<(Address, i128)>,
+    held_vested: Vec<(Address, i128)>,
+    held_fee: i128,
+    settled_at: u64,
+    deadline: u64,
+    status: DisputeStatus,
+}
+
+
+// ----- Storage Keys -----
+// Keys encode as the variant name plus its fields, without the enum name, so a
+// variant must not reuse a name and field types already taken by another key enum
+// here or in juke_common.
+#[contracttype]
+enum DataKey {
+    Admin,
+    TokenStellar,
+}
+
+#[contracttype]
+enum TimelockKey {
+    Delay,
+    Queued(u32),
+    Counter,
+}
+
+#[contracttype]
+enum CouncilKey {
+    Council,
+    Proposals(u32),
+    ProposalCounter,
+    Executing,
+}
+
+#[contracttype]
+enum ModerationKey {
+    Moderators(Address),
+    Reports(u32),
+    ReportCounter,
+    OpenReport(BytesN<32>, Address),
+    Strikes(Address),
+}
+
+#[contracttype]
+enum DisputeKey {
+    Arbitrator,
+    Disputes(BytesN<32>),
+}
+
+const STRIKE_REPUTATION_PENALTY: u32 = 10;
+const DISPUTE_WINDOW: u64 = 7 * 86400;
+// How long after a request settles either party can still open a dispute
+const DISPUTE_OPEN_WINDOW: u64 = 14 * 86400;
+const DEFAULT_TIMELOCK_DELAY: u64 = 2 * 86400;
+const MAX_TIMELOCK_DELAY: u64 = 30 * 86400;
+
+// The council, timelock, moderation and disputes; upgrades and fee changes for
+// every contract in the deployment go through here
+#[contract]
+pub struct JukeGovernance;
+
+#[contractimpl]
+impl JukeGovernance {
+    pub fn initialize(env: Env, admin: Address, token_stellar: Address, links: Links) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::TokenStellar, &token_stellar);
+        juke_common::set_links(&env, &links);
+        
+        env.events().publish(
+            (Symbol::new(&env, "initialized"), EVENT_SCHEMA_VERSION),
+            (admin, token_stellar)
+        );
+    }
+
+    pub fn get_version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
+
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    pub fn get_audit_count(env: Env) -> u32 {
+        juke_common::audit_count(&env)
+    }
+
+    pub fn get_audit_log(env: Env, start: u32, limit: u32) -> Vec<AuditEntry> {
+        juke_common::audit_log(&env, start, limit)
+    }
+
+    // Queues the change behind the timelock; returns the queued change id
+    pub fn update_platform_fee(env: Env, new_fee: u32) -> u32 {
+        Self::require_council(&env);
+        
+        if new_fee > 2000 {
+            panic!("Fee too high");
+        }
+        
+        Self::queue_param_change(&env, ParamChange::PlatformFee(new_fee))
+    }
+
+    // Timelock
+    pub fn set_timelock_delay(env: Env, delay_secs: u64) -> u32 {
+        Self::require_council(&env);
+        
+        if delay_secs > MAX_TIMELOCK_DELAY {
+            panic!("Delay too long");
+        }
+        
+        Self::queue_param_change(&env, ParamChange::TimelockDelay(delay_secs))
+    }
+
+    pub fn get_timelock_delay(env: Env) -> u64 {
+        env.storage().instance()
+            .get(&TimelockKey::Delay)
+            .unwrap_or(DEFAULT_TIMELOCK_DELAY)
+    }
+
+    pub fn get_param_change(env: Env, change_id: u32) -> Option<QueuedChange> {
+        env.storage().instance().get(&TimelockKey::Queued(change_id))
+    }
+
+    // Anyone can apply a change once its delay has passed
+    pub fn execute_param_change(env: Env, change_id: u32) {
+        let queued: QueuedChange = env.storage().instance()
+            .get(&TimelockKey::Queued(change_id))
+            .unwrap_or_else(|| panic!("Change not queued"));
+        
+        if env.ledger().timestamp() < queued.eta {
+            panic!("Timelock not elapsed");
+        }
+        
+        match queued.change.clone() {
+            ParamChange::TimelockDelay(delay) => env.storage().instance().set(&TimelockKey::Delay, &delay),
+            change => Self::payouts(&env).apply_param_change(&env.current_contract_address(), &change),
+        }
+        env.storage().instance().remove(&TimelockKey::Queued(change_id));
+        
+        env.events().publish(
+            (Symbol::new(&env, "param_change_executed"), EVENT_SCHEMA_VERSION, change_id),
+            queued.change
+        );
+    }
+
+    pub fn cancel_param_change(env: Env, change_id: u32) {
+        Self::require_council(&env);
+        
+        let queued: QueuedChange = env.storage().instance()
+            .get(&TimelockKey::Queued(change_id))
+            .unwrap_or_else(|| panic!("Change not queued"));
+        
+        env.storage().instance().remove(&TimelockKey::Queued(change_id));
+        record_audit(&env, &Self::privileged_actor(&env), "change_cancel", AuditTarget::Platform);
+        
+        env.events().publish(
+            (Symbol::new(&env, "param_change_cancelled"), EVENT_SCHEMA_VERSION, change_id),
+            queued.change
+        );
+    }
+
+    fn queue_param_change(env: &Env, change: ParamChange) -> u32 {
+        let change_id: u32 = env.storage().instance()
+            .get(&TimelockKey::Counter)
+            .unwrap_or(0) + 1;
+        env.storage().instance().set(&TimelockKey::Counter, &change_id);
+        
+        let eta = env.ledger().timestamp() + Self::get_timelock_delay(env.clone());
+        let queued = QueuedChange {
+            change_id,
+            change: change.clone(),
+            eta,
+        };
+        env.storage().instance().set(&TimelockKey::Queued(change_id), &queued);
+        let action = match change {
+            ParamChange::PlatformFee(_) => "fee_change",
+            ParamChange::FeeTiers(_) => "fee_tiers",
+            ParamChange::FeeOverride(..) => "fee_override",
+            ParamChange::TimelockDelay(_) => "timelock_delay",
+        };
+        record_audit(env, &Self::privileged_actor(env), action, AuditTarget::Platform);
+        
+        env.events().publish(
+            (Symbol::new(env, "param_change_queued"), EVENT_SCHEMA_VERSION, change_id),
+            (change, eta)
+        );
+        
+        change_id
+    }
+
+    // Admin council
+    // Installing a council moves fees, artist verification, treasury routing and
+    // upgrades behind M-of-N proposals; the admin key can no longer change them alone.
+    pub fn set_council(env: Env, council: Council) {
+        Self::require_council(&env);
+        
+        if council.threshold == 0 || council.threshold > council.members.len() {
+            panic!("Invalid council threshold");
+        }
+        
+        for (i, member) in council.members.iter().enumerate() {
+            if council.members.first_index_of(&member) != Some(i as u32) {
+                panic!("Duplicate council member");
+            }
+        }
+        
+        env.storage().instance().set(&CouncilKey::Council, &council);
+        record_audit(&env, &Self::privileged_actor(&env), "council_set", AuditTarget::Platform);
+        
+        env.events().publish(
+            (Symbol::new(&env, "council_updated"), EVENT_SCHEMA_VERSION),
+            council
+        );
+    }
+
+    pub fn get_council(env: Env) -> Option<Council> {
+        env.storage().instance().get(&CouncilKey::Council)
+    }
+
+    pub fn propose_admin_action(env: Env, member: Address, action: AdminAction) -> u32 {
+        member.require_auth();
+        
+        let council = Self::council_of(&env, &member);
+        
+        let proposal_id: u32 = env.storage().instance()
+            .get(&CouncilKey::ProposalCounter)
+            .unwrap_or(0) + 1;
+        env.storage().instance().set(&CouncilKey::ProposalCounter, &proposal_id);
+        
+        let mut approvals = Vec::new(&env);
+        approvals.push_back(member.clone());
+        
+        let proposal = AdminProposal {
+            proposal_id,
+            action,
+            proposer: member,
+            approvals,
+            created_at: env.ledger().timestamp(),
+            executed: false,
+        };
+        
+        env.events().publish(
+            (Symbol::new(&env, "proposal_created"), EVENT_SCHEMA_VERSION, proposal_id),
+            proposal.proposer.clone()
+        );
+        
+        Self::save_or_execute_proposal(&env, &council, proposal);
+        proposal_id
+    }
+
+    pub fn approve_proposal(env: Env, member: Address, proposal_id: u32) {
+        member.require_auth();
+        
+        let council = Self::council_of(&env, &member);
+        
+        let mut proposal: AdminProposal = env.storage().instance()
+            .get(&CouncilKey::Proposals(proposal_id))
+            .unwrap_or_else(|| panic!("Proposal not found"));
+        
+        if proposal.executed {
+            panic!("Proposal already executed");
+        }
+        
+        if proposal.approvals.contains(&member) {
+            panic!("Already approved");
+        }
+        
+        proposal.approvals.push_back(member.clone());
+        
+        env.events().publish(
+            (Symbol::new(&env, "proposal_approved"), EVENT_SCHEMA_VERSION, proposal_id),
+            member
+        );
+        
+        Self::save_or_execute_proposal(&env, &council, proposal);
+    }
+
+    pub fn get_proposal(env: Env, proposal_id: u32) -> Option<AdminProposal> {
+        env.storage().instance().get(&CouncilKey::Proposals(proposal_id))
+    }
+
+    fn council_of(env: &Env, member: &Address) -> Council {
+        let council: Council = env.storage().instance()
+            .get(&CouncilKey::Council)
+            .unwrap_or_else(|| panic!("No admin council"));
+        
+        if !council.members.contains(member) {
+            panic!("Not a council member");
+        }
+        
+        council
+    }
+
+    fn save_or_execute_proposal(env: &Env, council: &Council, mut proposal: AdminProposal) {
+        // Approvals from members since removed from the council don't count
+        let approvals = proposal.approvals.iter()
+            .filter(|approver| council.members.contains(approver))
+            .count() as u32;
+        
+        if approvals >= council.threshold {
+            proposal.executed = true;
+            
+            env.storage().instance().set(&CouncilKey::Executing, &true);
+            match proposal.action.clone() {
+                AdminAction::UpdatePlatformFee(fee) => {
+                    Self::update_platform_fee(env.clone(), fee);
+                }
+                AdminAction::SetArtistVerified(artist, verified) => {
+                    Self::set_artist_verified(env.clone(), artist, verified)
+                }
+                AdminAction::SetFeeTiers(tiers) => {
+                    Self::set_fee_tiers(env.clone(), tiers);
+                }
+                AdminAction::SetTreasuryTargets(targets) => Self::set_treasury_targets(env.clone(), targets),
+                AdminAction::SetCouncil(new_council) => Self::set_council(env.clone(), new_council),
+                AdminAction::Upgrade(wasm_hash) => Self::upgrade(env.clone(), wasm_hash),
+                AdminAction::UpgradeContract(contract, wasm_hash) => Self::upgrade_contract(env.clone(), contract, wasm_hash),
+                AdminAction::SetTimelockDelay(delay_secs) => {
+                    Self::set_timelock_delay(env.clone(), delay_secs);
+                }
+                AdminAction::CancelParamChange(change_id) => Self::cancel_param_change(env.clone(), change_id),
+                AdminAction::SetFeeOverride(class, fee_bps) => {
+                    Self::set_fee_override(env.clone(), class, fee_bps);
+                }
+            }
+            env.storage().instance().remove(&CouncilKey::Executing);
+            
+            env.events().publish(
+                (Symbol::new(env, "proposal_executed"), EVENT_SCHEMA_VERSION, proposal.proposal_id),
+                ()
+            );
+        }
+        
+        env.storage().instance().set(&CouncilKey::Proposals(proposal.proposal_id), &proposal);
+    }
+
+    // Admin-only until a council is installed, then only reachable by executing a proposal
+    fn require_council(env: &Env) {
+        if env.storage().instance().has(&CouncilKey::Council) {
+            if !env.storage().instance().has(&CouncilKey::Executing) {
+                panic!("Requires a council proposal");
+            }
+            return;
+        }
+        
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+    }
+
+    // Council-approved actions are attributed to the contract itself, since no single
+    // member authorized them
+    fn privileged_actor(env: &Env) -> Address {
+        if env.storage().instance().has(&CouncilKey::Council) {
+            env.current_contract_address()
+        } else {
+            env.storage().instance().get(&DataKey::Admin).unwrap()
+        }
+    }
+
+    // Fee tiers
+    // Tiers are ordered by min_volume; the highest one an artist's trailing volume
+    // reaches replaces the platform fee
+    pub fn set_fee_tiers(env: Env, tiers: Vec<FeeTier>) -> u32 {
+        Self::require_council(&env);
+        
+        let mut last_volume: Option<u32> = None;
+        for tier in tiers.iter() {
+            if tier.fee_bps > 2000 {
+                panic!("Fee too high");
+            }
+            if last_volume.is_some_and(|last| tier.min_volume <= last) {
+                panic!("Tiers must be ordered by volume");
+            }
+            last_volume = Some(tier.min_volume);
+        }
+        
+        Self::queue_param_change(&env, ParamChange::FeeTiers(tiers))
+    }
+
+    // Overrides a table class's platform fee; None queues removal of the override
+    pub fn set_fee_override(env: Env, class: FeeClass, fee_bps: Option<u32>) -> u32 {
+        Self::require_council(&env);
+        
+        if fee_bps.is_some_and(|fee| fee > 2000) {
+            panic!("Fee too high");
+        }
+        
+        Self::queue_param_change(&env, ParamChange::FeeOverride(class, fee_bps))
+    }
+
+    // Treasury
+    pub fn set_treasury_targets(env: Env, targets: Vec<(TreasuryTarget, u32)>) {
+        Self::require_council(&env);
+        
+        let mut total_bps = 0;
+        for (_, bps) in targets.iter() {
+            total_bps += bps;
+        }
+        if total_bps != 10000 {
+            panic!("Treasury targets must total 100%");
+        }
+        
+        record_audit(&env, &Self::privileged_actor(&env), "treasury_targets", AuditTarget::Platform);
+        Self::payouts(&env).apply_treasury_targets(&env.current_contract_address(), &targets);
+    }
+
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        Self::require_council(&env);
+        
+        record_audit(&env, &Self::privileged_actor(&env), "upgrade", AuditTarget::Platform);
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    // The other contracts only accept upgrades from here, so they go through the
+    // same council as this one
+    pub fn upgrade_contract(env: Env, contract: Address, new_wasm_hash: BytesN<32>) {
+        Self::require_council(&env);
+        
+        let links = links(&env);
+        let linked = [&links.catalog, &links.hub, &links.payouts, &links.market, &links.community, &links.venue];
+        if !linked.contains(&&contract) {
+            panic!("Not a linked contract");
+        }
+        
+        record_audit(&env, &Self::privileged_actor(&env), "upgrade", AuditTarget::Account(contract.clone()));
+        UpgradeableClient::new(&env, &contract).upgrade(&new_wasm_hash);
+    }
+
+    // Verification lowers an artist's fee on their own tables, so it sits behind
+    // the council; the catalog only accepts it from here
+    pub fn set_artist_verified(env: Env, artist: Address, verified: bool) {
+        Self::require_council(&env);
+        
+        let action = if verified { "verify" } else { "unverify" };
+        record_audit(&env, &Self::privileged_actor(&env), action, AuditTarget::Account(artist.clone()));
+        CatalogClient::new(&env, &links(&env).catalog).set_artist_verified(&artist, &verified);
+    }
+
+    // Moderation
+    pub fn set_moderator(env: Env, moderator: Address, enabled: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        let action = if enabled { "moderator_add" } else { "moderator_remove" };
+        record_audit(&env, &admin, action, AuditTarget::Account(moderator.clone()));
+        
+        if enabled {
+            env.storage().instance().set(&ModerationKey::Moderators(moderator.clone()), &true);
+        } else {
+            env.storage().instance().remove(&ModerationKey::Moderators(moderator.clone()));
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "moderator_set"), EVENT_SCHEMA_VERSION, moderator),
+            enabled
+        );
+    }
+
+    pub fn is_moderator(env: Env, moderator: Address) -> bool {
+        env.storage().instance().has(&ModerationKey::Moderators(moderator))
+    }
+
+    pub fn report_track(
+        env: Env,
+        user: Address,
+        track_id: BytesN<32>,
+        category: ReportCategory,
+        evidence_uri: String,
+    ) -> u32 {
+        user.require_auth();
+        
+        require_active_user(&env, &user);
+        
+        if Self::catalog(&env).get_track(&track_id).is_none() {
+            panic!("Track not found");
+        }
+        
+        if env.storage().instance().has(&ModerationKey::OpenReport(track_id.clone(), user.clone())) {
+            panic!("Report already open");
+        }
+        
+        let report_id: u32 = env.storage().instance()
+            .get(&ModerationKey::ReportCounter)
+            .unwrap_or(0) + 1;
+        env.storage().instance().set(&ModerationKey::ReportCounter, &report_id);
+        
+        let report = Report {
+            report_id,
+            reporter: user.clone(),
+            track_id: track_id.clone(),
+            category,
+            evidence_uri,
+            status: ReportStatus::Open,
+            filed_at: env.ledger().timestamp(),
+            resolved_by: None,
+        };
+        env.storage().instance().set(&ModerationKey::Reports(report_id), &report);
+        env.storage().instance().set(&ModerationKey::OpenReport(track_id.clone(), user), &report_id);
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_reported"), EVENT_SCHEMA_VERSION, track_id),
+            (report_id, category)
+        );
+        
+        report_id
+    }
+
+    // An accepted report is a strike against the track's artist; each strike costs
+    // more reputation than the last so repeat offenders fall off quickly
+    pub fn resolve_report(env: Env, moderator: Address, report_id: u32, accept: bool) {
+        moderator.require_auth();
+        
+        if !Self::is_moderator(env.clone(), moderator.clone()) {
+            panic!("Not a moderator");
+        }
+        
+        let mut report: Report = env.storage().instance()
+            .get(&ModerationKey::Reports(report_id))
+            .unwrap_or_else(|| panic!("Report not found"));
+        
+        if report.status != ReportStatus::Open {
+            panic!("Report already resolved");
+        }
+        
+        report.status = if accept { ReportStatus::Accepted } else { ReportStatus::Dismissed };
+        report.resolved_by = Some(moderator.clone());
+        let action = if accept { "report_accept" } else { "report_dismiss" };
+        record_audit(&env, &moderator, action, AuditTarget::Report(report_id));
+        env.storage().instance().set(&ModerationKey::Reports(report_id), &report);
+        env.storage().instance().remove(&ModerationKey::OpenReport(report.track_id.clone(), report.reporter.clone()));
+        
+        if accept {
+            let track = Self::catalog(&env).get_track(&report.track_id).unwrap();
+            
+            let strikes = Self::get_strikes(env.clone(), track.artist_id.clone()) + 1;
+            env.storage().instance().set(&ModerationKey::Strikes(track.artist_id.clone()), &strikes);
+            
+            let penalty = (STRIKE_REPUTATION_PENALTY * strikes).min(i32::MAX as u32) as i32;
+            Self::catalog(&env).adjust_reputation(
+                &env.current_contract_address(),
+                &track.artist_id,
+                &-penalty,
+                &Symbol::new(&env, "strike"),
+            );
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "report_resolved"), EVENT_SCHEMA_VERSION, report_id),
+            accept
+        );
+    }
+
+    pub fn get_report(env: Env, report_id: u32) -> Option<Report> {
+        env.storage().instance().get(&ModerationKey::Reports(report_id))
+    }
+
+    pub fn get_strikes(env: Env, artist: Address) -> u32 {
+        env.storage().instance()
+            .get(&ModerationKey::Strikes(artist))
+            .unwrap_or(0)
+    }
+
+    // Disputes
+    pub fn set_arbitrator(env: Env, arbitrator: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        env.storage().instance().set(&DisputeKey::Arbitrator, &arbitrator);
+        record_audit(&env, &admin, "arbitrator_set", AuditTarget::Account(arbitrator.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "arbitrator_set"), EVENT_SCHEMA_VERSION),
+            arbitrator
+        );
+    }
+
+    pub fn get_arbitrator(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DisputeKey::Arbitrator)
+    }
+
+    // Has payouts pull what the request's settlement credited back out of unclaimed
+    // accruals, vesting and the treasury, and holds it until the arbitrator rules or
+    // the deadline passes. Only possible within DISPUTE_OPEN_WINDOW of settling.
+    pub fn open_dispute(env: Env, caller: Address, request_id: BytesN<32>, reason_hash: BytesN<32>) {
+        caller.require_auth();
+        
+        let request = HubClient::new(&env, &links(&env).hub)
+            .get_request(&request_id)
+            .unwrap_or_else(|| panic!("Request not found"));
+        let track = Self::catalog(&env).get_track(&request.track_id).unwrap();
+        
+        if caller != request.requester && caller != track.artist_id {
+            panic!("Not a party to the request");
+        }
+        
+        if env.storage().instance().has(&DisputeKey::Disputes(request_id.clone())) {
+            panic!("Dispute already opened");
+        }
+        
+        if request.amount_paid <= 0 {
+            panic!("Nothing to dispute");
+        }
+        
+        if request.payment_token != Self::default_token(&env) {
+            panic!("Only default-token payments can be disputed");
+        }
+        
+        // Still-queued payments sit in escrow and are refunded, not disputed
+        let held = Self::payouts(&env).hold_settlement(
+            &env.current_contract_address(),
+            &request_id,
+            &track.track_id,
+        );
+        
+        if env.ledger().timestamp() > held.settled_at + DISPUTE_OPEN_WINDOW {
+            panic!("Dispute window closed");
+        }
+        
+        let dispute = Dispute {
+            request_id: request_id.clone(),
+            opened_by: caller,
+            reason_hash,
+            held: held.shares,
+            held_vested: held.vested,
+            held_fee: held.fee,
+            settled_at: held.settled_at,
+            deadline: env.ledger().timestamp() + DISPUTE_WINDOW,
+            status: DisputeStatus::Open,
+        };
+        env.storage().instance().set(&DisputeKey::Disputes(request_id.clone()), &dispute);
+        
+        env.events().publish(
+            (Symbol::new(&env, "dispute_opened"), EVENT_SCHEMA_VERSION, request_id),
+            dispute.deadline
+        );
+    }
+
+    pub fn rule_dispute(env: Env, arbitrator: Address, request_id: BytesN<32>, refund: bool) {
+        arbitrator.require_auth();
+        
+        if Self::get_arbitrator(env.clone()) != Some(arbitrator) {
+            panic!("Not the arbitrator");
+        }
+        
+        Self::close_dispute(&env, &request_id, refund);
+    }
+
+    // Disputes the arbitrator never ruled on default to paying the recipients
+    pub fn settle_expired_dispute(env: Env, request_id: BytesN<32>) {
+        let dispute: Dispute = env.storage().instance()
+            .get(&DisputeKey::Disputes(request_id.clone()))
+            .unwrap_or_else(|| panic!("Dispute not found"));
+        
+        if env.ledger().timestamp() < dispute.deadline {
+            panic!("Dispute still open");
+        }
+        
+        Self::close_dispute(&env, &request_id, false);
+    }
+
+    pub fn get_dispute(env: Env, request_id: BytesN<32>) -> Option<Dispute> {
+        env.storage().instance().get(&DisputeKey::Disputes(request_id))
+    }
+
+    fn close_dispute(env: &Env, request_id: &BytesN<32>, refund: bool) {
+        let mut dispute: Dispute = env.storage().instance()
+            .get(&DisputeKey::Disputes(request_id.clone()))
+            .unwrap_or_else(|| panic!("Dispute not found"));
+        
+        if dispute.status != DisputeStatus::Open {
+            panic!("Dispute already closed");
+        }
+        
+        let request = HubClient::new(env, &links(env).hub).get_request(request_id).unwrap();
+        
+        let held = Settlement {
+            shares: dispute.held.clone(),
+            vested: dispute.held_vested.clone(),
+            fee: dispute.held_fee,
+            settled_at: dispute.settled_at,
+        };
+        let refund_to = if refund { Some(request.requester) } else { None };
+        Self::payouts(env).release_settlement(&env.current_contract_address(), &request.track_id, &held, &refund_to);
+        dispute.status = if refund { DisputeStatus::Refunded } else { DisputeStatus::Released };
+        
+        env.storage().instance().set(&DisputeKey::Disputes(request_id.clone()), &dispute);
+        
+        env.events().publish(
+            (Symbol::new(env, "dispute_closed"), EVENT_SCHEMA_VERSION, request_id.clone()),
+            refund
+        );
+    }
+
+
+
+    fn catalog(env: &Env) -> CatalogClient<'_> {
+        CatalogClient::new(env, &links(env).catalog)
+    }
+
+    fn payouts(env: &Env) -> PayoutsClient<'_> {
+        PayoutsClient::new(env, &links(env).payouts)
+    }
+
+    fn default_token(env: &Env) -> Address {
+        env.storage().instance().get(&DataKey::TokenStellar).unwrap()
+    }
+}
@@ -0,0 +1,117 @@
+#![no_std]
+#![allow(clippy::too_many_arguments)]
+use soroban_sdk::{contract, contractclient, contractimpl, Address, BytesN, Env, String, Symbol, Vec};
+
+mod test;
+
+// The slices of the deployment's interfaces the harness drives. Declared independently
+// of the contract crates so a signature change on either side shows up as a failed call.
+#[contractclient(name = "CatalogClient")]
+pub trait CatalogInterface {
+    fn register_user(env: Env, user: Address, avatar_uri: String, referrer: Option<Address>);
+    fn register_artist(env: Env, user: Address, artist_name: String);
+    fn mint_track(
+        env: Env,
+        artist: Address,
+        title: String,
+        base_price: i128,
+        licenses: u32,
+        metadata_uri: String,
+        collaborators: Vec<Address>,
+        royalty_split: Vec<(Address, u32)>,
+        public_after: Option<u64>,
+        content_hash: Option<BytesN<32>>,
+        tags: Vec<Symbol>,
+    ) -> BytesN<32>;
+}
+
+#[contractclient(name = "HubClient")]
+pub trait HubInterface {
+    fn request_track(env: Env, requester: Address, track_id: BytesN<32>, table_id: BytesN<32>) -> BytesN<32>;
+}
+
+#[contractclient(name = "PayoutsClient")]
+pub trait PayoutsInterface {
+    fn withdraw_revenue(env: Env, artist: Address) -> i128;
+}
+
+#[contract]
+pub struct JukeHarness;
+
+// Each entry point authorizes the caller here and then invokes the deployment, so its
+// require_auth calls are satisfied as sub-invocations of this contract.
+#[contractimpl]
+impl JukeHarness {
+    pub fn register(env: Env, catalog: Address, user: Address, avatar_uri: String) {
+        user.require_auth();
+
+        match CatalogClient::new(&env, &catalog).try_register_user(&user, &avatar_uri, &None) {
+            Ok(Ok(())) => {}
+            _ => panic!("register_user failed"),
+        }
+    }
+
+    pub fn register_artist(env: Env, catalog: Address, user: Address, artist_name: String) {
+        user.require_auth();
+
+        match CatalogClient::new(&env, &catalog).try_register_artist(&user, &artist_name) {
+            Ok(Ok(())) => {}
+            _ => panic!("register_artist failed"),
+        }
+    }
+
+    pub fn mint(
+        env: Env,
+        catalog: Address,
+        artist: Address,
+        title: String,
+        base_price: i128,
+        licenses: u32,
+        metadata_uri: String,
+    ) -> BytesN<32> {
+        artist.require_auth();
+
+        let mut royalty_split = Vec::new(&env);
+        royalty_split.push_back((artist.clone(), 100u32));
+
+        match CatalogClient::new(&env, &catalog).try_mint_track(
+            &artist,
+            &title,
+            &base_price,
+            &licenses,
+            &metadata_uri,
+            &Vec::new(&env),
+            &royalty_split,
+            &None,
+            &None,
+            &Vec::new(&env),
+        ) {
+            Ok(Ok(track_id)) => track_id,
+            _ => panic!("mint_track failed"),
+        }
+    }
+
+    pub fn request(
+        env: Env,
+        hub: Address,
+        requester: Address,
+        track_id: BytesN<32>,
+        table_id: BytesN<32>,
+    ) -> BytesN<32> {
+        requester.require_auth();
+
+        match HubClient::new(&env, &hub).try_request_track(&requester, &track_id, &table_id) {
+            Ok(Ok(request_id)) => request_id,
+            _ => panic!("request_track failed"),
+        }
+    }
+
+    pub fn withdraw(env: Env, payouts: Address, artist: Address) -> i128 {
+        artist.require_auth();
+
+        match PayoutsClient::new(&env, &payouts).try_withdraw_revenue(&artist) {
+            Ok(Ok(amount)) => amount,
+            _ => panic!("withdraw_revenue failed"),
+        }
+    }
+}
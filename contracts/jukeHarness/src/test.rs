@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use crate::{JukeHarness, JukeHarnessClient};
+use meta_juke::fixtures::{self, Fixture};
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction};
+use soroban_sdk::{Address, String};
+
+fn harness(fixture: &Fixture) -> JukeHarnessClient<'_> {
+    let harness_id = fixture.env.register(JukeHarness, ());
+    JukeHarnessClient::new(&fixture.env, &harness_id)
+}
+
+#[test]
+fn register_propagates_user_auth() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let harness = harness(&fixture);
+
+    let user = Address::generate(env);
+
+    harness.register(&fixture.links.catalog, &user, &String::from_str(env, "ipfs://harness"));
+    assert!(fixture.client().catalog.get_user(&user).is_some());
+
+    harness.register_artist(&fixture.links.catalog, &user, &String::from_str(env, "Harness"));
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, user);
+    assert!(matches!(auths[0].1.function, AuthorizedFunction::Contract((ref id, _, _)) if *id == harness.address));
+    assert_eq!(auths[0].1.sub_invocations.len(), 1);
+    assert!(fixture.client().catalog.get_artist(&user).is_some());
+}
+
+#[test]
+fn withdraw_through_harness() {
+    let fixture = fixtures::setup();
+    let harness = harness(&fixture);
+    let artist = fixture.artists.get(0).unwrap();
+
+    assert_eq!(harness.withdraw(&fixture.links.payouts, &artist), 0);
+    assert!(harness.try_withdraw(&fixture.links.payouts, &fixture.listeners.get(0).unwrap()).is_err());
+}
+
+#[test]
+fn request_through_harness() {
+    let fixture = fixtures::setup();
+    let harness = harness(&fixture);
+    let listener = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    let first = harness.request(&fixture.links.hub, &listener, &track_id, &table_id);
+    let second = harness.request(&fixture.links.hub, &listener, &track_id, &table_id);
+    assert_ne!(first, second);
+    assert_eq!(fixture.client().community.get_user_request_count(&listener), 4);
+    assert_eq!(fixture.client().hub.get_queue(&table_id).last(), Some(track_id));
+}
+
+#[test]
+fn mint_and_request_through_harness() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let harness = harness(&fixture);
+    let artist = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    let track_id = harness.mint(
+        &fixture.links.catalog,
+        &artist,
+        &String::from_str(env, "Harness Track"),
+        &fixtures::FIXTURE_TRACK_PRICE,
+        &10,
+        &String::from_str(env, "ipfs://harness-track"),
+    );
+    assert!(fixture.client().catalog.get_track(&track_id).is_some());
+
+    harness.request(&fixture.links.hub, &listener, &track_id, &table_id);
+    assert_eq!(fixture.client().hub.get_queue(&table_id).last(), Some(track_id));
+}
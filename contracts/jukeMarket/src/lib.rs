@@ -0,0 +1,1519 @@
+#![no_std]
+#![allow(clippy::too_many_arguments)]
+use juke_common::{
+    links, record_audit, require_active_user, require_flag, require_not_paused, require_sibling, validation, Artist,
+    AuditEntry, AuditTarget, CatalogClient, HubClient, JukeboxTable, Links, PayoutsClient, Track, TrackRequest,
+    CONTRACT_VERSION, EVENT_SCHEMA_VERSION, PAUSE_REQUESTS,
+};
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contractmeta, contracttype, token, xdr::ToXdr, Address, Bytes, BytesN, Env,
+    String, Symbol, Vec,
+};
+
+contractmeta!(key = "binver", val = "0.1.0");
+
+// ----- Data Structures -----
+#[contracttype]
+#[derive(Clone)]
+pub struct LicenseAuction {
+    auction_id: BytesN<32>,
+    track_id: BytesN<32>,
+    seller: Address,
+    licenses: u32,
+    exclusive: bool,
+    start_price: i128,
+    floor_price: i128,
+    decay_per_ledger: i128,
+    start_ledger: u32,
+    buyer: Option<Address>,
+    is_active: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct InsuranceTerms {
+    premium_bps: u32,
+    coverage_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct License {
+    license_id: BytesN<32>,
+    track_id: BytesN<32>,
+    class: LicenseClass,
+    owner: Address,
+    units: u32,
+    issued_at: u64,
+    valid_until: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct LicenseListing {
+    license_id: BytesN<32>,
+    seller: Address,
+    price: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TourStop {
+    pub table_id: BytesN<32>,
+    pub starts_at: u64,
+    pub ends_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Tour {
+    pub tour_id: BytesN<32>,
+    pub artist: Address,
+    pub name: String,
+    pub promo_price: i128,
+    pub stops: Vec<TourStop>,
+    pub total_plays: u32,
+    pub total_revenue: i128,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Takeover {
+    pub tour_id: BytesN<32>,
+    pub artist: Address,
+    pub starts_at: u64,
+    pub ends_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct LoyaltyConfig {
+    pub earn_bps: u32,
+    pub points_per_request: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LicenseClass {
+    JukeboxPlay,
+    Sync,
+    Exclusive,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct LicenseSupply {
+    price: i128,
+    remaining: u32,
+    sold: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TimeLicenseTerms {
+    price: i128,
+    period_secs: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TrackProof {
+    pub source: Address,
+    pub track_id: BytesN<32>,
+    pub artist: Address,
+    pub title: String,
+    pub metadata_uri: String,
+    pub royalty_split: Vec<(Address, u32)>,
+    pub play_count: u32,
+    pub licenses_sold: u32,
+    pub exported_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SubscriptionTier {
+    pub price: i128,
+    pub period_secs: u64,
+    pub benefits_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Subscription {
+    pub tier: u32,
+    pub started_at: u64,
+    pub expires_at: u64,
+}
+
+// Any Juke market deployment; used to check proofs exported by another instance
+#[contractclient(name = "TrackProofSourceClient")]
+pub trait TrackProofSource {
+    fn is_proof_anchored(env: Env, digest: BytesN<32>) -> bool;
+}
+
+// ----- Storage Keys -----
+// Keys encode as the variant name plus its fields, without the enum name, so a
+// variant must not reuse a name and field types already taken by another key enum
+// here or in juke_common.
+#[contracttype]
+enum DataKey {
+    Admin,
+    TokenStellar,
+    Auctions(BytesN<32>),
+    AuctionIdCounter,
+    AuctionLicenses(BytesN<32>, Address),
+    ExclusiveHolder(BytesN<32>),
+    InsuranceTerms,
+    InsurancePool,
+    InsuredRequests(BytesN<32>),
+    Licenses(BytesN<32>),
+    LicenseIdCounter,
+    LicenseListings(BytesN<32>),
+    ResaleRoyaltyBps,
+    Tours(BytesN<32>),
+    TourIdCounter,
+    TableTakeovers(BytesN<32>),
+    AcceptsTakeovers(BytesN<32>),
+    LicenseClasses(BytesN<32>, LicenseClass),
+}
+
+#[contracttype]
+enum SubscriptionKey {
+    Tiers(Address),
+    Subscriptions(Address, Address),
+}
+
+#[contracttype]
+enum LicenseKey {
+    TimeLicenseTerms(BytesN<32>),
+    TimeLicenses(BytesN<32>, Address),
+}
+
+#[contracttype]
+enum TrackKey {
+    ProofAnchors(BytesN<32>),
+    EarlyAccess(BytesN<32>, Address),
+    Isrc(BytesN<32>),
+    IsrcToTrack(String),
+    Iswc(BytesN<32>),
+    IswcToTrack(String),
+}
+
+#[contracttype]
+enum ConfigKey {
+    ProofSources,
+}
+
+#[contracttype]
+enum LoyaltyKey {
+    LoyaltyConfig,
+    Points(Address),
+    AcceptingTables(BytesN<32>),
+}
+
+// Subscriptions, licenses and their resale market, auctions, insurance, tours,
+// loyalty points, and track identifiers and proofs
+#[contract]
+pub struct JukeMarket;
+
+#[contractimpl]
+impl JukeMarket {
+    pub fn initialize(env: Env, admin: Address, token_stellar: Address, links: Links) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::TokenStellar, &token_stellar);
+        juke_common::set_links(&env, &links);
+        
+        env.events().publish(
+            (Symbol::new(&env, "initialized"), EVENT_SCHEMA_VERSION),
+            (admin, token_stellar)
+        );
+    }
+
+    pub fn get_version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
+
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        links(&env).governance.require_auth();
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    // Trusting a proof source is audited here
+    pub fn get_audit_count(env: Env) -> u32 {
+        juke_common::audit_count(&env)
+    }
+
+    pub fn get_audit_log(env: Env, start: u32, limit: u32) -> Vec<AuditEntry> {
+        juke_common::audit_log(&env, start, limit)
+    }
+
+    // Linked contracts
+    // The hub reports each request once it is queued: the play draws on a time
+    // license, then on licenses won at auction, and otherwise is issued one
+    pub fn record_request(env: Env, caller: Address, request: TrackRequest, artist: Address, default_paid: i128, insured: bool) {
+        require_sibling(&env, &caller);
+        
+        let requester = &request.requester;
+        if insured && request.amount_paid > 0 {
+            Self::insure_request(&env, requester, &request.request_id, request.amount_paid);
+        }
+        
+        if !Self::is_license_valid(env.clone(), requester.clone(), request.track_id.clone()) {
+            let auction_key = DataKey::AuctionLicenses(request.track_id.clone(), requester.clone());
+            let auction_held: u32 = env.storage().instance().get(&auction_key).unwrap_or(0);
+            if auction_held > 0 {
+                // Auction licenses were issued when the auction settled
+                env.storage().instance().set(&auction_key, &(auction_held - 1));
+            } else {
+                Self::issue_license(&env, &request.track_id, LicenseClass::JukeboxPlay, requester, 1, 0);
+            }
+        }
+        
+        Self::record_tour_play(&env, &request.table_id, &artist, default_paid);
+    }
+
+    // A request's no-show cover lapses once it plays or is refunded; a claim pays
+    // it out first
+    pub fn close_coverage(env: Env, caller: Address, request: TrackRequest, claim: bool) {
+        require_sibling(&env, &caller);
+        
+        if claim {
+            Self::pay_insurance_claim(&env, &request);
+        }
+        env.storage().instance().remove(&DataKey::InsuredRequests(request.request_id));
+    }
+
+    // Tour takeovers sell the touring artist's catalogue at the tour's promo price
+    pub fn get_takeover_price(env: Env, table_id: BytesN<32>, artist: Address) -> Option<i128> {
+        let takeover = Self::active_takeover(&env, &table_id, &artist)?;
+        let tour: Tour = env.storage().instance()
+            .get(&DataKey::Tours(takeover.tour_id))
+            .unwrap();
+        Some(tour.promo_price)
+    }
+
+    // Subscriptions
+    pub fn set_subscription_tiers(env: Env, artist: Address, tiers: Vec<SubscriptionTier>) {
+        artist.require_auth();
+        
+        if Self::catalog(&env).get_artist(&artist).is_none() {
+            panic!("Not registered as artist");
+        }
+        
+        for tier in tiers.iter() {
+            if tier.price <= 0 || tier.period_secs == 0 {
+                panic!("Invalid subscription tier");
+            }
+        }
+        
+        env.storage().instance().set(&SubscriptionKey::Tiers(artist.clone()), &tiers);
+        
+        env.events().publish(
+            (Symbol::new(&env, "subscription_tiers_set"), EVENT_SCHEMA_VERSION, artist),
+            tiers.len()
+        );
+    }
+
+    pub fn get_subscription_tiers(env: Env, artist: Address) -> Vec<SubscriptionTier> {
+        env.storage().instance()
+            .get(&SubscriptionKey::Tiers(artist))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Renewing an active subscription stacks the new period onto the current expiry
+    pub fn subscribe(env: Env, user: Address, artist: Address, tier: u32) -> u64 {
+        user.require_auth();
+        
+        require_active_user(&env, &user);
+        
+        let terms = Self::get_subscription_tiers(env.clone(), artist.clone())
+            .get(tier)
+            .unwrap_or_else(|| panic!("Subscription tier not found"));
+        
+        if Self::catalog(&env).get_artist(&artist).is_none() {
+            panic!("Artist not found");
+        }
+        
+        // Renewing extends the current tier; switching waits for it to run out so
+        // no paid time is forfeited
+        let now = env.ledger().timestamp();
+        let key = SubscriptionKey::Subscriptions(user.clone(), artist.clone());
+        let current: Option<Subscription> = env.storage().instance().get(&key);
+        if current.as_ref().is_some_and(|current| current.tier != tier && current.expires_at > now) {
+            panic!("Current tier still active");
+        }
+        
+        Self::collect_payment(&env, &user, terms.price)
+            .collect_artist_payment(&env.current_contract_address(), &artist, &terms.price);
+        
+        let subscription = match current {
+            Some(current) if current.tier == tier && current.expires_at > now => Subscription {
+                tier,
+                started_at: current.started_at,
+                expires_at: current.expires_at + terms.period_secs,
+            },
+            _ => Subscription {
+                tier,
+                started_at: now,
+                expires_at: now + terms.period_secs,
+            },
+        };
+        env.storage().instance().set(&key, &subscription);
+        
+        env.events().publish(
+            (Symbol::new(&env, "subscribed"), EVENT_SCHEMA_VERSION, artist, user),
+            (tier, subscription.expires_at)
+        );
+        
+        subscription.expires_at
+    }
+
+    pub fn get_subscription(env: Env, user: Address, artist: Address) -> Option<Subscription> {
+        env.storage().instance().get(&SubscriptionKey::Subscriptions(user, artist))
+    }
+
+    pub fn is_subscriber(env: Env, user: Address, artist: Address) -> bool {
+        Self::get_subscription(env.clone(), user, artist)
+            .map(|subscription| subscription.expires_at > env.ledger().timestamp())
+            .unwrap_or(false)
+    }
+
+    // Early access
+    pub fn set_early_access(env: Env, artist: Address, track_id: BytesN<32>, user: Address, allowed: bool) {
+        artist.require_auth();
+        
+        let track: Track = Self::catalog(&env).get_track(&track_id)
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track artist");
+        }
+        
+        let key = TrackKey::EarlyAccess(track_id.clone(), user.clone());
+        if allowed {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "early_access_set"), EVENT_SCHEMA_VERSION, track_id, user),
+            allowed
+        );
+    }
+
+    // Before the public release only subscribers to the artist and allowlisted
+    // users can request the track
+    pub fn has_early_access(env: Env, user: Address, track_id: BytesN<32>) -> bool {
+        let embargoed = Self::catalog(&env).get_public_after(&track_id)
+            .map(|public_after| env.ledger().timestamp() < public_after)
+            .unwrap_or(false);
+        if !embargoed {
+            return true;
+        }
+        
+        if env.storage().instance().has(&TrackKey::EarlyAccess(track_id.clone(), user.clone())) {
+            return true;
+        }
+        
+        let track: Track = Self::catalog(&env).get_track(&track_id).unwrap();
+        Self::is_subscriber(env, user, track.artist_id)
+    }
+
+    // Industry identifiers, stored unformatted (12-char ISRC, 11-char ISWC)
+    // Passing None clears an identifier; each one can belong to a single track
+    pub fn set_track_identifiers(
+        env: Env,
+        artist: Address,
+        track_id: BytesN<32>,
+        isrc: Option<String>,
+        iswc: Option<String>,
+    ) {
+        artist.require_auth();
+        
+        let track: Track = Self::catalog(&env).get_track(&track_id)
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track artist");
+        }
+        
+        let current_isrc: Option<String> = env.storage().instance().get(&TrackKey::Isrc(track_id.clone()));
+        if let Some(current) = current_isrc {
+            env.storage().instance().remove(&TrackKey::IsrcToTrack(current));
+            env.storage().instance().remove(&TrackKey::Isrc(track_id.clone()));
+        }
+        if let Some(isrc) = isrc {
+            if isrc.len() != 12 {
+                panic!("Invalid ISRC");
+            }
+            if env.storage().instance().has(&TrackKey::IsrcToTrack(isrc.clone())) {
+                panic!("ISRC already registered");
+            }
+            env.storage().instance().set(&TrackKey::Isrc(track_id.clone()), &isrc);
+            env.storage().instance().set(&TrackKey::IsrcToTrack(isrc), &track_id);
+        }
+        
+        let current_iswc: Option<String> = env.storage().instance().get(&TrackKey::Iswc(track_id.clone()));
+        if let Some(current) = current_iswc {
+            env.storage().instance().remove(&TrackKey::IswcToTrack(current));
+            env.storage().instance().remove(&TrackKey::Iswc(track_id.clone()));
+        }
+        if let Some(iswc) = iswc {
+            if iswc.len() != 11 {
+                panic!("Invalid ISWC");
+            }
+            if env.storage().instance().has(&TrackKey::IswcToTrack(iswc.clone())) {
+                panic!("ISWC already registered");
+            }
+            env.storage().instance().set(&TrackKey::Iswc(track_id.clone()), &iswc);
+            env.storage().instance().set(&TrackKey::IswcToTrack(iswc), &track_id);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_identifiers_set"), EVENT_SCHEMA_VERSION, track_id.clone()),
+            (Self::get_isrc(env.clone(), track_id.clone()), Self::get_iswc(env.clone(), track_id))
+        );
+    }
+
+    pub fn get_isrc(env: Env, track_id: BytesN<32>) -> Option<String> {
+        env.storage().instance().get(&TrackKey::Isrc(track_id))
+    }
+
+    pub fn get_iswc(env: Env, track_id: BytesN<32>) -> Option<String> {
+        env.storage().instance().get(&TrackKey::Iswc(track_id))
+    }
+
+    pub fn get_track_by_isrc(env: Env, isrc: String) -> Option<BytesN<32>> {
+        env.storage().instance().get(&TrackKey::IsrcToTrack(isrc))
+    }
+
+    pub fn get_track_by_iswc(env: Env, iswc: String) -> Option<BytesN<32>> {
+        env.storage().instance().get(&TrackKey::IswcToTrack(iswc))
+    }
+
+    pub fn export_track_proof(env: Env, track_id: BytesN<32>) -> TrackProof {
+        let track: Track = Self::catalog(&env).get_track(&track_id)
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        track.artist_id.require_auth();
+        
+        let proof = TrackProof {
+            source: env.current_contract_address(),
+            track_id: track_id.clone(),
+            artist: track.artist_id,
+            title: track.title,
+            metadata_uri: track.metadata_uri,
+            royalty_split: track.royalty_split,
+            play_count: track.play_count,
+            licenses_sold: track.licenses_sold,
+            exported_at: env.ledger().timestamp(),
+        };
+        
+        // Anchoring the digest here lets any other contract check the proof against
+        // this deployment's state instead of trusting whoever relays it
+        let digest = Self::track_proof_digest(&env, &proof);
+        env.storage().instance().set(&TrackKey::ProofAnchors(digest.clone()), &true);
+        
+        env.events().publish(
+            (Symbol::new(&env, "track_proof_exported"), EVENT_SCHEMA_VERSION, track_id),
+            digest
+        );
+        
+        proof
+    }
+
+    // Other deployments are only asked to vouch for a proof once the admin has
+    // listed them as trusted; anyone can stand up a contract that anchors anything
+    pub fn verify_track_proof(env: Env, proof: TrackProof) -> bool {
+        let digest = Self::track_proof_digest(&env, &proof);
+        
+        if proof.source == env.current_contract_address() {
+            return Self::is_proof_anchored(env, digest);
+        }
+        
+        if !Self::get_trusted_proof_sources(env.clone()).contains(&proof.source) {
+            return false;
+        }
+        
+        TrackProofSourceClient::new(&env, &proof.source).is_proof_anchored(&digest)
+    }
+
+    pub fn set_proof_source_trusted(env: Env, source: Address, trusted: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        let mut sources = Self::get_trusted_proof_sources(env.clone());
+        match (sources.first_index_of(&source), trusted) {
+            (None, true) => sources.push_back(source.clone()),
+            (Some(index), false) => {
+                sources.remove(index);
+            }
+            _ => return,
+        }
+        env.storage().instance().set(&ConfigKey::ProofSources, &sources);
+        
+        let action = if trusted { "proof_source_trusted" } else { "proof_source_removed" };
+        record_audit(&env, &admin, action, AuditTarget::Account(source.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "proof_source_trusted"), EVENT_SCHEMA_VERSION, source),
+            trusted
+        );
+    }
+
+    pub fn get_trusted_proof_sources(env: Env) -> Vec<Address> {
+        env.storage().instance()
+            .get(&ConfigKey::ProofSources)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn is_proof_anchored(env: Env, digest: BytesN<32>) -> bool {
+        env.storage().instance().has(&TrackKey::ProofAnchors(digest))
+    }
+
+    fn track_proof_digest(env: &Env, proof: &TrackProof) -> BytesN<32> {
+        env.crypto().sha256(&proof.clone().to_xdr(env)).into()
+    }
+
+    // Loyalty points
+    pub fn set_loyalty_config(env: Env, config: LoyaltyConfig) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        env.storage().instance().set(&LoyaltyKey::LoyaltyConfig, &config);
+        
+        env.events().publish(
+            (Symbol::new(&env, "loyalty_config_set"), EVENT_SCHEMA_VERSION),
+            config
+        );
+    }
+
+    pub fn get_loyalty_config(env: Env) -> LoyaltyConfig {
+        env.storage().instance()
+            .get(&LoyaltyKey::LoyaltyConfig)
+            .unwrap_or(LoyaltyConfig {
+                earn_bps: 0,
+                points_per_request: 0,
+            })
+    }
+
+    pub fn set_table_loyalty(env: Env, owner: Address, table_id: BytesN<32>, accepts_points: bool) {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env).get_table(&table_id).unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        if accepts_points {
+            env.storage().instance().set(&LoyaltyKey::AcceptingTables(table_id.clone()), &true);
+        } else {
+            env.storage().instance().remove(&LoyaltyKey::AcceptingTables(table_id.clone()));
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "table_loyalty_set"), EVENT_SCHEMA_VERSION, table_id),
+            accepts_points
+        );
+    }
+
+    pub fn accepts_loyalty_points(env: Env, table_id: BytesN<32>) -> bool {
+        env.storage().instance().has(&LoyaltyKey::AcceptingTables(table_id))
+    }
+
+    pub fn get_loyalty_points(env: Env, user: Address) -> u64 {
+        env.storage().instance().get(&LoyaltyKey::Points(user)).unwrap_or(0)
+    }
+
+    // The hub reports each settled default-token payment and spends points on a
+    // redeemed request before placing it
+    pub fn award_loyalty_points(env: Env, caller: Address, user: Address, amount_paid: i128) {
+        require_sibling(&env, &caller);
+        
+        let earned = (amount_paid * Self::get_loyalty_config(env.clone()).earn_bps as i128) / 10000;
+        if earned <= 0 {
+            return;
+        }
+        
+        let points = Self::get_loyalty_points(env.clone(), user.clone()) + earned as u64;
+        env.storage().instance().set(&LoyaltyKey::Points(user), &points);
+    }
+
+    pub fn spend_loyalty_points(env: Env, caller: Address, user: Address, table_id: BytesN<32>) -> u64 {
+        require_sibling(&env, &caller);
+        
+        if !Self::accepts_loyalty_points(env.clone(), table_id) {
+            panic!("Table does not accept loyalty points");
+        }
+        
+        let cost = Self::get_loyalty_config(env.clone()).points_per_request as u64;
+        if cost == 0 {
+            panic!("Loyalty redemption disabled");
+        }
+        
+        let points = Self::get_loyalty_points(env.clone(), user.clone());
+        if points < cost {
+            panic!("Not enough loyalty points");
+        }
+        env.storage().instance().set(&LoyaltyKey::Points(user), &(points - cost));
+        
+        cost
+    }
+
+    // License auctions
+    pub fn create_license_auction(
+        env: Env,
+        artist: Address,
+        track_id: BytesN<32>,
+        licenses: u32,
+        exclusive: bool,
+        start_price: i128,
+        floor_price: i128,
+        decay_per_ledger: i128,
+    ) -> BytesN<32> {
+        artist.require_auth();
+        require_flag(&env, "auctions");
+        
+        let mut track: Track = Self::catalog(&env).get_track(&track_id).unwrap();
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        Self::catalog(&env).require_track_live(&track_id);
+        
+        if env.storage().instance().has(&DataKey::ExclusiveHolder(track_id.clone())) {
+            panic!("Track is under an exclusive license");
+        }
+        
+        if floor_price < 0 || start_price < floor_price || decay_per_ledger < 0 {
+            panic!("Invalid auction pricing");
+        }
+        
+        // An exclusive license takes every remaining license off the jukebox market
+        let licenses = if exclusive { track.licenses_remaining } else { licenses };
+        if licenses == 0 || licenses > track.licenses_remaining {
+            panic!("Not enough licenses remaining");
+        }
+        
+        track.licenses_remaining -= licenses;
+        Self::catalog(&env).save_track(&env.current_contract_address(), &track);
+        
+        let mut auction_counter: u32 = env.storage().instance()
+            .get(&DataKey::AuctionIdCounter)
+            .unwrap_or(0);
+        auction_counter += 1;
+        
+        let mut auction_id_bytes = Bytes::from_slice(&env, b"auction_");
+        auction_id_bytes.append(&track_id.clone().into());
+        auction_id_bytes.extend_from_array(&auction_counter.to_be_bytes());
+        let auction_id: BytesN<32> = env.crypto().sha256(&auction_id_bytes).into();
+        
+        let auction = LicenseAuction {
+            auction_id: auction_id.clone(),
+            track_id,
+            seller: artist,
+            licenses,
+            exclusive,
+            start_price,
+            floor_price,
+            decay_per_ledger,
+            start_ledger: env.ledger().sequence(),
+            buyer: None,
+            is_active: true,
+        };
+        
+        env.storage().instance().set(&DataKey::Auctions(auction_id.clone()), &auction);
+        env.storage().instance().set(&DataKey::AuctionIdCounter, &auction_counter);
+        
+        env.events().publish(
+            (Symbol::new(&env, "auction_created"), EVENT_SCHEMA_VERSION, auction_id.clone()),
+            start_price
+        );
+        
+        auction_id
+    }
+
+    fn auction_price(env: &Env, auction: &LicenseAuction) -> i128 {
+        let elapsed = env.ledger().sequence().saturating_sub(auction.start_ledger) as i128;
+        let decayed = auction.start_price - auction.decay_per_ledger * elapsed;
+        
+        if decayed < auction.floor_price {
+            auction.floor_price
+        } else {
+            decayed
+        }
+    }
+
+    pub fn get_auction_price(env: Env, auction_id: BytesN<32>) -> i128 {
+        let auction: LicenseAuction = env.storage().instance()
+            .get(&DataKey::Auctions(auction_id))
+            .unwrap_or_else(|| panic!("Auction not found"));
+        Self::auction_price(&env, &auction)
+    }
+
+    pub fn buy_auction_license(env: Env, buyer: Address, auction_id: BytesN<32>) -> i128 {
+        buyer.require_auth();
+        
+        require_not_paused(&env, PAUSE_REQUESTS);
+        
+        if Self::catalog(&env).get_user(&buyer).is_none() {
+            panic!("User not registered");
+        }
+        
+        let mut auction: LicenseAuction = env.storage().instance()
+            .get(&DataKey::Auctions(auction_id.clone()))
+            .unwrap_or_else(|| panic!("Auction not found"));
+        
+        if !auction.is_active {
+            panic!("Auction closed");
+        }
+        
+        let price = Self::auction_price(&env, &auction);
+        
+        auction.buyer = Some(buyer.clone());
+        auction.is_active = false;
+        env.storage().instance().set(&DataKey::Auctions(auction_id.clone()), &auction);
+        
+        let held: u32 = env.storage().instance()
+            .get(&DataKey::AuctionLicenses(auction.track_id.clone(), buyer.clone()))
+            .unwrap_or(0);
+        env.storage().instance().set(
+            &DataKey::AuctionLicenses(auction.track_id.clone(), buyer.clone()),
+            &(held + auction.licenses)
+        );
+        
+        if auction.exclusive {
+            env.storage().instance().set(&DataKey::ExclusiveHolder(auction.track_id.clone()), &buyer);
+        }
+        
+        let class = if auction.exclusive { LicenseClass::Exclusive } else { LicenseClass::JukeboxPlay };
+        Self::issue_license(&env, &auction.track_id, class, &buyer, auction.licenses, 0);
+        
+        let mut track: Track = Self::catalog(&env).get_track(&auction.track_id).unwrap();
+        track.licenses_sold += auction.licenses;
+        Self::catalog(&env).save_track(&env.current_contract_address(), &track);
+        
+        Self::collect_payment(&env, &buyer, price)
+            .settle_sale(&env.current_contract_address(), &auction.track_id, &price);
+        
+        env.events().publish(
+            (Symbol::new(&env, "auction_settled"), EVENT_SCHEMA_VERSION, auction_id),
+            (buyer, price)
+        );
+        
+        price
+    }
+
+    pub fn cancel_license_auction(env: Env, artist: Address, auction_id: BytesN<32>) {
+        artist.require_auth();
+        
+        let mut auction: LicenseAuction = env.storage().instance()
+            .get(&DataKey::Auctions(auction_id.clone()))
+            .unwrap_or_else(|| panic!("Auction not found"));
+        
+        if auction.seller != artist {
+            panic!("Not auction seller");
+        }
+        
+        if !auction.is_active {
+            panic!("Auction closed");
+        }
+        
+        auction.is_active = false;
+        env.storage().instance().set(&DataKey::Auctions(auction_id.clone()), &auction);
+        
+        let mut track: Track = Self::catalog(&env).get_track(&auction.track_id).unwrap();
+        track.licenses_remaining += auction.licenses;
+        Self::catalog(&env).save_track(&env.current_contract_address(), &track);
+        
+        env.events().publish(
+            (Symbol::new(&env, "auction_cancelled"), EVENT_SCHEMA_VERSION, auction_id),
+            auction.licenses
+        );
+    }
+
+    pub fn get_auction(env: Env, auction_id: BytesN<32>) -> Option<LicenseAuction> {
+        env.storage().instance().get(&DataKey::Auctions(auction_id))
+    }
+
+    pub fn get_auction_licenses(env: Env, holder: Address, track_id: BytesN<32>) -> u32 {
+        env.storage().instance()
+            .get(&DataKey::AuctionLicenses(track_id, holder))
+            .unwrap_or(0)
+    }
+
+    pub fn get_exclusive_holder(env: Env, track_id: BytesN<32>) -> Option<Address> {
+        env.storage().instance().get(&DataKey::ExclusiveHolder(track_id))
+    }
+
+    pub fn set_insurance_terms(env: Env, premium_bps: u32, coverage_bps: u32) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if premium_bps > 1000 {
+            panic!("Premium too high");
+        }
+        
+        if coverage_bps > 10000 {
+            panic!("Coverage too high");
+        }
+        
+        let terms = InsuranceTerms {
+            premium_bps,
+            coverage_bps,
+        };
+        env.storage().instance().set(&DataKey::InsuranceTerms, &terms);
+        
+        env.events().publish(
+            (Symbol::new(&env, "insurance_terms_set"), EVENT_SCHEMA_VERSION),
+            terms
+        );
+    }
+
+    fn insure_request(env: &Env, requester: &Address, request_id: &BytesN<32>, amount_paid: i128) {
+        let terms: InsuranceTerms = env.storage().instance()
+            .get(&DataKey::InsuranceTerms)
+            .unwrap_or_else(|| panic!("Insurance not offered"));
+        
+        if terms.premium_bps == 0 {
+            panic!("Insurance not offered");
+        }
+        
+        let premium = (amount_paid * terms.premium_bps as i128) / 10000;
+        let coverage = (amount_paid * terms.coverage_bps as i128) / 10000;
+        
+        let token_address: Address = Self::default_token(env);
+        let token_client = token::Client::new(env, &token_address);
+        
+        token_client.transfer(requester, &env.current_contract_address(), &premium);
+        
+        let pool: i128 = env.storage().instance()
+            .get(&DataKey::InsurancePool)
+            .unwrap_or(0);
+        env.storage().instance().set(&DataKey::InsurancePool, &(pool + premium));
+        env.storage().instance().set(&DataKey::InsuredRequests(request_id.clone()), &coverage);
+    }
+
+    // Compensates an insured request that can no longer play, capped by the pool balance
+    fn pay_insurance_claim(env: &Env, request: &TrackRequest) {
+        let request_id = &request.request_id;
+        let coverage: i128 = match env.storage().instance()
+            .get(&DataKey::InsuredRequests(request_id.clone()))
+        {
+            Some(coverage) => coverage,
+            None => return,
+        };
+        env.storage().instance().remove(&DataKey::InsuredRequests(request_id.clone()));
+        
+        let pool: i128 = env.storage().instance()
+            .get(&DataKey::InsurancePool)
+            .unwrap_or(0);
+        let payout = coverage.min(pool);
+        if payout <= 0 {
+            return;
+        }
+        
+        env.storage().instance().set(&DataKey::InsurancePool, &(pool - payout));
+        
+        let token_address: Address = Self::default_token(env);
+        let token_client = token::Client::new(env, &token_address);
+        
+        token_client.transfer(&env.current_contract_address(), &request.requester, &payout);
+        
+        env.events().publish(
+            (Symbol::new(env, "insurance_paid"), EVENT_SCHEMA_VERSION, request_id.clone(), request.requester.clone()),
+            (request.requester.clone(), payout)
+        );
+    }
+
+    pub fn get_insurance_terms(env: Env) -> Option<InsuranceTerms> {
+        env.storage().instance().get(&DataKey::InsuranceTerms)
+    }
+
+    pub fn get_insurance_pool(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::InsurancePool).unwrap_or(0)
+    }
+
+    pub fn get_request_coverage(env: Env, request_id: BytesN<32>) -> i128 {
+        env.storage().instance()
+            .get(&DataKey::InsuredRequests(request_id))
+            .unwrap_or(0)
+    }
+
+    // License tokens and resale market
+    fn issue_license(
+        env: &Env,
+        track_id: &BytesN<32>,
+        class: LicenseClass,
+        owner: &Address,
+        units: u32,
+        valid_until: u64,
+    ) -> BytesN<32> {
+        let mut license_counter: u32 = env.storage().instance()
+            .get(&DataKey::LicenseIdCounter)
+            .unwrap_or(0);
+        license_counter += 1;
+        
+        let mut license_id_bytes = Bytes::from_slice(env, b"license_");
+        license_id_bytes.extend_from_array(&license_counter.to_be_bytes());
+        let license_id: BytesN<32> = env.crypto().sha256(&license_id_bytes).into();
+        
+        let license = License {
+            license_id: license_id.clone(),
+            track_id: track_id.clone(),
+            class,
+            owner: owner.clone(),
+            units,
+            issued_at: env.ledger().timestamp(),
+            valid_until,
+        };
+        
+        env.storage().instance().set(&DataKey::Licenses(license_id.clone()), &license);
+        env.storage().instance().set(&DataKey::LicenseIdCounter, &license_counter);
+        
+        env.events().publish(
+            (Symbol::new(env, "license_issued"), EVENT_SCHEMA_VERSION, license_id.clone(), owner.clone()),
+            (track_id.clone(), owner.clone(), units)
+        );
+        
+        license_id
+    }
+
+    pub fn set_resale_royalty(env: Env, royalty_bps: u32) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if royalty_bps > 5000 {
+            panic!("Resale royalty too high");
+        }
+        
+        env.storage().instance().set(&DataKey::ResaleRoyaltyBps, &royalty_bps);
+        
+        env.events().publish(
+            (Symbol::new(&env, "resale_royalty_set"), EVENT_SCHEMA_VERSION),
+            royalty_bps
+        );
+    }
+
+    pub fn transfer_license(env: Env, from: Address, to: Address, license_id: BytesN<32>) {
+        from.require_auth();
+        
+        let mut license: License = env.storage().instance()
+            .get(&DataKey::Licenses(license_id.clone()))
+            .unwrap_or_else(|| panic!("License not found"));
+        
+        if license.owner != from {
+            panic!("Not license owner");
+        }
+        
+        if env.storage().instance().has(&DataKey::LicenseListings(license_id.clone())) {
+            panic!("License is listed for sale");
+        }
+        
+        Self::move_time_license(&env, &license, &to);
+        license.owner = to.clone();
+        env.storage().instance().set(&DataKey::Licenses(license_id.clone()), &license);
+        
+        env.events().publish(
+            (Symbol::new(&env, "license_transferred"), EVENT_SCHEMA_VERSION, license_id),
+            (from, to)
+        );
+    }
+
+    pub fn list_license_for_sale(env: Env, seller: Address, license_id: BytesN<32>, price: i128) {
+        seller.require_auth();
+        
+        let license: License = env.storage().instance()
+            .get(&DataKey::Licenses(license_id.clone()))
+            .unwrap_or_else(|| panic!("License not found"));
+        
+        if license.owner != seller {
+            panic!("Not license owner");
+        }
+        
+        if price <= 0 {
+            panic!("Price must be positive");
+        }
+        
+        let listing = LicenseListing {
+            license_id: license_id.clone(),
+            seller,
+            price,
+        };
+        env.storage().instance().set(&DataKey::LicenseListings(license_id.clone()), &listing);
+        
+        env.events().publish(
+            (Symbol::new(&env, "license_listed"), EVENT_SCHEMA_VERSION, license_id),
+            price
+        );
+    }
+
+    pub fn cancel_license_listing(env: Env, seller: Address, license_id: BytesN<32>) {
+        seller.require_auth();
+        
+        let listing: LicenseListing = env.storage().instance()
+            .get(&DataKey::LicenseListings(license_id.clone()))
+            .unwrap_or_else(|| panic!("License not listed"));
+        
+        if listing.seller != seller {
+            panic!("Not license owner");
+        }
+        
+        env.storage().instance().remove(&DataKey::LicenseListings(license_id));
+    }
+
+    pub fn buy_license(env: Env, buyer: Address, license_id: BytesN<32>) {
+        buyer.require_auth();
+        
+        require_not_paused(&env, PAUSE_REQUESTS);
+        
+        let listing: LicenseListing = env.storage().instance()
+            .get(&DataKey::LicenseListings(license_id.clone()))
+            .unwrap_or_else(|| panic!("License not listed"));
+        
+        let mut license: License = env.storage().instance()
+            .get(&DataKey::Licenses(license_id.clone()))
+            .unwrap();
+        
+        if listing.seller == buyer {
+            panic!("Cannot buy own license");
+        }
+        
+        let track: Track = Self::catalog(&env).get_track(&license.track_id).unwrap();
+        
+        let royalty_bps: u32 = env.storage().instance()
+            .get(&DataKey::ResaleRoyaltyBps)
+            .unwrap_or(0);
+        let royalty = (listing.price * royalty_bps as i128) / 10000;
+        
+        let token_address: Address = Self::default_token(&env);
+        let token_client = token::Client::new(&env, &token_address);
+        
+        token_client.transfer(&buyer, &listing.seller, &(listing.price - royalty));
+        
+        // The resale cut goes to the artist as is: it isn't a play, so no platform
+        // fee, split or play stats apply
+        if royalty > 0 {
+            Self::collect_payment(&env, &buyer, royalty)
+                .accrue_royalty(&env.current_contract_address(), &track.artist_id, &track.track_id, &royalty);
+        }
+        
+        Self::move_time_license(&env, &license, &buyer);
+        license.owner = buyer.clone();
+        env.storage().instance().set(&DataKey::Licenses(license_id.clone()), &license);
+        env.storage().instance().remove(&DataKey::LicenseListings(license_id.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "license_sold"), EVENT_SCHEMA_VERSION, license_id, buyer.clone()),
+            (listing.seller, buyer, listing.price)
+        );
+    }
+
+    pub fn get_license(env: Env, license_id: BytesN<32>) -> Option<License> {
+        env.storage().instance().get(&DataKey::Licenses(license_id))
+    }
+
+    pub fn get_license_listing(env: Env, license_id: BytesN<32>) -> Option<LicenseListing> {
+        env.storage().instance().get(&DataKey::LicenseListings(license_id))
+    }
+
+    pub fn set_accepts_takeovers(env: Env, owner: Address, table_id: BytesN<32>, accepts: bool) {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env).get_table(&table_id).unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        env.storage().instance().set(&DataKey::AcceptsTakeovers(table_id.clone()), &accepts);
+        
+        env.events().publish(
+            (Symbol::new(&env, "takeovers_set"), EVENT_SCHEMA_VERSION, table_id),
+            accepts
+        );
+    }
+
+    pub fn book_tour(
+        env: Env,
+        artist: Address,
+        name: String,
+        promo_price: i128,
+        stops: Vec<TourStop>,
+    ) -> BytesN<32> {
+        artist.require_auth();
+        
+        validation::require_name(&env, &name);
+        require_flag(&env, "tours");
+        
+        let artist_data: Artist = Self::catalog(&env).get_artist(&artist)
+            .unwrap_or_else(|| panic!("Not registered as artist"));
+        
+        if !artist_data.verified {
+            panic!("Only verified artists can book tours");
+        }
+        
+        if stops.is_empty() || stops.len() > 20 {
+            panic!("Tour needs between 1 and 20 stops");
+        }
+        
+        if promo_price < 0 {
+            panic!("Promo price cannot be negative");
+        }
+        
+        let mut tour_counter: u32 = env.storage().instance()
+            .get(&DataKey::TourIdCounter)
+            .unwrap_or(0);
+        tour_counter += 1;
+        
+        let mut tour_id_bytes = Bytes::from_slice(&env, b"tour_");
+        tour_id_bytes.extend_from_array(&tour_counter.to_be_bytes());
+        let tour_id: BytesN<32> = env.crypto().sha256(&tour_id_bytes).into();
+        
+        let now = env.ledger().timestamp();
+        for stop in stops.iter() {
+            if stop.starts_at >= stop.ends_at || stop.ends_at <= now {
+                panic!("Invalid tour stop window");
+            }
+            
+            let table: JukeboxTable = Self::hub(&env).get_table(&stop.table_id)
+                .unwrap_or_else(|| panic!("Table not found"));
+            
+            if !table.is_active {
+                panic!("Table is closed");
+            }
+            
+            let accepts: bool = env.storage().instance()
+                .get(&DataKey::AcceptsTakeovers(stop.table_id.clone()))
+                .unwrap_or(false);
+            if !accepts {
+                panic!("Table does not accept takeovers");
+            }
+            
+            let mut takeovers: Vec<Takeover> = env.storage().instance()
+                .get(&DataKey::TableTakeovers(stop.table_id.clone()))
+                .unwrap_or(Vec::new(&env));
+            
+            // Drop finished takeovers and refuse overlapping bookings
+            let mut upcoming = Vec::new(&env);
+            for takeover in takeovers.iter() {
+                if takeover.ends_at <= now {
+                    continue;
+                }
+                if takeover.starts_at < stop.ends_at && stop.starts_at < takeover.ends_at {
+                    panic!("Takeover slot already booked");
+                }
+                upcoming.push_back(takeover);
+            }
+            takeovers = upcoming;
+            
+            takeovers.push_back(Takeover {
+                tour_id: tour_id.clone(),
+                artist: artist.clone(),
+                starts_at: stop.starts_at,
+                ends_at: stop.ends_at,
+            });
+            env.storage().instance().set(&DataKey::TableTakeovers(stop.table_id.clone()), &takeovers);
+            
+            env.events().publish(
+                (Symbol::new(&env, "tour_stop"), EVENT_SCHEMA_VERSION, tour_id.clone(), stop.table_id.clone()),
+                (artist.clone(), stop.starts_at, stop.ends_at)
+            );
+        }
+        
+        let tour = Tour {
+            tour_id: tour_id.clone(),
+            artist,
+            name,
+            promo_price,
+            stops,
+            total_plays: 0,
+            total_revenue: 0,
+            created_at: now,
+        };
+        
+        env.storage().instance().set(&DataKey::Tours(tour_id.clone()), &tour);
+        env.storage().instance().set(&DataKey::TourIdCounter, &tour_counter);
+        
+        tour_id
+    }
+
+    fn active_takeover(env: &Env, table_id: &BytesN<32>, artist: &Address) -> Option<Takeover> {
+        let takeovers: Vec<Takeover> = env.storage().instance()
+            .get(&DataKey::TableTakeovers(table_id.clone()))?;
+        
+        let now = env.ledger().timestamp();
+        takeovers.iter().find(|takeover| {
+            &takeover.artist == artist && takeover.starts_at <= now && now < takeover.ends_at
+        })
+    }
+
+    fn record_tour_play(env: &Env, table_id: &BytesN<32>, artist: &Address, amount_paid: i128) {
+        if let Some(takeover) = Self::active_takeover(env, table_id, artist) {
+            let mut tour: Tour = env.storage().instance()
+                .get(&DataKey::Tours(takeover.tour_id.clone()))
+                .unwrap();
+            tour.total_plays += 1;
+            tour.total_revenue += amount_paid;
+            env.storage().instance().set(&DataKey::Tours(takeover.tour_id), &tour);
+        }
+    }
+
+    pub fn get_tour(env: Env, tour_id: BytesN<32>) -> Option<Tour> {
+        env.storage().instance().get(&DataKey::Tours(tour_id))
+    }
+
+    pub fn get_table_takeovers(env: Env, table_id: BytesN<32>) -> Vec<Takeover> {
+        env.storage().instance()
+            .get(&DataKey::TableTakeovers(table_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // License classes
+    pub fn set_license_class(
+        env: Env,
+        artist: Address,
+        track_id: BytesN<32>,
+        class: LicenseClass,
+        price: i128,
+        supply: u32,
+    ) {
+        artist.require_auth();
+        
+        let mut track: Track = Self::catalog(&env).get_track(&track_id).unwrap();
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        if price < 0 {
+            panic!("Price cannot be negative");
+        }
+        
+        // Jukebox plays keep living on the track itself so request pricing is unchanged
+        if class == LicenseClass::JukeboxPlay {
+            track.base_price = price;
+            track.licenses_remaining = supply;
+            Self::catalog(&env).save_track(&env.current_contract_address(), &track);
+            return;
+        }
+        
+        let sold = env.storage().instance()
+            .get::<_, LicenseSupply>(&DataKey::LicenseClasses(track_id.clone(), class))
+            .map(|existing| existing.sold)
+            .unwrap_or(0);
+        
+        let license_supply = LicenseSupply {
+            price,
+            remaining: supply,
+            sold,
+        };
+        env.storage().instance().set(&DataKey::LicenseClasses(track_id.clone(), class), &license_supply);
+        
+        env.events().publish(
+            (Symbol::new(&env, "license_class_set"), EVENT_SCHEMA_VERSION, track_id),
+            (class, price, supply)
+        );
+    }
+
+    pub fn get_license_class(env: Env, track_id: BytesN<32>, class: LicenseClass) -> Option<LicenseSupply> {
+        if class == LicenseClass::JukeboxPlay {
+            let track: Track = Self::catalog(&env).get_track(&track_id)?;
+            return Some(LicenseSupply {
+                price: Self::catalog(&env).get_current_price(&track_id),
+                remaining: track.licenses_remaining,
+                sold: track.licenses_sold,
+            });
+        }
+        
+        env.storage().instance().get(&DataKey::LicenseClasses(track_id, class))
+    }
+
+    pub fn purchase_license(env: Env, buyer: Address, track_id: BytesN<32>, class: LicenseClass) -> BytesN<32> {
+        buyer.require_auth();
+        
+        require_not_paused(&env, PAUSE_REQUESTS);
+        
+        if Self::catalog(&env).get_user(&buyer).is_none() {
+            panic!("User not registered");
+        }
+        
+        if class == LicenseClass::JukeboxPlay {
+            panic!("Jukebox plays are licensed through request_track");
+        }
+        
+        Self::catalog(&env).require_track_live(&track_id);
+        
+        let mut license_supply: LicenseSupply = env.storage().instance()
+            .get(&DataKey::LicenseClasses(track_id.clone(), class))
+            .unwrap_or_else(|| panic!("License class not offered"));
+        
+        if license_supply.remaining == 0 {
+            panic!("No licenses remaining for this class");
+        }
+        
+        if class == LicenseClass::Exclusive
+            && env.storage().instance().has(&DataKey::ExclusiveHolder(track_id.clone()))
+        {
+            panic!("Track is under an exclusive license");
+        }
+        
+        license_supply.remaining -= 1;
+        license_supply.sold += 1;
+        env.storage().instance().set(&DataKey::LicenseClasses(track_id.clone(), class), &license_supply);
+        
+        if class == LicenseClass::Exclusive {
+            env.storage().instance().set(&DataKey::ExclusiveHolder(track_id.clone()), &buyer);
+        }
+        
+        Self::collect_payment(&env, &buyer, license_supply.price)
+            .settle_sale(&env.current_contract_address(), &track_id, &license_supply.price);
+        
+        Self::issue_license(&env, &track_id, class, &buyer, 1, 0)
+    }
+
+    // Time-limited licenses
+    pub fn set_time_license_terms(env: Env, artist: Address, track_id: BytesN<32>, price: i128, period_secs: u64) {
+        artist.require_auth();
+        
+        let track: Track = Self::catalog(&env).get_track(&track_id).unwrap();
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        if price < 0 || period_secs == 0 {
+            panic!("Invalid time license terms");
+        }
+        
+        let terms = TimeLicenseTerms {
+            price,
+            period_secs,
+        };
+        env.storage().instance().set(&LicenseKey::TimeLicenseTerms(track_id.clone()), &terms);
+        
+        env.events().publish(
+            (Symbol::new(&env, "time_license_set"), EVENT_SCHEMA_VERSION, track_id),
+            terms
+        );
+    }
+
+    pub fn get_time_license_terms(env: Env, track_id: BytesN<32>) -> Option<TimeLicenseTerms> {
+        env.storage().instance().get(&LicenseKey::TimeLicenseTerms(track_id))
+    }
+
+    // Buys a first period or extends an existing one from whichever is later, now or its expiry
+    pub fn renew_license(env: Env, holder: Address, track_id: BytesN<32>) -> u64 {
+        holder.require_auth();
+        
+        require_not_paused(&env, PAUSE_REQUESTS);
+        
+        if Self::catalog(&env).get_user(&holder).is_none() {
+            panic!("User not registered");
+        }
+        
+        let terms: TimeLicenseTerms = env.storage().instance()
+            .get(&LicenseKey::TimeLicenseTerms(track_id.clone()))
+            .unwrap_or_else(|| panic!("Time licenses not offered"));
+        
+        Self::catalog(&env).require_track_live(&track_id);
+        
+        Self::collect_payment(&env, &holder, terms.price)
+            .settle_sale(&env.current_contract_address(), &track_id, &terms.price);
+        
+        let now = env.ledger().timestamp();
+        let existing: Option<BytesN<32>> = env.storage().instance()
+            .get(&LicenseKey::TimeLicenses(track_id.clone(), holder.clone()));
+        
+        let valid_until = match existing {
+            Some(license_id) => {
+                let mut license: License = env.storage().instance()
+                    .get(&DataKey::Licenses(license_id.clone()))
+                    .unwrap();
+                license.valid_until = license.valid_until.max(now) + terms.period_secs;
+                env.storage().instance().set(&DataKey::Licenses(license_id), &license);
+                license.valid_until
+            }
+            None => {
+                let valid_until = now + terms.period_secs;
+                let license_id = Self::issue_license(
+                    &env,
+                    &track_id,
+                    LicenseClass::JukeboxPlay,
+                    &holder,
+                    1,
+                    valid_until,
+                );
+                env.storage().instance().set(&LicenseKey::TimeLicenses(track_id.clone(), holder.clone()), &license_id);
+                valid_until
+            }
+        };
+        
+        env.events().publish(
+            (Symbol::new(&env, "license_renewed"), EVENT_SCHEMA_VERSION, track_id),
+            (holder, valid_until)
+        );
+        
+        valid_until
+    }
+
+    pub fn is_license_valid(env: Env, holder: Address, track_id: BytesN<32>) -> bool {
+        let license_id: BytesN<32> = match env.storage().instance()
+            .get(&LicenseKey::TimeLicenses(track_id, holder))
+        {
+            Some(license_id) => license_id,
+            None => return false,
+        };
+        
+        let license: License = env.storage().instance()
+            .get(&DataKey::Licenses(license_id))
+            .unwrap();
+        env.ledger().timestamp() < license.valid_until
+    }
+
+    fn move_time_license(env: &Env, license: &License, to: &Address) {
+        if license.valid_until == 0 {
+            return;
+        }
+        
+        if env.storage().instance().has(&LicenseKey::TimeLicenses(license.track_id.clone(), to.clone())) {
+            panic!("Recipient already holds a time license for this track");
+        }
+        
+        env.storage().instance().remove(&LicenseKey::TimeLicenses(license.track_id.clone(), license.owner.clone()));
+        env.storage().instance().set(&LicenseKey::TimeLicenses(license.track_id.clone(), to.clone()), &license.license_id);
+    }
+
+    fn default_token(env: &Env) -> Address {
+        env.storage().instance().get(&DataKey::TokenStellar).unwrap()
+    }
+
+    fn catalog(env: &Env) -> CatalogClient<'_> {
+        CatalogClient::new(env, &links(env).catalog)
+    }
+
+    // Payments are held by payouts, which is then told what they are for
+    fn collect_payment<'a>(env: &'a Env, from: &Address, amount: i128) -> PayoutsClient<'a> {
+        let payouts = links(env).payouts;
+        token::Client::new(env, &Self::default_token(env)).transfer(from, &payouts, &amount);
+        PayoutsClient::new(env, &payouts)
+    }
+
+    fn hub(env: &Env) -> HubClient<'_> {
+        HubClient::new(env, &links(env).hub)
+    }
+}
@@ -0,0 +1,1932 @@
+#![no_std]
+#![allow(clippy::too_many_arguments)]
+use juke_common::{
+    bump_instance, links, record_audit, require_not_paused, require_sibling, AuditEntry, AuditTarget, CatalogClient,
+    CommunityClient, FeeClass, FeeTier, HubClient, JukeboxTable, Links, ParamChange, Settlement, SplitChange, SwapRouterClient, Track,
+    TrackRequest, TreasuryTarget, CONTRACT_VERSION, EVENT_SCHEMA_VERSION, PAUSE_WITHDRAWALS,
+};
+use soroban_sdk::{contract, contractclient, contractimpl, contractmeta, contracttype, token, Address, BytesN, Env, Symbol, Vec};
+
+contractmeta!(key = "binver", val = "0.1.0");
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SplitProposal {
+    new_split: Vec<(Address, u32)>,
+    required: Vec<Address>,
+    approvals: Vec<Address>,
+    proposed_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TrackEarnings {
+    pub gross_revenue: i128,
+    pub platform_fees_paid: i128,
+    pub plays_paid: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct VestingSchedule {
+    vested_bps: u32,
+    start: u64,
+    cliff_secs: u64,
+    duration_secs: u64,
+    total: i128,
+    claimed: i128,
+}
+
+// How a table splits each paid request, in basis points of the payment
+#[contracttype]
+#[derive(Clone)]
+pub struct FeeRouting {
+    pub platform_bps: u32,
+    pub owner_bps: u32,
+    pub table_treasury_bps: u32,
+    pub artist_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct VolumeWindow {
+    window_start: u64,
+    current: u32,
+    previous: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct ReferralConfig {
+    pub reward_bps: u32,
+    pub rewarded_requests: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct ArtistAwardConfig {
+    pub share_bps: u32,
+    pub period_secs: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct ArtistEarnings {
+    pub gross_revenue: i128,
+    pub platform_fees_paid: i128,
+    pub plays_paid: u32,
+    pub top_tracks: Vec<(BytesN<32>, i128)>,
+    pub claimable: i128,
+}
+
+// ----- Storage Keys -----
+// Keys encode as the variant name plus its fields, without the enum name, so a
+// variant must not reuse a name and field types already taken by another key enum
+// here or in juke_common.
+#[contracttype]
+enum DataKey {
+    Admin,
+    TokenStellar,
+    PlatformFee,
+}
+
+#[contracttype]
+enum FeeKey {
+    Tiers,
+    ArtistVolume(Address),
+    Overrides(FeeClass),
+    Routing(BytesN<32>),
+    TableTreasury(BytesN<32>),
+}
+
+#[contracttype]
+enum TreasuryKey {
+    Balance,
+    Targets,
+    GrantPool,
+}
+
+#[contracttype]
+enum TokenKey {
+    Allowed,
+    TableTokens(BytesN<32>),
+    Accruals(Address, Address),
+    Treasury(Address),
+    TableTreasuries(BytesN<32>, Address),
+}
+
+#[contracttype]
+enum ReferralKey {
+    ReferralConfig,
+    Referrer(Address),
+    Count(Address),
+    Remaining(Address),
+    Rewards(Address),
+}
+
+#[contracttype]
+enum AwardKey {
+    AwardConfig,
+    PeriodFees(u64),
+    PeriodPlays(u64, Address),
+    PeriodLeader(u64),
+    PeriodClosed(u64),
+}
+
+#[contracttype]
+enum PayoutKey {
+    SwapRouter,
+    PayoutToken(Address),
+    Accruals(Address, BytesN<32>),
+    AccrualIndex(Address),
+    Revenue(Address),
+}
+
+// Running per-artist totals, kept alongside the per-track TrackEarnings
+#[contracttype]
+enum EarningsKey {
+    ArtistTotals(Address),
+    TopEarners(Address),
+}
+
+#[contracttype]
+enum TrackKey {
+    SplitProposals(BytesN<32>),
+    Earnings(BytesN<32>),
+    Vesting(BytesN<32>, Address),
+    Splitter(BytesN<32>),
+    Charities,
+}
+
+#[contracttype]
+enum EscrowKey {
+    Settlements(BytesN<32>),
+}
+
+const VOLUME_WINDOW: u64 = 30 * 86400;
+const MAX_TOP_EARNING_TRACKS: u32 = 5;
+
+// External royalty splitter a track can pay instead of its inline split. It is
+// sent `amount` of the default token and then asked to pass it on.
+#[contractclient(name = "PayoutSplitterClient")]
+pub trait PayoutSplitter {
+    fn distribute(env: Env, amount: i128);
+}
+
+// Fees, royalties and the treasury; every payment in the deployment ends up here
+#[contract]
+pub struct JukePayouts;
+
+#[contractimpl]
+impl JukePayouts {
+    pub fn initialize(env: Env, admin: Address, token_stellar: Address, platform_fee: u32, links: Links) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        
+        admin.require_auth();
+        
+        if platform_fee > 2000 {
+            panic!("Fee too high");
+        }
+        
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::TokenStellar, &token_stellar);
+        env.storage().instance().set(&DataKey::PlatformFee, &platform_fee);
+        juke_common::set_links(&env, &links);
+        
+        env.events().publish(
+            (Symbol::new(&env, "initialized"), EVENT_SCHEMA_VERSION),
+            (admin, token_stellar, platform_fee)
+        );
+    }
+
+    pub fn get_version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
+
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    pub fn get_audit_count(env: Env) -> u32 {
+        juke_common::audit_count(&env)
+    }
+
+    pub fn get_audit_log(env: Env, start: u32, limit: u32) -> Vec<AuditEntry> {
+        juke_common::audit_log(&env, start, limit)
+    }
+
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        links(&env).governance.require_auth();
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    pub fn get_platform_fee(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::PlatformFee).unwrap()
+    }
+
+    // Fee parameters are queued and executed by governance
+    pub fn apply_param_change(env: Env, caller: Address, change: ParamChange) {
+        require_sibling(&env, &caller);
+        
+        match change {
+            ParamChange::PlatformFee(fee) => env.storage().instance().set(&DataKey::PlatformFee, &fee),
+            ParamChange::FeeTiers(tiers) => env.storage().instance().set(&FeeKey::Tiers, &tiers),
+            ParamChange::FeeOverride(class, Some(fee)) => env.storage().instance().set(&FeeKey::Overrides(class), &fee),
+            ParamChange::FeeOverride(class, None) => env.storage().instance().remove(&FeeKey::Overrides(class)),
+            // Governance keeps its own delay
+            ParamChange::TimelockDelay(_) => {}
+        }
+    }
+
+    pub fn apply_treasury_targets(env: Env, caller: Address, targets: Vec<(TreasuryTarget, u32)>) {
+        require_sibling(&env, &caller);
+        env.storage().instance().set(&TreasuryKey::Targets, &targets);
+        
+        env.events().publish(
+            (Symbol::new(&env, "treasury_targets_set"), EVENT_SCHEMA_VERSION),
+            targets
+        );
+    }
+
+    // Linked contracts
+    // Every payment ends up here. Callers move the funds to this contract first,
+    // then call in to have them credited.
+    pub fn settle_request(env: Env, caller: Address, request: TrackRequest, table: JukeboxTable) -> Option<Settlement> {
+        require_sibling(&env, &caller);
+        
+        let track = Self::catalog(&env).get_track(&request.track_id).unwrap();
+        
+        if request.payment_token != Self::default_token(&env) {
+            Self::distribute_token_royalties(&env, &track, &table, &request.payment_token, request.amount_paid);
+            return None;
+        }
+        
+        let settlement = Self::distribute_royalties(&env, &track, Some(&table), &request.amount_paid);
+        Self::pay_referral(&env, &request.requester, settlement.fee);
+        env.storage().instance().set(&EscrowKey::Settlements(request.request_id), &settlement);
+        bump_instance(&env);
+        Some(settlement)
+    }
+
+    pub fn get_settlement(env: Env, request_id: BytesN<32>) -> Option<Settlement> {
+        env.storage().instance().get(&EscrowKey::Settlements(request_id))
+    }
+
+    // Pulls what a disputed settlement credited back out of unclaimed accruals,
+    // vesting and the treasury, and returns what it could hold. The table treasury
+    // cut stays with the table.
+    pub fn hold_settlement(env: Env, caller: Address, request_id: BytesN<32>, track_id: BytesN<32>) -> Settlement {
+        require_sibling(&env, &caller);
+        
+        let settlement: Settlement = env.storage().instance()
+            .get(&EscrowKey::Settlements(request_id))
+            .unwrap_or_else(|| panic!("Request not settled"));
+        
+        let mut held = Vec::new(&env);
+        for (recipient, share) in settlement.shares.iter() {
+            let key = PayoutKey::Accruals(recipient.clone(), track_id.clone());
+            let accrued: i128 = env.storage().instance().get(&key).unwrap_or(0);
+            let taken = share.min(accrued);
+            if taken > 0 {
+                env.storage().instance().set(&key, &(accrued - taken));
+                held.push_back((recipient, taken));
+            }
+        }
+        
+        let mut held_vested = Vec::new(&env);
+        for (recipient, locked) in settlement.vested.iter() {
+            let key = TrackKey::Vesting(track_id.clone(), recipient.clone());
+            let mut schedule: VestingSchedule = env.storage().instance().get(&key).unwrap();
+            let taken = locked.min(schedule.total - schedule.claimed);
+            if taken > 0 {
+                schedule.total -= taken;
+                env.storage().instance().set(&key, &schedule);
+                held_vested.push_back((recipient, taken));
+            }
+        }
+        
+        let treasury = Self::get_treasury_balance(env.clone());
+        let held_fee = settlement.fee.min(treasury);
+        env.storage().instance().set(&TreasuryKey::Balance, &(treasury - held_fee));
+        
+        Settlement {
+            shares: held,
+            vested: held_vested,
+            fee: held_fee,
+            settled_at: settlement.settled_at,
+        }
+    }
+
+    // Refunds a held settlement in full, or credits it back where it came from
+    pub fn release_settlement(env: Env, caller: Address, track_id: BytesN<32>, held: Settlement, refund_to: Option<Address>) {
+        require_sibling(&env, &caller);
+        
+        if let Some(requester) = refund_to {
+            let mut total = held.fee;
+            for (_, amount) in held.shares.iter().chain(held.vested.iter()) {
+                total += amount;
+            }
+            
+            token::Client::new(&env, &Self::default_token(&env)).transfer(
+                &env.current_contract_address(),
+                &requester,
+                &total,
+            );
+            return;
+        }
+        
+        for (recipient, amount) in held.shares.iter() {
+            Self::accrue_revenue(&env, &recipient, &track_id, amount);
+        }
+        for (recipient, amount) in held.vested.iter() {
+            let key = TrackKey::Vesting(track_id.clone(), recipient);
+            let mut schedule: VestingSchedule = env.storage().instance().get(&key).unwrap();
+            schedule.total += amount;
+            env.storage().instance().set(&key, &schedule);
+        }
+        let treasury = Self::get_treasury_balance(env.clone());
+        env.storage().instance().set(&TreasuryKey::Balance, &(treasury + held.fee));
+    }
+
+    // License sales pay out like a play with no table
+    pub fn settle_sale(env: Env, caller: Address, track_id: BytesN<32>, amount: i128) {
+        require_sibling(&env, &caller);
+        
+        let track = Self::catalog(&env).get_track(&track_id).unwrap();
+        Self::distribute_royalties(&env, &track, None, &amount);
+    }
+
+    // Credited as is: no platform fee, split or play stats apply
+    pub fn accrue_royalty(env: Env, caller: Address, recipient: Address, track_id: BytesN<32>, amount: i128) {
+        require_sibling(&env, &caller);
+        Self::accrue_revenue(&env, &recipient, &track_id, amount);
+    }
+
+    // Subscriptions and campaign funds: the artist's tiered fee goes to the
+    // treasury and the rest to their revenue balance
+    pub fn collect_artist_payment(env: Env, caller: Address, artist: Address, amount: i128) {
+        require_sibling(&env, &caller);
+        
+        let fee_amount = (amount * Self::platform_fee_for(&env, &artist) as i128) / 10000;
+        let treasury = Self::get_treasury_balance(env.clone());
+        env.storage().instance().set(&TreasuryKey::Balance, &(treasury + fee_amount));
+        Self::credit_revenue(&env, &artist, amount - fee_amount);
+    }
+
+    pub fn credit_treasury(env: Env, caller: Address, amount: i128) {
+        require_sibling(&env, &caller);
+        
+        let treasury = Self::get_treasury_balance(env.clone());
+        env.storage().instance().set(&TreasuryKey::Balance, &(treasury + amount));
+    }
+
+    // Pays up to `amount` out of the treasury; returns what was paid
+    pub fn pay_from_treasury(env: Env, caller: Address, recipient: Address, amount: i128) -> i128 {
+        require_sibling(&env, &caller);
+        
+        let treasury = Self::get_treasury_balance(env.clone());
+        let paid = amount.min(treasury);
+        if paid <= 0 {
+            return 0;
+        }
+        
+        env.storage().instance().set(&TreasuryKey::Balance, &(treasury - paid));
+        token::Client::new(&env, &Self::default_token(&env)).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &paid,
+        );
+        paid
+    }
+
+    pub fn credit_table_treasury(env: Env, caller: Address, table_id: BytesN<32>, amount: i128) {
+        require_sibling(&env, &caller);
+        
+        let balance = Self::get_table_treasury(env.clone(), table_id.clone());
+        env.storage().instance().set(&FeeKey::TableTreasury(table_id), &(balance + amount));
+    }
+
+    pub fn get_fee_tiers(env: Env) -> Vec<FeeTier> {
+        env.storage().instance()
+            .get(&FeeKey::Tiers)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn get_fee_override(env: Env, class: FeeClass) -> Option<u32> {
+        env.storage().instance().get(&FeeKey::Overrides(class))
+    }
+
+    // Paid plays over the current and previous volume windows
+    pub fn get_artist_volume(env: Env, artist: Address) -> u32 {
+        let window = Self::volume_window(&env, &artist);
+        window.previous + window.current
+    }
+
+    fn volume_window(env: &Env, artist: &Address) -> VolumeWindow {
+        let now = env.ledger().timestamp();
+        let window: VolumeWindow = env.storage().instance()
+            .get(&FeeKey::ArtistVolume(artist.clone()))
+            .unwrap_or(VolumeWindow {
+                window_start: now,
+                current: 0,
+                previous: 0,
+            });
+        
+        let elapsed = now.saturating_sub(window.window_start);
+        if elapsed < VOLUME_WINDOW {
+            window
+        } else if elapsed < 2 * VOLUME_WINDOW {
+            VolumeWindow {
+                window_start: window.window_start + VOLUME_WINDOW,
+                current: 0,
+                previous: window.current,
+            }
+        } else {
+            VolumeWindow {
+                window_start: now,
+                current: 0,
+                previous: 0,
+            }
+        }
+    }
+
+    fn record_artist_volume(env: &Env, artist: &Address) {
+        let mut window = Self::volume_window(env, artist);
+        window.current += 1;
+        env.storage().instance().set(&FeeKey::ArtistVolume(artist.clone()), &window);
+    }
+
+    fn platform_fee_for(env: &Env, artist: &Address) -> u32 {
+        let platform_fee: u32 = env.storage().instance()
+            .get(&DataKey::PlatformFee)
+            .unwrap();
+        
+        let volume = Self::get_artist_volume(env.clone(), artist.clone());
+        let mut fee = platform_fee;
+        for tier in Self::get_fee_tiers(env.clone()).iter() {
+            if volume < tier.min_volume {
+                break;
+            }
+            fee = tier.fee_bps;
+        }
+        fee
+    }
+
+    // A verified artist's play on their own table takes that override first, then
+    // the table's category override; otherwise the artist's tiered fee applies
+    fn resolve_platform_fee(env: &Env, artist: &Address, table: Option<&JukeboxTable>) -> u32 {
+        if let Some(table) = table {
+            let verified = Self::catalog(env)
+                .get_artist(artist)
+                .is_some_and(|artist| artist.verified);
+            if verified && table.owner == *artist {
+                if let Some(fee) = Self::get_fee_override(env.clone(), FeeClass::VerifiedOwnTable) {
+                    return fee;
+                }
+            }
+            if let Some(fee) = Self::get_fee_override(env.clone(), FeeClass::Category(table.category.clone())) {
+                return fee;
+            }
+        }
+        
+        Self::platform_fee_for(env, artist)
+    }
+
+    // Replaces the fee-to-treasury, rest-to-artists split for the table's paid
+    // requests; None restores it
+    pub fn set_fee_routing(env: Env, owner: Address, table_id: BytesN<32>, routing: Option<FeeRouting>) {
+        owner.require_auth();
+        
+        let table = HubClient::new(&env, &links(&env).hub)
+            .get_table(&table_id)
+            .unwrap_or_else(|| panic!("Table not found"));
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        match &routing {
+            Some(routing) => {
+                let total = routing.platform_bps + routing.owner_bps + routing.table_treasury_bps + routing.artist_bps;
+                if total != 10000 {
+                    panic!("Routing must total 10000 bps");
+                }
+                // The platform can always take up to the fee cap, out of the artists' share
+                if routing.platform_bps + routing.artist_bps < 2000 {
+                    panic!("Routing leaves too little for the platform fee");
+                }
+                env.storage().instance().set(&FeeKey::Routing(table_id.clone()), routing);
+            }
+            None => env.storage().instance().remove(&FeeKey::Routing(table_id.clone())),
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "fee_routing_set"), EVENT_SCHEMA_VERSION, table_id),
+            routing
+        );
+    }
+
+    pub fn get_fee_routing(env: Env, table_id: BytesN<32>) -> Option<FeeRouting> {
+        env.storage().instance().get(&FeeKey::Routing(table_id))
+    }
+
+    pub fn get_table_treasury(env: Env, table_id: BytesN<32>) -> i128 {
+        env.storage().instance()
+            .get(&FeeKey::TableTreasury(table_id))
+            .unwrap_or(0)
+    }
+
+    pub fn withdraw_table_treasury(env: Env, owner: Address, table_id: BytesN<32>, amount: i128, destination: Address) {
+        owner.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        let table = HubClient::new(&env, &links(&env).hub)
+            .get_table(&table_id)
+            .unwrap_or_else(|| panic!("Table not found"));
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        let balance = Self::get_table_treasury(env.clone(), table_id.clone());
+        if amount <= 0 || amount > balance {
+            panic!("Invalid amount");
+        }
+        
+        env.storage().instance().set(&FeeKey::TableTreasury(table_id.clone()), &(balance - amount));
+        token::Client::new(&env, &Self::default_token(&env)).transfer(
+            &env.current_contract_address(),
+            &destination,
+            &amount,
+        );
+        
+        env.events().publish(
+            (Symbol::new(&env, "table_treasury_withdrawn"), EVENT_SCHEMA_VERSION, table_id),
+            (amount, destination)
+        );
+    }
+
+    // Splits a paid request into (platform fee, owner cut, table treasury cut, royalties).
+    // A routed table's platform share never drops below the resolved platform fee;
+    // any difference comes out of the royalties.
+    fn route_payment(env: &Env, artist: &Address, table: Option<&JukeboxTable>, amount: i128) -> (i128, i128, i128, i128) {
+        let platform_fee = Self::resolve_platform_fee(env, artist, table);
+        let routing: Option<FeeRouting> = table
+            .and_then(|table| env.storage().instance().get(&FeeKey::Routing(table.table_id.clone())));
+        
+        let routing = match routing {
+            Some(routing) => routing,
+            None => {
+                let fee_amount = (amount * platform_fee as i128) / 10000;
+                return (fee_amount, 0, 0, amount - fee_amount);
+            }
+        };
+        
+        let fee_amount = (amount * routing.platform_bps.max(platform_fee) as i128) / 10000;
+        let owner_cut = (amount * routing.owner_bps as i128) / 10000;
+        let table_cut = (amount * routing.table_treasury_bps as i128) / 10000;
+        (fee_amount, owner_cut, table_cut, amount - fee_amount - owner_cut - table_cut)
+    }
+
+    pub fn get_treasury_targets(env: Env) -> Vec<(TreasuryTarget, u32)> {
+        env.storage().instance()
+            .get(&TreasuryKey::Targets)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn get_treasury_balance(env: Env) -> i128 {
+        env.storage().instance()
+            .get(&TreasuryKey::Balance)
+            .unwrap_or(0)
+    }
+
+    pub fn get_grant_pool(env: Env) -> i128 {
+        env.storage().instance()
+            .get(&TreasuryKey::GrantPool)
+            .unwrap_or(0)
+    }
+
+    // Anyone can trigger a distribution; funds only ever go to the configured targets
+    pub fn distribute_treasury(env: Env) -> i128 {
+        let targets: Vec<(TreasuryTarget, u32)> = env.storage().instance()
+            .get(&TreasuryKey::Targets)
+            .unwrap_or_else(|| panic!("No treasury targets"));
+        
+        let balance = Self::get_treasury_balance(env.clone());
+        if balance <= 0 {
+            panic!("Treasury empty");
+        }
+        
+        let token_address: Address = env.storage().instance()
+            .get(&DataKey::TokenStellar)
+            .unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        
+        let mut distributed = 0;
+        for (target, bps) in targets.iter() {
+            let amount = balance * bps as i128 / 10000;
+            if amount == 0 {
+                continue;
+            }
+            
+            match target.clone() {
+                TreasuryTarget::Wallet(wallet) => {
+                    token_client.transfer(&env.current_contract_address(), &wallet, &amount);
+                }
+                TreasuryTarget::GrantPool => {
+                    let pool = Self::get_grant_pool(env.clone());
+                    env.storage().instance().set(&TreasuryKey::GrantPool, &(pool + amount));
+                }
+                TreasuryTarget::Burn => {
+                    token_client.burn(&env.current_contract_address(), &amount);
+                }
+            }
+            distributed += amount;
+            
+            env.events().publish(
+                (Symbol::new(&env, "treasury_payout"), EVENT_SCHEMA_VERSION),
+                (target, amount)
+            );
+        }
+        
+        // Rounding dust stays behind for the next distribution
+        env.storage().instance().set(&TreasuryKey::Balance, &(balance - distributed));
+        
+        env.events().publish(
+            (Symbol::new(&env, "treasury_distributed"), EVENT_SCHEMA_VERSION),
+            (balance, distributed)
+        );
+        
+        distributed
+    }
+
+    // Same targets as distribute_treasury; the grant pool only holds the default
+    // token, so its share stays behind in this token's treasury
+    pub fn distribute_token_treasury(env: Env, token: Address) -> i128 {
+        let targets: Vec<(TreasuryTarget, u32)> = env.storage().instance()
+            .get(&TreasuryKey::Targets)
+            .unwrap_or_else(|| panic!("No treasury targets"));
+        
+        let balance = Self::get_token_treasury_balance(env.clone(), token.clone());
+        if balance <= 0 {
+            panic!("Treasury empty");
+        }
+        
+        let token_client = token::Client::new(&env, &token);
+        
+        let mut distributed = 0;
+        for (target, bps) in targets.iter() {
+            let amount = balance * bps as i128 / 10000;
+            if amount == 0 {
+                continue;
+            }
+            
+            match target {
+                TreasuryTarget::Wallet(wallet) => {
+                    token_client.transfer(&env.current_contract_address(), &wallet, &amount);
+                }
+                TreasuryTarget::GrantPool => continue,
+                TreasuryTarget::Burn => {
+                    token_client.burn(&env.current_contract_address(), &amount);
+                }
+            }
+            distributed += amount;
+        }
+        
+        env.storage().instance().set(&TokenKey::Treasury(token.clone()), &(balance - distributed));
+        
+        env.events().publish(
+            (Symbol::new(&env, "treasury_distributed"), EVENT_SCHEMA_VERSION, token),
+            (balance, distributed)
+        );
+        
+        distributed
+    }
+
+    pub fn award_grant(env: Env, artist: Address, amount: i128) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        record_audit(&env, &admin, "grant", AuditTarget::Account(artist.clone()));
+        
+        Self::require_artist(&env, &artist);
+        
+        let pool = Self::get_grant_pool(env.clone());
+        if amount <= 0 || amount > pool {
+            panic!("Invalid grant amount");
+        }
+        
+        env.storage().instance().set(&TreasuryKey::GrantPool, &(pool - amount));
+        Self::credit_revenue(&env, &artist, amount);
+        
+        env.events().publish(
+            (Symbol::new(&env, "grant_awarded"), EVENT_SCHEMA_VERSION, artist),
+            amount
+        );
+    }
+
+    // Payment tokens
+    // The token set at initialize stays the default: it is always accepted and is
+    // the unit for credits, fee tiers and every spend-based aggregate
+    fn default_token(env: &Env) -> Address {
+        env.storage().instance().get(&DataKey::TokenStellar).unwrap()
+    }
+
+    pub fn get_token_revenue(env: Env, recipient: Address, token: Address) -> i128 {
+        env.storage().instance().get(&TokenKey::Accruals(recipient, token)).unwrap_or(0)
+    }
+
+    pub fn withdraw_token_revenue(env: Env, recipient: Address, token: Address) -> i128 {
+        recipient.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        let amount = Self::get_token_revenue(env.clone(), recipient.clone(), token.clone());
+        if amount == 0 {
+            panic!("Nothing to withdraw");
+        }
+        env.storage().instance().remove(&TokenKey::Accruals(recipient.clone(), token.clone()));
+        
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+        
+        amount
+    }
+
+    pub fn get_token_treasury_balance(env: Env, token: Address) -> i128 {
+        env.storage().instance().get(&TokenKey::Treasury(token)).unwrap_or(0)
+    }
+
+    pub fn get_table_token_treasury(env: Env, table_id: BytesN<32>, token: Address) -> i128 {
+        env.storage().instance().get(&TokenKey::TableTreasuries(table_id, token)).unwrap_or(0)
+    }
+
+    pub fn withdraw_table_token_treasury(
+        env: Env,
+        owner: Address,
+        table_id: BytesN<32>,
+        token: Address,
+        amount: i128,
+        destination: Address,
+    ) {
+        owner.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        let table = HubClient::new(&env, &links(&env).hub)
+            .get_table(&table_id)
+            .unwrap_or_else(|| panic!("Table not found"));
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        let balance = Self::get_table_token_treasury(env.clone(), table_id.clone(), token.clone());
+        if amount <= 0 || amount > balance {
+            panic!("Invalid amount");
+        }
+        
+        env.storage().instance().set(&TokenKey::TableTreasuries(table_id.clone(), token.clone()), &(balance - amount));
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &destination,
+            &amount,
+        );
+        
+        env.events().publish(
+            (Symbol::new(&env, "table_treasury_withdrawn"), EVENT_SCHEMA_VERSION, table_id),
+            (amount, destination)
+        );
+    }
+
+    // Mirrors distribute_royalties for payments in a non-default token, including
+    // the table's fee routing. Earnings and volume tracking are default-token only
+    // and are skipped here; place_request keeps splitter and vesting tracks to the
+    // default token.
+    fn distribute_token_royalties(env: &Env, track: &Track, table: &JukeboxTable, token: &Address, payment_amount: i128) {
+        let treasury = Self::get_token_treasury_balance(env.clone(), token.clone());
+        
+        if Self::catalog(env).is_track_taken_down(&track.track_id) {
+            env.storage().instance().set(&TokenKey::Treasury(token.clone()), &(treasury + payment_amount));
+            return;
+        }
+        
+        let (fee_amount, owner_cut, table_cut, royalty_amount) =
+            Self::route_payment(env, &track.artist_id, Some(table), payment_amount);
+        env.storage().instance().set(&TokenKey::Treasury(token.clone()), &(treasury + fee_amount));
+        
+        if owner_cut > 0 {
+            let key = TokenKey::Accruals(table.owner.clone(), token.clone());
+            let accrued: i128 = env.storage().instance().get(&key).unwrap_or(0);
+            env.storage().instance().set(&key, &(accrued + owner_cut));
+        }
+        if table_cut > 0 {
+            let table_id = table.table_id.clone();
+            let table_treasury = Self::get_table_token_treasury(env.clone(), table_id.clone(), token.clone());
+            env.storage().instance().set(&TokenKey::TableTreasuries(table_id, token.clone()), &(table_treasury + table_cut));
+        }
+        
+        let mut royalty_amount = royalty_amount;
+        if let Some((charity, share)) = Self::charity_share(env, track, royalty_amount) {
+            let key = TokenKey::Accruals(charity, token.clone());
+            let accrued: i128 = env.storage().instance().get(&key).unwrap_or(0);
+            env.storage().instance().set(&key, &(accrued + share));
+            royalty_amount -= share;
+        }
+        
+        for (recipient, percentage) in track.royalty_split.iter() {
+            let share = (royalty_amount * (percentage as i128)) / 100;
+            let key = TokenKey::Accruals(recipient.clone(), token.clone());
+            let accrued: i128 = env.storage().instance().get(&key).unwrap_or(0);
+            env.storage().instance().set(&key, &(accrued + share));
+        }
+    }
+
+    // Referrals
+    pub fn set_referral_config(env: Env, config: ReferralConfig) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if config.reward_bps > 10000 {
+            panic!("Referral reward cannot exceed the platform fee");
+        }
+        
+        env.storage().instance().set(&ReferralKey::ReferralConfig, &config);
+        
+        env.events().publish(
+            (Symbol::new(&env, "referral_config_set"), EVENT_SCHEMA_VERSION),
+            config
+        );
+    }
+
+    pub fn get_referral_config(env: Env) -> ReferralConfig {
+        env.storage().instance()
+            .get(&ReferralKey::ReferralConfig)
+            .unwrap_or(ReferralConfig {
+                reward_bps: 0,
+                rewarded_requests: 0,
+            })
+    }
+
+    pub fn get_referrer(env: Env, user: Address) -> Option<Address> {
+        env.storage().instance().get(&ReferralKey::Referrer(user))
+    }
+
+    pub fn get_referral_count(env: Env, referrer: Address) -> u32 {
+        env.storage().instance().get(&ReferralKey::Count(referrer)).unwrap_or(0)
+    }
+
+    pub fn get_referral_rewards(env: Env, referrer: Address) -> i128 {
+        env.storage().instance().get(&ReferralKey::Rewards(referrer)).unwrap_or(0)
+    }
+
+    pub fn claim_referral_rewards(env: Env, referrer: Address) -> i128 {
+        referrer.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        let amount = Self::get_referral_rewards(env.clone(), referrer.clone());
+        if amount == 0 {
+            panic!("Nothing to claim");
+        }
+        env.storage().instance().remove(&ReferralKey::Rewards(referrer.clone()));
+        
+        let token_address: Address = env.storage().instance()
+            .get(&DataKey::TokenStellar)
+            .unwrap();
+        token::Client::new(&env, &token_address).transfer(
+            &env.current_contract_address(),
+            &referrer,
+            &amount,
+        );
+        
+        amount
+    }
+
+    // Called by the catalog as `user` registers, once it has checked the referrer
+    pub fn record_referral(env: Env, caller: Address, user: Address, referrer: Address) {
+        require_sibling(&env, &caller);
+        
+        env.storage().instance().set(&ReferralKey::Referrer(user.clone()), &referrer);
+        
+        let count = Self::get_referral_count(env.clone(), referrer.clone()) + 1;
+        env.storage().instance().set(&ReferralKey::Count(referrer.clone()), &count);
+        
+        // The reward window is fixed when the referee joins, so later config
+        // changes don't reach back to existing referrals
+        let config = Self::get_referral_config(env.clone());
+        if config.rewarded_requests > 0 {
+            env.storage().instance().set(&ReferralKey::Remaining(user.clone()), &config.rewarded_requests);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "user_referred"), EVENT_SCHEMA_VERSION, referrer),
+            user
+        );
+    }
+
+    // The referrer's cut comes out of the platform fee the treasury just took on
+    // this payment
+    fn pay_referral(env: &Env, requester: &Address, fee_amount: i128) {
+        let remaining: u32 = env.storage().instance()
+            .get(&ReferralKey::Remaining(requester.clone()))
+            .unwrap_or(0);
+        if remaining == 0 {
+            return;
+        }
+        
+        if remaining == 1 {
+            env.storage().instance().remove(&ReferralKey::Remaining(requester.clone()));
+        } else {
+            env.storage().instance().set(&ReferralKey::Remaining(requester.clone()), &(remaining - 1));
+        }
+        
+        let referrer = match Self::get_referrer(env.clone(), requester.clone()) {
+            Some(referrer) => referrer,
+            None => return,
+        };
+        
+        let treasury = Self::get_treasury_balance(env.clone());
+        let reward = ((fee_amount * Self::get_referral_config(env.clone()).reward_bps as i128) / 10000).min(treasury);
+        if reward <= 0 {
+            return;
+        }
+        
+        env.storage().instance().set(&TreasuryKey::Balance, &(treasury - reward));
+        let rewards = Self::get_referral_rewards(env.clone(), referrer.clone()) + reward;
+        env.storage().instance().set(&ReferralKey::Rewards(referrer.clone()), &rewards);
+    }
+
+    // Artist of the month
+    // Periods are numbered timestamp / period_secs, so changing period_secs
+    // renumbers them; close any open period before changing it
+    pub fn set_artist_award_config(env: Env, config: ArtistAwardConfig) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if config.share_bps > 10000 || config.period_secs == 0 {
+            panic!("Invalid award config");
+        }
+        
+        env.storage().instance().set(&AwardKey::AwardConfig, &config);
+        record_audit(&env, &admin, "award_config", AuditTarget::Platform);
+        
+        env.events().publish(
+            (Symbol::new(&env, "award_config_set"), EVENT_SCHEMA_VERSION),
+            config
+        );
+    }
+
+    pub fn get_artist_award_config(env: Env) -> Option<ArtistAwardConfig> {
+        env.storage().instance().get(&AwardKey::AwardConfig)
+    }
+
+    pub fn get_current_award_period(env: Env) -> u64 {
+        let config = Self::get_artist_award_config(env.clone())
+            .unwrap_or_else(|| panic!("Artist award not configured"));
+        env.ledger().timestamp() / config.period_secs
+    }
+
+    // Leading artist and their paid plays for the period; the first to reach a
+    // count keeps the lead on a tie
+    pub fn get_period_leader(env: Env, period: u64) -> Option<(Address, u32)> {
+        env.storage().instance().get(&AwardKey::PeriodLeader(period))
+    }
+
+    fn record_award_play(env: &Env, artist: &Address, fee_amount: i128) {
+        let config = match Self::get_artist_award_config(env.clone()) {
+            Some(config) if config.share_bps > 0 => config,
+            _ => return,
+        };
+        let period = env.ledger().timestamp() / config.period_secs;
+        
+        let fees: i128 = env.storage().instance().get(&AwardKey::PeriodFees(period)).unwrap_or(0);
+        env.storage().instance().set(&AwardKey::PeriodFees(period), &(fees + fee_amount));
+        
+        let plays_key = AwardKey::PeriodPlays(period, artist.clone());
+        let plays: u32 = env.storage().instance().get::<_, u32>(&plays_key).unwrap_or(0) + 1;
+        env.storage().instance().set(&plays_key, &plays);
+        
+        let leader = Self::get_period_leader(env.clone(), period);
+        if leader.map(|(_, leading)| plays > leading).unwrap_or(true) {
+            env.storage().instance().set(&AwardKey::PeriodLeader(period), &(artist.clone(), plays));
+        }
+    }
+
+    // Permissionless once the period is over: moves share_bps of the fees collected
+    // in the period from the treasury to the leading artist's revenue balance
+    pub fn close_period(env: Env, period: u64) -> i128 {
+        if period >= Self::get_current_award_period(env.clone()) {
+            panic!("Period still open");
+        }
+        
+        if env.storage().instance().has(&AwardKey::PeriodClosed(period)) {
+            panic!("Period already closed");
+        }
+        env.storage().instance().set(&AwardKey::PeriodClosed(period), &true);
+        
+        let (winner, plays) = match Self::get_period_leader(env.clone(), period) {
+            Some(leader) => leader,
+            None => return 0,
+        };
+        
+        let config = Self::get_artist_award_config(env.clone()).unwrap();
+        let fees: i128 = env.storage().instance().get(&AwardKey::PeriodFees(period)).unwrap_or(0);
+        let treasury: i128 = env.storage().instance().get(&TreasuryKey::Balance).unwrap_or(0);
+        let award = ((fees * config.share_bps as i128) / 10000).min(treasury);
+        if award <= 0 {
+            return 0;
+        }
+        
+        env.storage().instance().set(&TreasuryKey::Balance, &(treasury - award));
+        Self::credit_revenue(&env, &winner, award);
+        
+        env.events().publish(
+            (Symbol::new(&env, "artist_of_period"), EVENT_SCHEMA_VERSION, winner),
+            (period, plays, award)
+        );
+        
+        award
+    }
+
+    pub fn propose_split_change(
+        env: Env,
+        artist: Address,
+        track_id: BytesN<32>,
+        new_split: Vec<(Address, u32)>,
+    ) {
+        artist.require_auth();
+        
+        let track = Self::catalog(&env)
+            .get_track(&track_id)
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        if env.storage().instance().has(&TrackKey::Splitter(track_id.clone())) {
+            panic!("Track pays a splitter contract");
+        }
+        
+        let mut total_split = 0;
+        for (_, percentage) in new_split.iter() {
+            total_split += percentage;
+        }
+        if total_split != 100 {
+            panic!("Royalty splits must total 100%");
+        }
+        
+        // Every current recipient signs off, and so does anyone being newly added
+        let mut required: Vec<Address> = Vec::new(&env);
+        for (recipient, _) in track.royalty_split.iter().chain(new_split.iter()) {
+            if recipient != artist && !required.contains(&recipient) {
+                required.push_back(recipient);
+            }
+        }
+        
+        let proposal = SplitProposal {
+            new_split,
+            required,
+            approvals: Vec::new(&env),
+            proposed_at: env.ledger().timestamp(),
+        };
+        
+        env.events().publish(
+            (Symbol::new(&env, "split_change_proposed"), EVENT_SCHEMA_VERSION, track_id.clone()),
+            proposal.new_split.clone()
+        );
+        
+        if proposal.required.is_empty() {
+            Self::apply_split_change(&env, track, proposal.new_split);
+        } else {
+            env.storage().instance().set(&TrackKey::SplitProposals(track_id), &proposal);
+        }
+    }
+
+    pub fn approve_split_change(env: Env, recipient: Address, track_id: BytesN<32>) {
+        recipient.require_auth();
+        
+        let mut proposal: SplitProposal = env.storage().instance()
+            .get(&TrackKey::SplitProposals(track_id.clone()))
+            .unwrap_or_else(|| panic!("No pending split change"));
+        
+        if !proposal.required.contains(&recipient) {
+            panic!("Not a split recipient");
+        }
+        
+        if proposal.approvals.contains(&recipient) {
+            panic!("Already approved");
+        }
+        
+        proposal.approvals.push_back(recipient.clone());
+        
+        env.events().publish(
+            (Symbol::new(&env, "split_change_approved"), EVENT_SCHEMA_VERSION, track_id.clone(), recipient.clone()),
+            recipient
+        );
+        
+        if proposal.approvals.len() == proposal.required.len() {
+            env.storage().instance().remove(&TrackKey::SplitProposals(track_id.clone()));
+            let track = Self::catalog(&env).get_track(&track_id).unwrap();
+            Self::apply_split_change(&env, track, proposal.new_split);
+        } else {
+            env.storage().instance().set(&TrackKey::SplitProposals(track_id), &proposal);
+        }
+    }
+
+    pub fn cancel_split_change(env: Env, artist: Address, track_id: BytesN<32>) {
+        artist.require_auth();
+        
+        let track = Self::catalog(&env)
+            .get_track(&track_id)
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        if !env.storage().instance().has(&TrackKey::SplitProposals(track_id.clone())) {
+            panic!("No pending split change");
+        }
+        
+        env.storage().instance().remove(&TrackKey::SplitProposals(track_id));
+    }
+
+    pub fn get_split_proposal(env: Env, track_id: BytesN<32>) -> Option<SplitProposal> {
+        env.storage().instance().get(&TrackKey::SplitProposals(track_id))
+    }
+
+    fn apply_split_change(env: &Env, mut track: Track, new_split: Vec<(Address, u32)>) {
+        // Keep the outgoing split so payouts made under it can still be audited
+        track.split_history.push_back(SplitChange {
+            split: track.royalty_split.clone(),
+            play_count: track.play_count,
+            replaced_at: env.ledger().timestamp(),
+        });
+        track.royalty_split = new_split;
+        
+        Self::catalog(env).save_track(&env.current_contract_address(), &track);
+        
+        env.events().publish(
+            (Symbol::new(env, "split_changed"), EVENT_SCHEMA_VERSION, track.track_id),
+            track.royalty_split
+        );
+    }
+
+    // Charities
+    pub fn set_charity_allowed(env: Env, charity: Address, allowed: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        let mut charities = Self::get_allowed_charities(env.clone());
+        match (charities.first_index_of(&charity), allowed) {
+            (None, true) => charities.push_back(charity.clone()),
+            (Some(index), false) => {
+                charities.remove(index);
+            }
+            _ => return,
+        }
+        env.storage().instance().set(&TrackKey::Charities, &charities);
+        
+        let action = if allowed { "charity_allowed" } else { "charity_removed" };
+        record_audit(&env, &admin, action, AuditTarget::Account(charity.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "charity_allowed"), EVENT_SCHEMA_VERSION, charity),
+            allowed
+        );
+    }
+
+    pub fn get_allowed_charities(env: Env) -> Vec<Address> {
+        env.storage().instance()
+            .get(&TrackKey::Charities)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Like a splitter, a charity share comes out of everyone's royalties, so it can
+    // only be added while the split is the artist alone
+    pub fn set_track_charity(env: Env, artist: Address, track_id: BytesN<32>, charity: Option<(Address, u32)>) {
+        artist.require_auth();
+        
+        let mut track = Self::catalog(&env)
+            .get_track(&track_id)
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        if let Some((address, bps)) = &charity {
+            if !Self::get_allowed_charities(env.clone()).contains(address) {
+                panic!("Charity not allowlisted");
+            }
+            if *bps == 0 || *bps > 10000 {
+                panic!("Invalid charity share");
+            }
+            if track.royalty_split != Vec::from_array(&env, [(artist.clone(), 100)]) {
+                panic!("Split must be the artist alone");
+            }
+        }
+        
+        track.charity = charity.as_ref().map(|(address, _)| address.clone());
+        track.charity_bps = charity.as_ref().map_or(0, |(_, bps)| *bps);
+        Self::catalog(&env).save_track(&env.current_contract_address(), &track);
+        
+        env.events().publish(
+            (Symbol::new(&env, "charity_set"), EVENT_SCHEMA_VERSION, track_id),
+            charity
+        );
+    }
+
+    // A charity dropped from the allowlist stops receiving at once; its share goes
+    // back to the split
+    fn charity_share(env: &Env, track: &Track, royalty_amount: i128) -> Option<(Address, i128)> {
+        let charity = track.charity.clone()?;
+        if !Self::get_allowed_charities(env.clone()).contains(&charity) {
+            return None;
+        }
+        Some((charity, (royalty_amount * track.charity_bps as i128) / 10000))
+    }
+
+    // Hands the track's default-token royalties to a splitter contract instead of
+    // its inline split. Only a track whose split is the artist alone can switch, so
+    // no other recipient is cut out without agreeing to a split change first.
+    pub fn set_track_splitter(env: Env, artist: Address, track_id: BytesN<32>, splitter: Option<Address>) {
+        artist.require_auth();
+        
+        let track = Self::catalog(&env)
+            .get_track(&track_id)
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        // Whatever the outgoing splitter is still owed goes out before it's replaced
+        if let Some(current) = Self::get_track_splitter(env.clone(), track_id.clone()) {
+            Self::flush_splitter(&env, &current, &track_id);
+        }
+        
+        match &splitter {
+            Some(splitter) => {
+                if track.royalty_split != Vec::from_array(&env, [(artist.clone(), 100)]) {
+                    panic!("Split must be the artist alone");
+                }
+                env.storage().instance().set(&TrackKey::Splitter(track_id.clone()), splitter);
+            }
+            None => env.storage().instance().remove(&TrackKey::Splitter(track_id.clone())),
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "splitter_set"), EVENT_SCHEMA_VERSION, track_id),
+            splitter
+        );
+    }
+
+    pub fn get_track_splitter(env: Env, track_id: BytesN<32>) -> Option<Address> {
+        env.storage().instance().get(&TrackKey::Splitter(track_id))
+    }
+
+    // Splitter payouts and vesting schedules are kept in the default token
+    pub fn requires_default_token(env: Env, track_id: BytesN<32>) -> bool {
+        if env.storage().instance().has(&TrackKey::Splitter(track_id.clone())) {
+            return true;
+        }
+        let track = Self::catalog(&env).get_track(&track_id).unwrap();
+        track.royalty_split.iter().any(|(recipient, _)| {
+            env.storage().instance().has(&TrackKey::Vesting(track_id.clone(), recipient))
+        })
+    }
+
+    // The splitter's share accrues like any other recipient's, so a dispute can
+    // still hold it back; push_splitter_revenue sends it on
+    fn pay_splitter(env: &Env, splitter: &Address, track_id: &BytesN<32>, amount: i128) {
+        if amount <= 0 {
+            return;
+        }
+        
+        Self::accrue_revenue(env, splitter, track_id, amount);
+        
+        env.events().publish(
+            (Symbol::new(env, "royalty_paid"), EVENT_SCHEMA_VERSION, splitter.clone(), track_id.clone()),
+            amount
+        );
+    }
+
+    // Permissionless: moves the track's accrued splitter share to the splitter
+    // contract and asks it to distribute
+    pub fn push_splitter_revenue(env: Env, track_id: BytesN<32>) -> i128 {
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        let splitter = Self::get_track_splitter(env.clone(), track_id.clone())
+            .unwrap_or_else(|| panic!("No splitter set"));
+        let amount = Self::flush_splitter(&env, &splitter, &track_id);
+        if amount == 0 {
+            panic!("Nothing to push");
+        }
+        amount
+    }
+
+    // A splitter that fails to distribute keeps the funds rather than failing the push
+    fn flush_splitter(env: &Env, splitter: &Address, track_id: &BytesN<32>) -> i128 {
+        let key = PayoutKey::Accruals(splitter.clone(), track_id.clone());
+        let amount: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        if amount <= 0 {
+            return 0;
+        }
+        // Zeroed rather than removed so the recipient's accrual index stays in step
+        env.storage().instance().set(&key, &0i128);
+        
+        token::Client::new(env, &Self::default_token(env)).transfer(
+            &env.current_contract_address(),
+            splitter,
+            &amount,
+        );
+        let distributed = PayoutSplitterClient::new(env, splitter).try_distribute(&amount).is_ok();
+        
+        env.events().publish(
+            (Symbol::new(env, "splitter_pushed"), EVENT_SCHEMA_VERSION, splitter.clone(), track_id.clone()),
+            amount
+        );
+        if !distributed {
+            env.events().publish(
+                (Symbol::new(env, "splitter_failed"), EVENT_SCHEMA_VERSION, splitter.clone(), track_id.clone()),
+                amount
+            );
+        }
+        amount
+    }
+
+    // Returns what was credited where, for callers that may need to undo it
+    fn distribute_royalties(env: &Env, track: &Track, table: Option<&JukeboxTable>, payment_amount: &i128) -> Settlement {
+        let mut settlement = Settlement {
+            shares: Vec::new(env),
+            vested: Vec::new(env),
+            fee: 0,
+            settled_at: env.ledger().timestamp(),
+        };
+        
+        // Royalties for taken-down tracks are frozen; the whole payment goes to the treasury
+        if Self::catalog(env).is_track_taken_down(&track.track_id) {
+            let treasury: i128 = env.storage().instance()
+                .get(&TreasuryKey::Balance)
+                .unwrap_or(0);
+            env.storage().instance().set(&TreasuryKey::Balance, &(treasury + payment_amount));
+            Self::catalog(env).record_stats(&env.current_contract_address(), &0, &0, &0, payment_amount);
+            settlement.fee = *payment_amount;
+            return settlement;
+        }
+        
+        let (fee_amount, owner_cut, table_cut, royalty_amount) =
+            Self::route_payment(env, &track.artist_id, table, *payment_amount);
+        Self::record_artist_volume(env, &track.artist_id);
+        
+        let mut earnings = Self::get_track_earnings(env.clone(), track.track_id.clone());
+        earnings.gross_revenue += payment_amount;
+        earnings.platform_fees_paid += fee_amount;
+        earnings.plays_paid += 1;
+        env.storage().instance().set(&TrackKey::Earnings(track.track_id.clone()), &earnings);
+        Self::record_artist_earnings(env, &track.artist_id, &track.track_id, &earnings, *payment_amount, fee_amount);
+        
+        let treasury: i128 = env.storage().instance()
+            .get(&TreasuryKey::Balance)
+            .unwrap_or(0);
+        env.storage().instance().set(&TreasuryKey::Balance, &(treasury + fee_amount));
+        Self::catalog(env).record_stats(&env.current_contract_address(), &0, &0, &royalty_amount, &fee_amount);
+        Self::record_award_play(env, &track.artist_id, fee_amount);
+        settlement.fee = fee_amount;
+        
+        if let Some(table) = table.filter(|_| owner_cut > 0 || table_cut > 0) {
+            if owner_cut > 0 {
+                Self::accrue_revenue(env, &table.owner, &track.track_id, owner_cut);
+                settlement.shares.push_back((table.owner.clone(), owner_cut));
+            }
+            let table_treasury = Self::get_table_treasury(env.clone(), table.table_id.clone());
+            env.storage().instance().set(&FeeKey::TableTreasury(table.table_id.clone()), &(table_treasury + table_cut));
+        }
+        
+        let mut royalty_amount = royalty_amount;
+        if let Some((charity, share)) = Self::charity_share(env, track, royalty_amount) {
+            Self::accrue_revenue(env, &charity, &track.track_id, share);
+            settlement.shares.push_back((charity.clone(), share));
+            royalty_amount -= share;
+            
+            env.events().publish(
+                (Symbol::new(env, "charity_paid"), EVENT_SCHEMA_VERSION, charity, track.track_id.clone()),
+                share
+            );
+        }
+        
+        if let Some(splitter) = Self::get_track_splitter(env.clone(), track.track_id.clone()) {
+            Self::pay_splitter(env, &splitter, &track.track_id, royalty_amount);
+            return settlement;
+        }
+        
+        // Shares are accrued rather than pushed so one unpayable recipient can't
+        // fail the request; recipients pull them through withdraw_revenue
+        for (recipient, percentage) in track.royalty_split.iter() {
+            let share = (royalty_amount * (percentage as i128)) / 100;
+            let unlocked = Self::lock_vesting(env, &track.track_id, &recipient, share);
+            Self::accrue_revenue(env, &recipient, &track.track_id, unlocked);
+            settlement.shares.push_back((recipient.clone(), unlocked));
+            if share > unlocked {
+                settlement.vested.push_back((recipient.clone(), share - unlocked));
+            }
+            
+            env.events().publish(
+                (Symbol::new(env, "royalty_paid"), EVENT_SCHEMA_VERSION, recipient, track.track_id.clone()),
+                unlocked
+            );
+        }
+        
+        settlement
+    }
+
+    fn accrue_revenue(env: &Env, recipient: &Address, track_id: &BytesN<32>, amount: i128) {
+        let key = PayoutKey::Accruals(recipient.clone(), track_id.clone());
+        let accrued: Option<i128> = env.storage().instance().get(&key);
+        
+        if accrued.is_none() {
+            let mut index: Vec<BytesN<32>> = env.storage().instance()
+                .get(&PayoutKey::AccrualIndex(recipient.clone()))
+                .unwrap_or(Vec::new(env));
+            index.push_back(track_id.clone());
+            env.storage().instance().set(&PayoutKey::AccrualIndex(recipient.clone()), &index);
+        }
+        
+        env.storage().instance().set(&key, &(accrued.unwrap_or(0) + amount));
+    }
+
+    // An artist's balance holds what isn't tied to a track: grants, awards,
+    // subscriptions and campaign funds
+    fn revenue_balance(env: &Env, artist: &Address) -> i128 {
+        env.storage().instance().get(&PayoutKey::Revenue(artist.clone())).unwrap_or(0)
+    }
+
+    fn credit_revenue(env: &Env, artist: &Address, amount: i128) {
+        let balance = Self::revenue_balance(env, artist);
+        env.storage().instance().set(&PayoutKey::Revenue(artist.clone()), &(balance + amount));
+    }
+
+    fn require_artist(env: &Env, artist: &Address) {
+        if Self::catalog(env).get_artist(artist).is_none() {
+            panic!("Not registered as artist");
+        }
+    }
+
+    fn catalog(env: &Env) -> CatalogClient<'_> {
+        CatalogClient::new(env, &links(env).catalog)
+    }
+
+    // Zeroes and returns the recipient's artist balance plus up to `limit` per-track
+    // accruals, oldest first
+    fn take_revenue(env: &Env, recipient: &Address, limit: u32) -> i128 {
+        let mut amount = Self::revenue_balance(env, recipient);
+        env.storage().instance().remove(&PayoutKey::Revenue(recipient.clone()));
+        
+        let index: Vec<BytesN<32>> = env.storage().instance()
+            .get(&PayoutKey::AccrualIndex(recipient.clone()))
+            .unwrap_or(Vec::new(env));
+        let taken = limit.min(index.len());
+        
+        for track_id in index.slice(0..taken).iter() {
+            let key = PayoutKey::Accruals(recipient.clone(), track_id);
+            amount += env.storage().instance().get::<_, i128>(&key).unwrap_or(0);
+            env.storage().instance().remove(&key);
+        }
+        
+        if taken == index.len() {
+            env.storage().instance().remove(&PayoutKey::AccrualIndex(recipient.clone()));
+        } else {
+            env.storage().instance().set(&PayoutKey::AccrualIndex(recipient.clone()), &index.slice(taken..));
+        }
+        
+        amount
+    }
+
+    // Both sides agree to the deal: from now on `vested_bps` of the recipient's share
+    // is held back and unlocks linearly over `duration_secs` after a cliff
+    pub fn set_vesting_schedule(
+        env: Env,
+        artist: Address,
+        recipient: Address,
+        track_id: BytesN<32>,
+        vested_bps: u32,
+        cliff_secs: u64,
+        duration_secs: u64,
+    ) {
+        artist.require_auth();
+        if recipient != artist {
+            recipient.require_auth();
+        }
+        
+        let track = Self::catalog(&env)
+            .get_track(&track_id)
+            .unwrap_or_else(|| panic!("Track not found"));
+        
+        if track.artist_id != artist {
+            panic!("Not track owner");
+        }
+        
+        if !track.royalty_split.iter().any(|(address, _)| address == recipient) {
+            panic!("Not a split recipient");
+        }
+        
+        if vested_bps == 0 || vested_bps > 10000 {
+            panic!("Invalid vested share");
+        }
+        
+        if duration_secs == 0 || cliff_secs > duration_secs {
+            panic!("Invalid vesting period");
+        }
+        
+        if env.storage().instance().has(&TrackKey::Vesting(track_id.clone(), recipient.clone())) {
+            panic!("Vesting schedule already set");
+        }
+        
+        let schedule = VestingSchedule {
+            vested_bps,
+            start: env.ledger().timestamp(),
+            cliff_secs,
+            duration_secs,
+            total: 0,
+            claimed: 0,
+        };
+        env.storage().instance().set(&TrackKey::Vesting(track_id.clone(), recipient.clone()), &schedule);
+        
+        env.events().publish(
+            (Symbol::new(&env, "vesting_set"), EVENT_SCHEMA_VERSION, track_id, recipient),
+            schedule
+        );
+    }
+
+    pub fn get_vesting_schedule(env: Env, track_id: BytesN<32>, recipient: Address) -> Option<VestingSchedule> {
+        env.storage().instance().get(&TrackKey::Vesting(track_id, recipient))
+    }
+
+    pub fn claim_vested(env: Env, recipient: Address, track_id: BytesN<32>) -> i128 {
+        recipient.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        let mut schedule: VestingSchedule = env.storage().instance()
+            .get(&TrackKey::Vesting(track_id.clone(), recipient.clone()))
+            .unwrap_or_else(|| panic!("No vesting schedule"));
+        
+        let elapsed = env.ledger().timestamp().saturating_sub(schedule.start);
+        let unlocked = if elapsed < schedule.cliff_secs {
+            0
+        } else if elapsed >= schedule.duration_secs {
+            schedule.total
+        } else {
+            schedule.total * elapsed as i128 / schedule.duration_secs as i128
+        };
+        
+        let amount = unlocked - schedule.claimed;
+        if amount <= 0 {
+            panic!("Nothing vested");
+        }
+        
+        schedule.claimed = unlocked;
+        env.storage().instance().set(&TrackKey::Vesting(track_id.clone(), recipient.clone()), &schedule);
+        
+        let token_address: Address = env.storage().instance()
+            .get(&DataKey::TokenStellar)
+            .unwrap();
+        token::Client::new(&env, &token_address).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+        
+        env.events().publish(
+            (Symbol::new(&env, "vested_claimed"), EVENT_SCHEMA_VERSION, track_id, recipient.clone()),
+            (recipient, amount)
+        );
+        
+        amount
+    }
+
+    // Moves the vesting portion of a share into the schedule and returns the rest
+    fn lock_vesting(env: &Env, track_id: &BytesN<32>, recipient: &Address, share: i128) -> i128 {
+        let key = TrackKey::Vesting(track_id.clone(), recipient.clone());
+        let Some(mut schedule) = env.storage().instance().get::<_, VestingSchedule>(&key) else {
+            return share;
+        };
+        
+        let locked = share * schedule.vested_bps as i128 / 10000;
+        schedule.total += locked;
+        env.storage().instance().set(&key, &schedule);
+        
+        share - locked
+    }
+
+    pub fn get_track_earnings(env: Env, track_id: BytesN<32>) -> TrackEarnings {
+        env.storage().instance()
+            .get(&TrackKey::Earnings(track_id))
+            .unwrap_or(TrackEarnings {
+                gross_revenue: 0,
+                platform_fees_paid: 0,
+                plays_paid: 0,
+            })
+    }
+
+    // Totals are attributed to the track's artist; split recipients see their own
+    // share through get_claimable_revenue
+    pub fn get_artist_earnings(env: Env, artist: Address) -> ArtistEarnings {
+        let totals = Self::artist_earning_totals(&env, &artist);
+        ArtistEarnings {
+            gross_revenue: totals.gross_revenue,
+            platform_fees_paid: totals.platform_fees_paid,
+            plays_paid: totals.plays_paid,
+            top_tracks: env.storage().instance()
+                .get(&EarningsKey::TopEarners(artist.clone()))
+                .unwrap_or(Vec::new(&env)),
+            claimable: Self::get_claimable_revenue(env.clone(), artist),
+        }
+    }
+
+    fn artist_earning_totals(env: &Env, artist: &Address) -> TrackEarnings {
+        env.storage().instance()
+            .get(&EarningsKey::ArtistTotals(artist.clone()))
+            .unwrap_or(TrackEarnings {
+                gross_revenue: 0,
+                platform_fees_paid: 0,
+                plays_paid: 0,
+            })
+    }
+
+    // Track gross only grows, so the top list is updated in one bounded pass the
+    // same way leaderboards are
+    fn record_artist_earnings(
+        env: &Env,
+        artist: &Address,
+        track_id: &BytesN<32>,
+        track_earnings: &TrackEarnings,
+        payment_amount: i128,
+        fee_amount: i128,
+    ) {
+        let mut totals = Self::artist_earning_totals(env, artist);
+        totals.gross_revenue += payment_amount;
+        totals.platform_fees_paid += fee_amount;
+        totals.plays_paid += 1;
+        env.storage().instance().set(&EarningsKey::ArtistTotals(artist.clone()), &totals);
+        CommunityClient::new(env, &links(env).community).record_top_artist(
+            &env.current_contract_address(),
+            artist,
+            &totals.gross_revenue,
+        );
+        
+        let key = EarningsKey::TopEarners(artist.clone());
+        let mut top: Vec<(BytesN<32>, i128)> = env.storage().instance()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        if let Some(index) = top.iter().position(|(id, _)| &id == track_id) {
+            top.remove(index as u32);
+        }
+        
+        let gross = track_earnings.gross_revenue;
+        let position = top.iter()
+            .position(|(_, earned)| earned < gross)
+            .map(|index| index as u32)
+            .unwrap_or(top.len());
+        
+        if position < MAX_TOP_EARNING_TRACKS {
+            top.insert(position, (track_id.clone(), gross));
+            if top.len() > MAX_TOP_EARNING_TRACKS {
+                top.pop_back();
+            }
+            env.storage().instance().set(&key, &top);
+        }
+    }
+
+    pub fn get_artist_earnings_breakdown(
+        env: Env,
+        artist: Address,
+        start: u32,
+        limit: u32,
+    ) -> Vec<(BytesN<32>, TrackEarnings)> {
+        let catalog = Self::catalog(&env).get_artist_tracks(&artist);
+        
+        let mut breakdown = Vec::new(&env);
+        let end = start.saturating_add(limit).min(catalog.len());
+        for i in start..end {
+            let track_id = catalog.get(i).unwrap();
+            breakdown.push_back((track_id.clone(), Self::get_track_earnings(env.clone(), track_id)));
+        }
+        breakdown
+    }
+
+    pub fn get_claimable_revenue(env: Env, recipient: Address) -> i128 {
+        let mut amount = Self::revenue_balance(&env, &recipient);
+        
+        for track_id in Self::get_accrual_tracks(env.clone(), recipient.clone()).iter() {
+            amount += env.storage().instance()
+                .get::<_, i128>(&PayoutKey::Accruals(recipient.clone(), track_id))
+                .unwrap_or(0);
+        }
+        
+        amount
+    }
+
+    pub fn get_accrual_tracks(env: Env, recipient: Address) -> Vec<BytesN<32>> {
+        env.storage().instance()
+            .get(&PayoutKey::AccrualIndex(recipient))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Claims up to `limit` per-track accruals in a single transfer. Call repeatedly
+    // until get_accrual_tracks is empty for recipients with a long tail of tracks.
+    pub fn claim_all(env: Env, recipient: Address, limit: u32) -> i128 {
+        recipient.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        if limit == 0 {
+            panic!("Limit must be positive");
+        }
+        
+        let amount = Self::take_revenue(&env, &recipient, limit);
+        
+        if amount > 0 {
+            let token_address: Address = env.storage().instance()
+                .get(&DataKey::TokenStellar)
+                .unwrap();
+            token::Client::new(&env, &token_address).transfer(
+                &env.current_contract_address(),
+                &recipient,
+                &amount,
+            );
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "revenue_claimed"), EVENT_SCHEMA_VERSION, recipient),
+            amount
+        );
+        
+        amount
+    }
+
+    pub fn withdraw_revenue(env: Env, artist: Address) -> i128 {
+        artist.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        // Non-artist split recipients withdraw through here too
+        let amount = Self::take_revenue(&env, &artist, u32::MAX);
+        if amount == 0 && Self::catalog(&env).get_artist(&artist).is_none() {
+            panic!("Nothing to withdraw");
+        }
+        
+        let token_address: Address = env.storage().instance()
+            .get(&DataKey::TokenStellar)
+            .unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        
+        token_client.transfer(
+            &env.current_contract_address(),
+            &artist,
+            &amount,
+        );
+        
+        env.events().publish(
+            (Symbol::new(&env, "revenue_withdrawn"), EVENT_SCHEMA_VERSION, artist.clone()),
+            (amount, artist)
+        );
+        
+        amount
+    }
+
+    // Pays part of the artist's balance to any address; whatever is left, including
+    // per-track accruals swept up along the way, stays on the revenue balance
+    pub fn withdraw_revenue_amount(env: Env, artist: Address, amount: i128, destination: Address) -> i128 {
+        artist.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        Self::require_artist(&env, &artist);
+        
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+        
+        let balance = Self::take_revenue(&env, &artist, u32::MAX);
+        if amount > balance {
+            panic!("Insufficient balance");
+        }
+        
+        Self::credit_revenue(&env, &artist, balance - amount);
+        
+        let token_address: Address = env.storage().instance()
+            .get(&DataKey::TokenStellar)
+            .unwrap();
+        token::Client::new(&env, &token_address).transfer(
+            &env.current_contract_address(),
+            &destination,
+            &amount,
+        );
+        
+        env.events().publish(
+            (Symbol::new(&env, "revenue_withdrawn"), EVENT_SCHEMA_VERSION, artist),
+            (amount, destination)
+        );
+        
+        balance - amount
+    }
+
+    // Payout currency conversion
+    pub fn set_swap_router(env: Env, router: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        env.storage().instance().set(&PayoutKey::SwapRouter, &router);
+        record_audit(&env, &admin, "swap_router_set", AuditTarget::Account(router.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "swap_router_set"), EVENT_SCHEMA_VERSION),
+            router
+        );
+    }
+
+    pub fn get_swap_router(env: Env) -> Option<Address> {
+        env.storage().instance().get(&PayoutKey::SwapRouter)
+    }
+
+    pub fn set_payout_token(env: Env, artist: Address, payout_token: Option<Address>) {
+        artist.require_auth();
+        
+        Self::require_artist(&env, &artist);
+        
+        match &payout_token {
+            Some(payout_token) => env.storage().instance().set(&PayoutKey::PayoutToken(artist.clone()), payout_token),
+            None => env.storage().instance().remove(&PayoutKey::PayoutToken(artist.clone())),
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "payout_token_set"), EVENT_SCHEMA_VERSION, artist),
+            payout_token
+        );
+    }
+
+    pub fn get_payout_token(env: Env, artist: Address) -> Option<Address> {
+        env.storage().instance().get(&PayoutKey::PayoutToken(artist))
+    }
+
+    // Withdraws the full revenue balance, swapped into the artist's preferred payout token
+    pub fn withdraw_revenue_swapped(env: Env, artist: Address, min_amount_out: i128) -> i128 {
+        artist.require_auth();
+        
+        require_not_paused(&env, PAUSE_WITHDRAWALS);
+        
+        let payout_token: Address = env.storage().instance()
+            .get(&PayoutKey::PayoutToken(artist.clone()))
+            .unwrap_or_else(|| panic!("No payout token set"));
+        
+        let router: Address = env.storage().instance()
+            .get(&PayoutKey::SwapRouter)
+            .unwrap_or_else(|| panic!("No swap router configured"));
+        
+        let amount = Self::take_revenue(&env, &artist, u32::MAX);
+        if amount <= 0 {
+            panic!("Nothing to withdraw");
+        }
+        
+        let token_address: Address = env.storage().instance()
+            .get(&DataKey::TokenStellar)
+            .unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        
+        token_client.approve(
+            &env.current_contract_address(),
+            &router,
+            &amount,
+            &env.ledger().sequence(),
+        );
+        
+        let amount_out = SwapRouterClient::new(&env, &router).swap_exact_in(
+            &env.current_contract_address(),
+            &token_address,
+            &payout_token,
+            &amount,
+            &min_amount_out,
+            &artist,
+        );
+        
+        if amount_out < min_amount_out {
+            panic!("Slippage bound exceeded");
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "revenue_swapped"), EVENT_SCHEMA_VERSION, artist),
+            (amount, payout_token, amount_out)
+        );
+        
+        amount_out
+    }
+}
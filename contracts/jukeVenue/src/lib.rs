@@ -0,0 +1,1319 @@
+#![no_std]
+#![allow(clippy::too_many_arguments)]
+use juke_common::{
+    links, record_audit, require_active_user, validation, AuditEntry, AuditTarget, CatalogClient, CommunityClient,
+    HubClient, JukeboxTable, Links, PayoutsClient, SwapRouterClient, TrackMintParams, TrackRequest, CONTRACT_VERSION,
+    EVENT_SCHEMA_VERSION, FEATURE_PROMOS,
+};
+use soroban_sdk::{contract, contractimpl, contractmeta, contracttype, token, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Symbol, Vec};
+
+contractmeta!(key = "binver", val = "0.1.0");
+
+// ----- Data Structures -----
+#[contracttype]
+#[derive(Clone)]
+pub struct Album {
+    pub album_id: BytesN<32>,
+    pub artist: Address,
+    pub title: String,
+    pub metadata_uri: String,
+    pub track_ids: Vec<BytesN<32>>,
+    pub discount_bps: u32,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Playlist {
+    pub playlist_id: BytesN<32>,
+    pub owner: Address,
+    pub name: String,
+    pub track_ids: Vec<BytesN<32>>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CampaignStatus {
+    Open,
+    Funded,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Campaign {
+    campaign_id: u32,
+    artist: Address,
+    goal: i128,
+    raised: i128,
+    deadline: u64,
+    backer_share: u32,
+    status: CampaignStatus,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct KioskDelegation {
+    pub spend_cap: i128,
+    pub spent: i128,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Device {
+    pub table_id: BytesN<32>,
+    pub label: String,
+    pub registered_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum PromoReward {
+    Requests(u32),
+    Credit(i128),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PromoCode {
+    code_hash: BytesN<32>,
+    reward: PromoReward,
+    uses_remaining: u32,
+    expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct VoucherPayload {
+    pub user: Address,
+    pub track_id: BytesN<32>,
+    pub table_id: BytesN<32>,
+    pub max_price: i128,
+    pub nonce: u64,
+    pub expires_at: u64,
+}
+
+// ----- Storage Keys -----
+// Keys encode as the variant name plus its fields, without the enum name, so a
+// variant must not reuse a name and field types already taken by another key enum
+// here or in juke_common.
+#[contracttype]
+enum DataKey {
+    Admin,
+    TokenStellar,
+}
+
+#[contracttype]
+enum VoucherKey {
+    SigningKeys(Address),
+    Nonces(Address, u64),
+}
+
+#[contracttype]
+enum DeviceKey {
+    Devices(Address),
+    TableDevices(BytesN<32>),
+}
+
+#[contracttype]
+enum KioskKey {
+    Delegations(Address, Address),
+}
+
+#[contracttype]
+enum SponsorKey {
+    Sponsors(BytesN<32>),
+    Spend(Address),
+}
+
+#[contracttype]
+enum AirdropKey {
+    Round,
+    Roots(u32),
+    Claimed(u32, Address),
+    Unclaimed(u32),
+}
+
+#[contracttype]
+enum PromoKey {
+    Codes(BytesN<32>),
+    Redeemed(BytesN<32>, Address),
+}
+
+#[contracttype]
+enum CampaignKey {
+    CampaignCounter,
+    Campaigns(u32),
+    Pledges(u32, Address),
+    Backers(u32),
+    Track(u32),
+}
+
+#[contracttype]
+enum PlaylistKey {
+    Playlists(BytesN<32>),
+    PlaylistIdCounter,
+}
+
+#[contracttype]
+enum AlbumKey {
+    Albums(BytesN<32>),
+    AlbumIdCounter,
+    TrackAlbum(BytesN<32>),
+    Upc(BytesN<32>),
+    UpcToAlbum(String),
+}
+
+const MAX_CAMPAIGN_BACKERS: u32 = 50;
+// How long after the deadline a funded campaign has to mint before backers can walk away
+const CAMPAIGN_MINT_WINDOW: u64 = 30 * 86400;
+
+// Kiosks, vouchers, sponsors, swaps, devices, albums, playlists, crowdfunding,
+// airdrops, promo codes and gifted credit; requests are placed through the hub
+#[contract]
+pub struct JukeVenue;
+
+#[contractimpl]
+impl JukeVenue {
+    pub fn initialize(env: Env, admin: Address, token_stellar: Address, links: Links) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::TokenStellar, &token_stellar);
+        juke_common::set_links(&env, &links);
+        
+        env.events().publish(
+            (Symbol::new(&env, "initialized"), EVENT_SCHEMA_VERSION),
+            (admin, token_stellar)
+        );
+    }
+
+    pub fn get_version(_env: Env) -> (u32, u32, u32) {
+        CONTRACT_VERSION
+    }
+
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        links(&env).governance.require_auth();
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    // Opening an airdrop round is audited here
+    pub fn get_audit_count(env: Env) -> u32 {
+        juke_common::audit_count(&env)
+    }
+
+    pub fn get_audit_log(env: Env, start: u32, limit: u32) -> Vec<AuditEntry> {
+        juke_common::audit_log(&env, start, limit)
+    }
+
+    // Kiosk delegation
+    // Lets a venue kiosk place requests for the user, paid only from their prepaid
+    // balance and credit, up to `spend_cap` in total until `expires_at`
+    pub fn authorize_kiosk(env: Env, user: Address, kiosk: Address, spend_cap: i128, expires_at: u64) {
+        user.require_auth();
+        
+        require_active_user(&env, &user);
+        
+        if spend_cap <= 0 || expires_at <= env.ledger().timestamp() {
+            panic!("Invalid delegation");
+        }
+        
+        env.storage().instance().set(
+            &KioskKey::Delegations(user.clone(), kiosk.clone()),
+            &KioskDelegation {
+                spend_cap,
+                spent: 0,
+                expires_at,
+            },
+        );
+        
+        env.events().publish(
+            (Symbol::new(&env, "kiosk_authorized"), EVENT_SCHEMA_VERSION, user, kiosk),
+            (spend_cap, expires_at)
+        );
+    }
+
+    pub fn revoke_kiosk(env: Env, user: Address, kiosk: Address) {
+        user.require_auth();
+        
+        env.storage().instance().remove(&KioskKey::Delegations(user.clone(), kiosk.clone()));
+        
+        env.events().publish(
+            (Symbol::new(&env, "kiosk_revoked"), EVENT_SCHEMA_VERSION, user, kiosk),
+            ()
+        );
+    }
+
+    pub fn get_kiosk_delegation(env: Env, user: Address, kiosk: Address) -> Option<KioskDelegation> {
+        env.storage().instance().get(&KioskKey::Delegations(user, kiosk))
+    }
+
+    pub fn request_track_delegated(
+        env: Env,
+        kiosk: Address,
+        user: Address,
+        track_id: BytesN<32>,
+        table_id: BytesN<32>,
+    ) -> BytesN<32> {
+        kiosk.require_auth();
+        
+        let key = KioskKey::Delegations(user.clone(), kiosk.clone());
+        let mut delegation: KioskDelegation = env.storage().instance()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Kiosk not authorized"));
+        
+        if env.ledger().timestamp() >= delegation.expires_at {
+            panic!("Delegation expired");
+        }
+        
+        // The user isn't signing, so the request must never fall through to a
+        // token transfer from their account
+        let hub = Self::hub(&env);
+        let max_price = hub.get_request_price(&track_id, &table_id);
+        let prepaid = hub.get_wallet_balance(&user) + hub.get_token_credit(&user);
+        if prepaid < max_price {
+            panic!("Prepaid balance too low");
+        }
+        
+        let request_id = Self::place_request(&env, &user, &user, &track_id, &table_id, 0);
+        
+        let request: TrackRequest = hub.get_request(&request_id).unwrap();
+        delegation.spent += request.amount_paid;
+        if delegation.spent > delegation.spend_cap {
+            panic!("Kiosk spend cap exceeded");
+        }
+        env.storage().instance().set(&key, &delegation);
+        
+        request_id
+    }
+
+    // Vouchers
+    pub fn set_voucher_key(env: Env, user: Address, public_key: BytesN<32>) {
+        user.require_auth();
+        
+        require_active_user(&env, &user);
+        
+        env.storage().instance().set(&VoucherKey::SigningKeys(user.clone()), &public_key);
+        
+        env.events().publish(
+            (Symbol::new(&env, "voucher_key_set"), EVENT_SCHEMA_VERSION, user),
+            public_key
+        );
+    }
+
+    pub fn get_voucher_key(env: Env, user: Address) -> Option<BytesN<32>> {
+        env.storage().instance().get(&VoucherKey::SigningKeys(user))
+    }
+
+    pub fn is_nonce_used(env: Env, user: Address, nonce: u64) -> bool {
+        env.storage().instance().has(&VoucherKey::Nonces(user, nonce))
+    }
+
+    // Burns a nonce so a voucher already handed out can't be redeemed
+    pub fn cancel_voucher(env: Env, user: Address, nonce: u64) {
+        user.require_auth();
+        
+        env.storage().instance().set(&VoucherKey::Nonces(user.clone(), nonce), &true);
+        
+        env.events().publish(
+            (Symbol::new(&env, "voucher_cancelled"), EVENT_SCHEMA_VERSION, user),
+            nonce
+        );
+    }
+
+    // The signed message is this contract's address XDR followed by the payload
+    // XDR. Like kiosk requests, vouchers are paid only from prepaid balance and
+    // credit, since the user isn't present to authorize a transfer.
+    pub fn redeem_voucher(env: Env, signature: BytesN<64>, payload: VoucherPayload) -> BytesN<32> {
+        let public_key = Self::get_voucher_key(env.clone(), payload.user.clone())
+            .unwrap_or_else(|| panic!("No voucher key registered"));
+        
+        let mut message = env.current_contract_address().to_xdr(&env);
+        message.append(&payload.clone().to_xdr(&env));
+        env.crypto().ed25519_verify(&public_key, &message, &signature);
+        
+        if env.ledger().timestamp() >= payload.expires_at {
+            panic!("Voucher expired");
+        }
+        
+        if Self::is_nonce_used(env.clone(), payload.user.clone(), payload.nonce) {
+            panic!("Voucher already used");
+        }
+        env.storage().instance().set(&VoucherKey::Nonces(payload.user.clone(), payload.nonce), &true);
+        
+        let hub = Self::hub(&env);
+        let price = hub.get_request_price(&payload.track_id, &payload.table_id);
+        if price > payload.max_price {
+            panic!("Price above voucher maximum");
+        }
+        let prepaid = hub.get_wallet_balance(&payload.user) + hub.get_token_credit(&payload.user);
+        if prepaid < price {
+            panic!("Prepaid balance too low");
+        }
+        
+        let request_id = Self::place_request(&env, &payload.user, &payload.user, &payload.track_id, &payload.table_id, 0);
+        
+        env.events().publish(
+            (Symbol::new(&env, "voucher_redeemed"), EVENT_SCHEMA_VERSION, payload.user),
+            (payload.nonce, request_id.clone())
+        );
+        
+        request_id
+    }
+
+    // Airdrops
+    // Each new root opens a new round; earlier rounds can no longer be claimed and
+    // whatever they left unclaimed goes back to the admin. Like promo credit, the
+    // round's `total` is escrowed up front and claims stop once it runs out.
+    pub fn set_airdrop_root(env: Env, root: BytesN<32>, total: i128) -> u32 {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        if total < 0 {
+            panic!("Total cannot be negative");
+        }
+        
+        let token_client = token::Client::new(&env, &Self::default_token(&env));
+        let previous = Self::get_airdrop_round(env.clone());
+        let leftover = Self::get_airdrop_unclaimed(env.clone(), previous);
+        if leftover > 0 {
+            env.storage().instance().remove(&AirdropKey::Unclaimed(previous));
+            token_client.transfer(&env.current_contract_address(), &admin, &leftover);
+        }
+        if total > 0 {
+            token_client.transfer(&admin, &env.current_contract_address(), &total);
+        }
+        
+        let round = previous + 1;
+        env.storage().instance().set(&AirdropKey::Round, &round);
+        env.storage().instance().set(&AirdropKey::Roots(round), &root);
+        env.storage().instance().set(&AirdropKey::Unclaimed(round), &total);
+        record_audit(&env, &admin, "airdrop_root", AuditTarget::Platform);
+        
+        env.events().publish(
+            (Symbol::new(&env, "airdrop_opened"), EVENT_SCHEMA_VERSION, round),
+            (root, total)
+        );
+        
+        round
+    }
+
+    pub fn get_airdrop_round(env: Env) -> u32 {
+        env.storage().instance().get(&AirdropKey::Round).unwrap_or(0)
+    }
+
+    pub fn get_airdrop_unclaimed(env: Env, round: u32) -> i128 {
+        env.storage().instance().get(&AirdropKey::Unclaimed(round)).unwrap_or(0)
+    }
+
+    pub fn is_airdrop_claimed(env: Env, round: u32, user: Address) -> bool {
+        env.storage().instance().has(&AirdropKey::Claimed(round, user))
+    }
+
+    // Leaves are sha256(user XDR || amount as big-endian i128) and pairs are hashed
+    // in sorted order, so proofs don't need to carry left/right positions
+    pub fn claim_airdrop(env: Env, user: Address, proof: Vec<BytesN<32>>, amount: i128) {
+        user.require_auth();
+        
+        if Self::catalog(&env).get_user(&user).is_none() {
+            panic!("User not registered");
+        }
+        
+        let round = Self::get_airdrop_round(env.clone());
+        let root: BytesN<32> = env.storage().instance()
+            .get(&AirdropKey::Roots(round))
+            .unwrap_or_else(|| panic!("No active airdrop"));
+        
+        if Self::is_airdrop_claimed(env.clone(), round, user.clone()) {
+            panic!("Airdrop already claimed");
+        }
+        
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+        
+        let mut leaf = user.clone().to_xdr(&env);
+        leaf.extend_from_array(&amount.to_be_bytes());
+        let mut node: BytesN<32> = env.crypto().sha256(&leaf).into();
+        
+        for sibling in proof.iter() {
+            let (first, second) = if node.to_array() <= sibling.to_array() {
+                (node, sibling)
+            } else {
+                (sibling, node)
+            };
+            let mut pair = Bytes::from_array(&env, &first.to_array());
+            pair.extend_from_array(&second.to_array());
+            node = env.crypto().sha256(&pair).into();
+        }
+        
+        if node != root {
+            panic!("Invalid airdrop proof");
+        }
+        
+        let unclaimed = Self::get_airdrop_unclaimed(env.clone(), round);
+        if amount > unclaimed {
+            panic!("Airdrop exhausted");
+        }
+        env.storage().instance().set(&AirdropKey::Unclaimed(round), &(unclaimed - amount));
+        env.storage().instance().set(&AirdropKey::Claimed(round, user.clone()), &true);
+        
+        Self::credit_on_hub(&env, &env.current_contract_address(), &user, amount);
+        
+        env.events().publish(
+            (Symbol::new(&env, "airdrop_claimed"), EVENT_SCHEMA_VERSION, round, user),
+            amount
+        );
+    }
+
+    // Promo codes
+    // Rewards are spent on the hub, so redeeming one credits it there; token
+    // credits are backed here up front and move to the hub as they're redeemed
+    pub fn register_promo(
+        env: Env,
+        code_hash: BytesN<32>,
+        reward: PromoReward,
+        max_uses: u32,
+        expires_at: u64,
+    ) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        Self::require_promos(&env);
+        
+        if env.storage().instance().has(&PromoKey::Codes(code_hash.clone())) {
+            panic!("Promo code already registered");
+        }
+        
+        if max_uses == 0 {
+            panic!("Promo code needs at least one use");
+        }
+        
+        if expires_at <= env.ledger().timestamp() {
+            panic!("Promo code already expired");
+        }
+        
+        // Token credits are backed up front so redemptions are always payable
+        if let PromoReward::Credit(amount) = reward {
+            if amount <= 0 {
+                panic!("Credit must be positive");
+            }
+            
+            token::Client::new(&env, &Self::default_token(&env)).transfer(
+                &admin,
+                &env.current_contract_address(),
+                &(amount * max_uses as i128),
+            );
+        }
+        
+        let promo = PromoCode {
+            code_hash: code_hash.clone(),
+            reward,
+            uses_remaining: max_uses,
+            expires_at,
+        };
+        
+        env.storage().instance().set(&PromoKey::Codes(code_hash.clone()), &promo);
+        
+        env.events().publish(
+            (Symbol::new(&env, "promo_registered"), EVENT_SCHEMA_VERSION, code_hash),
+            (promo.uses_remaining, promo.expires_at)
+        );
+    }
+
+    pub fn redeem_promo(env: Env, user: Address, code_preimage: Bytes) {
+        user.require_auth();
+        
+        if Self::catalog(&env).get_user(&user).is_none() {
+            panic!("User not registered");
+        }
+        
+        Self::require_promos(&env);
+        
+        let code_hash: BytesN<32> = env.crypto().sha256(&code_preimage).into();
+        
+        let mut promo: PromoCode = env.storage().instance()
+            .get(&PromoKey::Codes(code_hash.clone()))
+            .unwrap_or_else(|| panic!("Invalid promo code"));
+        
+        if env.ledger().timestamp() >= promo.expires_at {
+            panic!("Promo code expired");
+        }
+        
+        if promo.uses_remaining == 0 {
+            panic!("Promo code fully redeemed");
+        }
+        
+        if env.storage().instance().has(&PromoKey::Redeemed(code_hash.clone(), user.clone())) {
+            panic!("Promo code already redeemed");
+        }
+        
+        promo.uses_remaining -= 1;
+        env.storage().instance().set(&PromoKey::Codes(code_hash.clone()), &promo);
+        env.storage().instance().set(&PromoKey::Redeemed(code_hash.clone(), user.clone()), &true);
+        
+        match promo.reward {
+            PromoReward::Requests(count) => {
+                Self::hub(&env).credit_requests(&env.current_contract_address(), &user, &count);
+            }
+            PromoReward::Credit(amount) => {
+                Self::credit_on_hub(&env, &env.current_contract_address(), &user, amount);
+            }
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "promo_redeemed"), EVENT_SCHEMA_VERSION, code_hash),
+            user
+        );
+    }
+
+    // Once a credit promo expires, the admin takes back what was escrowed for the
+    // uses nobody redeemed
+    pub fn reclaim_promo(env: Env, code_hash: BytesN<32>) -> i128 {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        
+        let mut promo: PromoCode = env.storage().instance()
+            .get(&PromoKey::Codes(code_hash.clone()))
+            .unwrap_or_else(|| panic!("Invalid promo code"));
+        
+        if env.ledger().timestamp() < promo.expires_at {
+            panic!("Promo code still active");
+        }
+        
+        let amount = match promo.reward {
+            PromoReward::Credit(amount) => amount * promo.uses_remaining as i128,
+            PromoReward::Requests(_) => 0,
+        };
+        if amount == 0 {
+            panic!("Nothing to reclaim");
+        }
+        
+        promo.uses_remaining = 0;
+        env.storage().instance().set(&PromoKey::Codes(code_hash.clone()), &promo);
+        token::Client::new(&env, &Self::default_token(&env)).transfer(
+            &env.current_contract_address(),
+            &admin,
+            &amount,
+        );
+        
+        env.events().publish(
+            (Symbol::new(&env, "promo_reclaimed"), EVENT_SCHEMA_VERSION, code_hash),
+            amount
+        );
+        
+        amount
+    }
+
+    // Moves tokens from the giver into a credit on the hub the recipient spends on
+    // their own requests
+    pub fn gift_credit(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        
+        require_active_user(&env, &from);
+        
+        if Self::catalog(&env).get_user(&to).is_none() {
+            panic!("User not registered");
+        }
+        
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+        
+        Self::credit_on_hub(&env, &from, &to, amount);
+        
+        env.events().publish(
+            (Symbol::new(&env, "credit_gifted"), EVENT_SCHEMA_VERSION, from, to),
+            amount
+        );
+    }
+
+    pub fn get_promo(env: Env, code_hash: BytesN<32>) -> Option<PromoCode> {
+        env.storage().instance().get(&PromoKey::Codes(code_hash))
+    }
+
+    // Crowdfunding
+    // `backer_share` is the percentage of the future track's royalties set aside
+    // for backers, divided between them in proportion to what they pledged
+    pub fn open_campaign(env: Env, artist: Address, goal: i128, deadline: u64, backer_share: u32) -> u32 {
+        artist.require_auth();
+        
+        if Self::catalog(&env).get_artist(&artist).is_none() {
+            panic!("Not registered as artist");
+        }
+        
+        require_active_user(&env, &artist);
+        
+        if goal <= 0 || deadline <= env.ledger().timestamp() {
+            panic!("Invalid campaign terms");
+        }
+        
+        if backer_share == 0 || backer_share >= 100 {
+            panic!("Backer share must be between 1 and 99");
+        }
+        
+        let campaign_id: u32 = env.storage().instance().get(&CampaignKey::CampaignCounter).unwrap_or(0) + 1;
+        env.storage().instance().set(&CampaignKey::CampaignCounter, &campaign_id);
+        
+        let campaign = Campaign {
+            campaign_id,
+            artist: artist.clone(),
+            goal,
+            raised: 0,
+            deadline,
+            backer_share,
+            status: CampaignStatus::Open,
+        };
+        env.storage().instance().set(&CampaignKey::Campaigns(campaign_id), &campaign);
+        
+        env.events().publish(
+            (Symbol::new(&env, "campaign_opened"), EVENT_SCHEMA_VERSION, artist),
+            (campaign_id, goal, deadline)
+        );
+        
+        campaign_id
+    }
+
+    pub fn pledge(env: Env, backer: Address, campaign_id: u32, amount: i128) {
+        backer.require_auth();
+        
+        require_active_user(&env, &backer);
+        
+        let mut campaign = Self::get_campaign(env.clone(), campaign_id)
+            .unwrap_or_else(|| panic!("Campaign not found"));
+        
+        if campaign.status != CampaignStatus::Open || env.ledger().timestamp() >= campaign.deadline {
+            panic!("Campaign closed");
+        }
+        
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+        
+        let pledged = Self::get_pledge(env.clone(), campaign_id, backer.clone());
+        if pledged == 0 {
+            let mut backers: Vec<Address> = env.storage().instance()
+                .get(&CampaignKey::Backers(campaign_id))
+                .unwrap_or(Vec::new(&env));
+            if backers.len() >= MAX_CAMPAIGN_BACKERS {
+                panic!("Campaign has too many backers");
+            }
+            backers.push_back(backer.clone());
+            env.storage().instance().set(&CampaignKey::Backers(campaign_id), &backers);
+        }
+        
+        let token_address: Address = Self::default_token(&env);
+        token::Client::new(&env, &token_address).transfer(
+            &backer,
+            &env.current_contract_address(),
+            &amount,
+        );
+        
+        env.storage().instance().set(&CampaignKey::Pledges(campaign_id, backer.clone()), &(pledged + amount));
+        campaign.raised += amount;
+        env.storage().instance().set(&CampaignKey::Campaigns(campaign_id), &campaign);
+        
+        env.events().publish(
+            (Symbol::new(&env, "campaign_pledged"), EVENT_SCHEMA_VERSION, campaign_id, backer),
+            amount
+        );
+    }
+
+    // Mints the funded track with backers written into its royalty split and
+    // releases the pledged funds to the artist. Rounding dust and any backer whose
+    // share rounds to zero fall to the artist. Backers bought their share, so they
+    // are never asked to co-sign the track.
+    pub fn mint_campaign_track(
+        env: Env,
+        campaign_id: u32,
+        title: String,
+        base_price: i128,
+        licenses: u32,
+        metadata_uri: String,
+    ) -> BytesN<32> {
+        let mut campaign = Self::get_campaign(env.clone(), campaign_id)
+            .unwrap_or_else(|| panic!("Campaign not found"));
+        campaign.artist.require_auth();
+        
+        if campaign.status != CampaignStatus::Open {
+            panic!("Campaign already funded");
+        }
+        
+        if campaign.raised < campaign.goal {
+            panic!("Campaign goal not reached");
+        }
+        
+        if env.ledger().timestamp() >= campaign.deadline + CAMPAIGN_MINT_WINDOW {
+            panic!("Campaign mint window closed");
+        }
+        
+        let backers: Vec<Address> = env.storage().instance()
+            .get(&CampaignKey::Backers(campaign_id))
+            .unwrap_or(Vec::new(&env));
+        
+        let mut royalty_split: Vec<(Address, u32)> = Vec::new(&env);
+        let mut backer_total: u32 = 0;
+        for backer in backers.iter() {
+            let pledged = Self::get_pledge(env.clone(), campaign_id, backer.clone());
+            let share = (pledged * campaign.backer_share as i128 / campaign.raised) as u32;
+            if share > 0 {
+                royalty_split.push_back((backer, share));
+                backer_total += share;
+            }
+        }
+        royalty_split.push_front((campaign.artist.clone(), 100 - backer_total));
+        
+        let params = TrackMintParams {
+            title,
+            base_price,
+            licenses,
+            metadata_uri,
+            collaborators: Vec::new(&env),
+            royalty_split,
+        };
+        let links = links(&env);
+        // The only names on the split besides the artist are backers
+        let track_id = CatalogClient::new(&env, &links.catalog)
+            .mint_for(&env.current_contract_address(), &campaign.artist, &params, &false);
+        
+        token::Client::new(&env, &Self::default_token(&env))
+            .transfer(&env.current_contract_address(), &links.payouts, &campaign.raised);
+        PayoutsClient::new(&env, &links.payouts)
+            .collect_artist_payment(&env.current_contract_address(), &campaign.artist, &campaign.raised);
+        
+        campaign.status = CampaignStatus::Funded;
+        env.storage().instance().set(&CampaignKey::Campaigns(campaign_id), &campaign);
+        env.storage().instance().set(&CampaignKey::Track(campaign_id), &track_id);
+        
+        env.events().publish(
+            (Symbol::new(&env, "campaign_funded"), EVENT_SCHEMA_VERSION, campaign_id),
+            (track_id.clone(), campaign.raised)
+        );
+        
+        track_id
+    }
+
+    // Pledges come back if the goal was missed, or if the artist never minted a
+    // funded campaign within the mint window
+    pub fn refund_pledge(env: Env, backer: Address, campaign_id: u32) -> i128 {
+        backer.require_auth();
+        
+        let campaign = Self::get_campaign(env.clone(), campaign_id)
+            .unwrap_or_else(|| panic!("Campaign not found"));
+        
+        let now = env.ledger().timestamp();
+        let failed = campaign.status == CampaignStatus::Open
+            && now >= campaign.deadline
+            && (campaign.raised < campaign.goal || now >= campaign.deadline + CAMPAIGN_MINT_WINDOW);
+        if !failed {
+            panic!("Campaign has not failed");
+        }
+        
+        let pledged = Self::get_pledge(env.clone(), campaign_id, backer.clone());
+        if pledged == 0 {
+            panic!("Nothing to refund");
+        }
+        env.storage().instance().remove(&CampaignKey::Pledges(campaign_id, backer.clone()));
+        
+        let token_address: Address = Self::default_token(&env);
+        token::Client::new(&env, &token_address).transfer(
+            &env.current_contract_address(),
+            &backer,
+            &pledged,
+        );
+        
+        env.events().publish(
+            (Symbol::new(&env, "pledge_refunded"), EVENT_SCHEMA_VERSION, campaign_id, backer),
+            pledged
+        );
+        
+        pledged
+    }
+
+    pub fn get_campaign(env: Env, campaign_id: u32) -> Option<Campaign> {
+        env.storage().instance().get(&CampaignKey::Campaigns(campaign_id))
+    }
+
+    pub fn get_campaign_track(env: Env, campaign_id: u32) -> Option<BytesN<32>> {
+        env.storage().instance().get(&CampaignKey::Track(campaign_id))
+    }
+
+    pub fn get_pledge(env: Env, campaign_id: u32, backer: Address) -> i128 {
+        env.storage().instance().get(&CampaignKey::Pledges(campaign_id, backer)).unwrap_or(0)
+    }
+
+    // Devices
+    // Venue hardware gets its own revocable key per table instead of sharing the
+    // owner's, and can only act on that table
+    pub fn register_device(env: Env, owner: Address, table_id: BytesN<32>, device: Address, label: String) {
+        owner.require_auth();
+        
+        let table: JukeboxTable = Self::hub(&env).get_table(&table_id).unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        if env.storage().instance().has(&DeviceKey::Devices(device.clone())) {
+            panic!("Device already registered");
+        }
+        
+        env.storage().instance().set(&DeviceKey::Devices(device.clone()), &Device {
+            table_id: table_id.clone(),
+            label,
+            registered_at: env.ledger().timestamp(),
+        });
+        
+        let mut devices = Self::get_table_devices(env.clone(), table_id.clone());
+        devices.push_back(device.clone());
+        env.storage().instance().set(&DeviceKey::TableDevices(table_id.clone()), &devices);
+        
+        env.events().publish(
+            (Symbol::new(&env, "device_registered"), EVENT_SCHEMA_VERSION, table_id),
+            device
+        );
+    }
+
+    pub fn revoke_device(env: Env, owner: Address, device: Address) {
+        owner.require_auth();
+        
+        let registered: Device = env.storage().instance()
+            .get(&DeviceKey::Devices(device.clone()))
+            .unwrap_or_else(|| panic!("Device not found"));
+        let table: JukeboxTable = Self::hub(&env).get_table(&registered.table_id).unwrap();
+        
+        if table.owner != owner {
+            panic!("Not table owner");
+        }
+        
+        env.storage().instance().remove(&DeviceKey::Devices(device.clone()));
+        
+        let mut devices = Self::get_table_devices(env.clone(), registered.table_id.clone());
+        if let Some(index) = devices.first_index_of(&device) {
+            devices.remove(index);
+        }
+        env.storage().instance().set(&DeviceKey::TableDevices(registered.table_id.clone()), &devices);
+        
+        env.events().publish(
+            (Symbol::new(&env, "device_revoked"), EVENT_SCHEMA_VERSION, registered.table_id),
+            device
+        );
+    }
+
+    pub fn get_device(env: Env, device: Address) -> Option<Device> {
+        env.storage().instance().get(&DeviceKey::Devices(device))
+    }
+
+    pub fn get_table_devices(env: Env, table_id: BytesN<32>) -> Vec<Address> {
+        env.storage().instance()
+            .get(&DeviceKey::TableDevices(table_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // The device reports the current track finished and moves the queue on
+    pub fn device_advance_queue(env: Env, device: Address) -> Option<BytesN<32>> {
+        let table_id = Self::require_device(&env, &device);
+        Self::hub(&env).advance_for(&env.current_contract_address(), &table_id)
+    }
+
+    // The device attests the member is physically present, e.g. after an NFC tap
+    pub fn device_check_in(env: Env, device: Address, user: Address, session_id: u32) {
+        let table_id = Self::require_device(&env, &device);
+        CommunityClient::new(&env, &links(&env).community)
+            .check_in_for(&env.current_contract_address(), &user, &table_id, &session_id);
+    }
+
+    fn require_device(env: &Env, device: &Address) -> BytesN<32> {
+        device.require_auth();
+        
+        let registered: Device = env.storage().instance()
+            .get(&DeviceKey::Devices(device.clone()))
+            .unwrap_or_else(|| panic!("Device not registered"));
+        registered.table_id
+    }
+
+    // Swaps all of `max_in` through the router into the default token, then pays for
+    // the request as usual; output beyond the price stays with the requester
+    pub fn request_track_with_swap(
+        env: Env,
+        requester: Address,
+        in_token: Address,
+        max_in: i128,
+        track_id: BytesN<32>,
+        table_id: BytesN<32>,
+    ) -> BytesN<32> {
+        requester.require_auth();
+        
+        let links = links(&env);
+        let router = PayoutsClient::new(&env, &links.payouts)
+            .get_swap_router()
+            .unwrap_or_else(|| panic!("No swap router configured"));
+        
+        let price = HubClient::new(&env, &links.hub).get_request_price(&track_id, &table_id);
+        
+        let settlement_token = Self::default_token(&env);
+        let in_client = token::Client::new(&env, &in_token);
+        in_client.transfer(&requester, &env.current_contract_address(), &max_in);
+        in_client.approve(
+            &env.current_contract_address(),
+            &router,
+            &max_in,
+            &env.ledger().sequence(),
+        );
+        
+        let amount_out = SwapRouterClient::new(&env, &router).swap_exact_in(
+            &env.current_contract_address(),
+            &in_token,
+            &settlement_token,
+            &max_in,
+            &price,
+            &requester,
+        );
+        
+        if amount_out < price {
+            panic!("Slippage bound exceeded");
+        }
+        
+        Self::place_request(&env, &requester, &requester, &track_id, &table_id, 0)
+    }
+
+    // Unlike a gift, the sponsor needn't be a registered user, so brands can pick
+    // up the tab. The beneficiary gets the reputation and receipt as requester.
+    pub fn sponsor_request(
+        env: Env,
+        sponsor: Address,
+        beneficiary: Address,
+        track_id: BytesN<32>,
+        table_id: BytesN<32>,
+    ) -> BytesN<32> {
+        sponsor.require_auth();
+        
+        let request_id = Self::place_request(&env, &sponsor, &beneficiary, &track_id, &table_id, 0);
+        
+        let amount_paid = Self::hub(&env).get_request(&request_id).unwrap().amount_paid;
+        env.storage().instance().set(&SponsorKey::Sponsors(request_id.clone()), &sponsor);
+        let spend = Self::get_sponsor_spend(env.clone(), sponsor.clone()) + amount_paid;
+        env.storage().instance().set(&SponsorKey::Spend(sponsor.clone()), &spend);
+        
+        env.events().publish(
+            (Symbol::new(&env, "request_sponsored"), EVENT_SCHEMA_VERSION, sponsor, beneficiary),
+            (request_id.clone(), amount_paid)
+        );
+        
+        request_id
+    }
+
+    pub fn get_request_sponsor(env: Env, request_id: BytesN<32>) -> Option<Address> {
+        env.storage().instance().get(&SponsorKey::Sponsors(request_id))
+    }
+
+    pub fn get_sponsor_spend(env: Env, sponsor: Address) -> i128 {
+        env.storage().instance().get(&SponsorKey::Spend(sponsor)).unwrap_or(0)
+    }
+
+    // Albums
+    pub fn mint_album(
+        env: Env,
+        artist: Address,
+        title: String,
+        metadata_uri: String,
+        discount_bps: u32,
+        tracks: Vec<TrackMintParams>,
+    ) -> Vec<BytesN<32>> {
+        artist.require_auth();
+        
+        validation::require_name(&env, &title);
+        validation::require_uri(&env, &metadata_uri);
+        
+        if Self::catalog(&env).get_artist(&artist).is_none() {
+            panic!("Not registered as artist");
+        }
+        
+        if tracks.is_empty() || tracks.len() > 25 {
+            panic!("Album needs between 1 and 25 tracks");
+        }
+        
+        if discount_bps > 10000 {
+            panic!("Discount too high");
+        }
+        
+        let catalog = Self::catalog(&env);
+        let mut track_ids = Vec::new(&env);
+        for params in tracks.iter() {
+            track_ids.push_back(catalog.mint_for(&env.current_contract_address(), &artist, &params, &true));
+        }
+        
+        let mut album_counter: u32 = env.storage().instance()
+            .get(&AlbumKey::AlbumIdCounter)
+            .unwrap_or(0);
+        album_counter += 1;
+        
+        let mut album_id_bytes = Bytes::from_slice(&env, b"album_");
+        album_id_bytes.extend_from_array(&album_counter.to_be_bytes());
+        let album_id: BytesN<32> = env.crypto().sha256(&album_id_bytes).into();
+        
+        for track_id in track_ids.iter() {
+            env.storage().instance().set(&AlbumKey::TrackAlbum(track_id), &album_id);
+        }
+        
+        let album = Album {
+            album_id: album_id.clone(),
+            artist,
+            title,
+            metadata_uri,
+            track_ids: track_ids.clone(),
+            discount_bps,
+            created_at: env.ledger().timestamp(),
+        };
+        
+        env.storage().instance().set(&AlbumKey::Albums(album_id.clone()), &album);
+        env.storage().instance().set(&AlbumKey::AlbumIdCounter, &album_counter);
+        
+        env.events().publish(
+            (Symbol::new(&env, "album_minted"), EVENT_SCHEMA_VERSION, album_id),
+            track_ids.len()
+        );
+        
+        track_ids
+    }
+
+    // Queues the whole album at the table, each track at the album's bundle discount
+    pub fn request_album(
+        env: Env,
+        requester: Address,
+        album_id: BytesN<32>,
+        table_id: BytesN<32>,
+    ) -> Vec<BytesN<32>> {
+        requester.require_auth();
+        
+        let album: Album = env.storage().instance()
+            .get(&AlbumKey::Albums(album_id))
+            .unwrap_or_else(|| panic!("Album not found"));
+        
+        let mut request_ids = Vec::new(&env);
+        for track_id in album.track_ids.iter() {
+            let request_id = Self::place_request(&env, &requester, &requester, &track_id, &table_id, album.discount_bps);
+            request_ids.push_back(request_id);
+        }
+        
+        request_ids
+    }
+
+    pub fn get_album(env: Env, album_id: BytesN<32>) -> Option<Album> {
+        env.storage().instance().get(&AlbumKey::Albums(album_id))
+    }
+
+    // UPC-A (12 digits) or EAN-13; passing None clears it
+    pub fn set_album_upc(env: Env, artist: Address, album_id: BytesN<32>, upc: Option<String>) {
+        artist.require_auth();
+        
+        let album: Album = env.storage().instance()
+            .get(&AlbumKey::Albums(album_id.clone()))
+            .unwrap_or_else(|| panic!("Album not found"));
+        
+        if album.artist != artist {
+            panic!("Not album artist");
+        }
+        
+        let current: Option<String> = env.storage().instance().get(&AlbumKey::Upc(album_id.clone()));
+        if let Some(current) = current {
+            env.storage().instance().remove(&AlbumKey::UpcToAlbum(current));
+            env.storage().instance().remove(&AlbumKey::Upc(album_id.clone()));
+        }
+        if let Some(upc) = upc {
+            if upc.len() != 12 && upc.len() != 13 {
+                panic!("Invalid UPC");
+            }
+            if env.storage().instance().has(&AlbumKey::UpcToAlbum(upc.clone())) {
+                panic!("UPC already registered");
+            }
+            env.storage().instance().set(&AlbumKey::Upc(album_id.clone()), &upc);
+            env.storage().instance().set(&AlbumKey::UpcToAlbum(upc), &album_id);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "album_upc_set"), EVENT_SCHEMA_VERSION, album_id.clone()),
+            Self::get_album_upc(env.clone(), album_id)
+        );
+    }
+
+    pub fn get_album_upc(env: Env, album_id: BytesN<32>) -> Option<String> {
+        env.storage().instance().get(&AlbumKey::Upc(album_id))
+    }
+
+    pub fn get_album_by_upc(env: Env, upc: String) -> Option<BytesN<32>> {
+        env.storage().instance().get(&AlbumKey::UpcToAlbum(upc))
+    }
+
+    pub fn get_track_album(env: Env, track_id: BytesN<32>) -> Option<BytesN<32>> {
+        env.storage().instance().get(&AlbumKey::TrackAlbum(track_id))
+    }
+
+    // Playlists
+    pub fn create_playlist(env: Env, owner: Address, name: String, track_ids: Vec<BytesN<32>>) -> BytesN<32> {
+        owner.require_auth();
+        
+        validation::require_name(&env, &name);
+        
+        if Self::catalog(&env).get_user(&owner).is_none() {
+            panic!("User not registered");
+        }
+        
+        Self::validate_playlist_tracks(&env, &track_ids);
+        
+        let mut playlist_counter: u32 = env.storage().instance()
+            .get(&PlaylistKey::PlaylistIdCounter)
+            .unwrap_or(0);
+        playlist_counter += 1;
+        
+        let mut playlist_id_bytes = Bytes::from_slice(&env, b"playlist_");
+        playlist_id_bytes.extend_from_array(&playlist_counter.to_be_bytes());
+        let playlist_id: BytesN<32> = env.crypto().sha256(&playlist_id_bytes).into();
+        
+        let now = env.ledger().timestamp();
+        let playlist = Playlist {
+            playlist_id: playlist_id.clone(),
+            owner: owner.clone(),
+            name,
+            track_ids,
+            created_at: now,
+            updated_at: now,
+        };
+        
+        env.storage().instance().set(&PlaylistKey::Playlists(playlist_id.clone()), &playlist);
+        env.storage().instance().set(&PlaylistKey::PlaylistIdCounter, &playlist_counter);
+        
+        env.events().publish(
+            (Symbol::new(&env, "playlist_created"), EVENT_SCHEMA_VERSION, playlist_id.clone()),
+            owner
+        );
+        
+        playlist_id
+    }
+
+    pub fn update_playlist(
+        env: Env,
+        owner: Address,
+        playlist_id: BytesN<32>,
+        name: String,
+        track_ids: Vec<BytesN<32>>,
+    ) {
+        owner.require_auth();
+        
+        validation::require_name(&env, &name);
+        
+        let mut playlist: Playlist = env.storage().instance()
+            .get(&PlaylistKey::Playlists(playlist_id.clone()))
+            .unwrap_or_else(|| panic!("Playlist not found"));
+        
+        if playlist.owner != owner {
+            panic!("Not playlist owner");
+        }
+        
+        Self::validate_playlist_tracks(&env, &track_ids);
+        
+        playlist.name = name;
+        playlist.track_ids = track_ids;
+        playlist.updated_at = env.ledger().timestamp();
+        env.storage().instance().set(&PlaylistKey::Playlists(playlist_id.clone()), &playlist);
+        
+        env.events().publish(
+            (Symbol::new(&env, "playlist_updated"), EVENT_SCHEMA_VERSION, playlist_id),
+            (playlist.name, playlist.track_ids)
+        );
+    }
+
+    fn validate_playlist_tracks(env: &Env, track_ids: &Vec<BytesN<32>>) {
+        if track_ids.is_empty() || track_ids.len() > 50 {
+            panic!("Playlist needs between 1 and 50 tracks");
+        }
+        
+        let catalog = Self::catalog(env);
+        for track_id in track_ids.iter() {
+            if catalog.get_track(&track_id).is_none() {
+                panic!("Track not found");
+            }
+        }
+    }
+
+    // Queues every playlist track at the table; fails as a whole if any request can't be placed
+    pub fn request_playlist(
+        env: Env,
+        user: Address,
+        playlist_id: BytesN<32>,
+        table_id: BytesN<32>,
+    ) -> Vec<BytesN<32>> {
+        user.require_auth();
+        
+        let playlist: Playlist = env.storage().instance()
+            .get(&PlaylistKey::Playlists(playlist_id))
+            .unwrap_or_else(|| panic!("Playlist not found"));
+        
+        let hub = Self::hub(&env);
+        let profile = hub.get_profile();
+        if profile.max_queue_length > 0 {
+            let queued = hub.get_queue(&table_id).len();
+            if queued + playlist.track_ids.len() > profile.max_queue_length {
+                panic!("Queue is full");
+            }
+        }
+        
+        let mut request_ids = Vec::new(&env);
+        for track_id in playlist.track_ids.iter() {
+            let request_id = Self::place_request(&env, &user, &user, &track_id, &table_id, 0);
+            request_ids.push_back(request_id);
+        }
+        
+        request_ids
+    }
+
+    pub fn get_playlist(env: Env, playlist_id: BytesN<32>) -> Option<Playlist> {
+        env.storage().instance().get(&PlaylistKey::Playlists(playlist_id))
+    }
+
+    fn place_request(
+        env: &Env,
+        payer: &Address,
+        requester: &Address,
+        track_id: &BytesN<32>,
+        table_id: &BytesN<32>,
+        discount_bps: u32,
+    ) -> BytesN<32> {
+        Self::hub(env).place_request_for(&env.current_contract_address(), payer, requester, track_id, table_id, &discount_bps)
+    }
+
+    fn default_token(env: &Env) -> Address {
+        env.storage().instance().get(&DataKey::TokenStellar).unwrap()
+    }
+
+    // Credit is spent on the hub, so the funds move there with it
+    fn credit_on_hub(env: &Env, from: &Address, user: &Address, amount: i128) {
+        let hub = links(env).hub;
+        token::Client::new(env, &Self::default_token(env)).transfer(from, &hub, &amount);
+        HubClient::new(env, &hub).credit_token(&env.current_contract_address(), user, &amount);
+    }
+
+    fn require_promos(env: &Env) {
+        if Self::hub(env).get_profile().features & FEATURE_PROMOS == 0 {
+            panic!("Feature not enabled on this deployment");
+        }
+    }
+
+    fn catalog(env: &Env) -> CatalogClient<'_> {
+        CatalogClient::new(env, &links(env).catalog)
+    }
+
+    fn hub(env: &Env) -> HubClient<'_> {
+        HubClient::new(env, &links(env).hub)
+    }
+}
@@ -0,0 +1,223 @@
+use crate::*;
+use juke_common::FEATURE_PROMOS;
+use juke_catalog::{JukeCatalog, JukeCatalogClient};
+use juke_community::{JukeCommunity, JukeCommunityClient};
+use juke_governance::{JukeGovernance, JukeGovernanceClient};
+use juke_market::{JukeMarket, JukeMarketClient};
+use juke_payouts::{JukePayouts, JukePayoutsClient};
+use juke_venue::{JukeVenue, JukeVenueClient};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::{vec, Bytes};
+
+pub const FIXTURE_TIMESTAMP: u64 = 1_700_000_000;
+pub const FIXTURE_ARTISTS: u32 = 3;
+pub const FIXTURE_TRACKS: u32 = 20;
+pub const FIXTURE_LISTENERS: u32 = 4;
+pub const FIXTURE_TABLES: u32 = 2;
+pub const FIXTURE_REQUESTS_PER_TABLE: u32 = 3;
+pub const FIXTURE_BALANCE: i128 = 1_000_000;
+pub const FIXTURE_TRACK_PRICE: i128 = 1_000;
+pub const FIXTURE_PLATFORM_FEE: u32 = 500;
+pub const FIXTURE_TABLE_CATEGORY: &str = "bar";
+
+pub struct Fixture {
+    pub env: Env,
+    pub links: Links,
+    pub admin: Address,
+    pub token: Address,
+    pub artists: Vec<Address>,
+    pub listeners: Vec<Address>,
+    pub tracks: Vec<BytesN<32>>,
+    pub tables: Vec<BytesN<32>>,
+    pub requests: Vec<BytesN<32>>,
+}
+
+impl Fixture {
+    pub fn client(&self) -> Clients<'_> {
+        Clients::new(&self.env, &self.links)
+    }
+}
+
+// One client per contract of a deployment
+pub struct Clients<'a> {
+    pub hub: MetaJukeClient<'a>,
+    pub catalog: JukeCatalogClient<'a>,
+    pub payouts: JukePayoutsClient<'a>,
+    pub market: JukeMarketClient<'a>,
+    pub community: JukeCommunityClient<'a>,
+    pub venue: JukeVenueClient<'a>,
+    pub governance: JukeGovernanceClient<'a>,
+}
+
+impl Clients<'_> {
+    pub fn new(env: &Env, links: &Links) -> Self {
+        Clients {
+            hub: MetaJukeClient::new(env, &links.hub),
+            catalog: JukeCatalogClient::new(env, &links.catalog),
+            payouts: JukePayoutsClient::new(env, &links.payouts),
+            market: JukeMarketClient::new(env, &links.market),
+            community: JukeCommunityClient::new(env, &links.community),
+            venue: JukeVenueClient::new(env, &links.venue),
+            governance: JukeGovernanceClient::new(env, &links.governance),
+        }
+    }
+}
+
+pub fn default_profile(env: &Env) -> DeploymentProfile {
+    DeploymentProfile {
+        branding: Symbol::new(env, "MetaJuke"),
+        default_free_requests: 0,
+        default_skip_fee: 0,
+        max_queue_length: 0,
+        features: FEATURE_FREE_REQUESTS | FEATURE_SKIP_FEES | FEATURE_PROMOS | FEATURE_HAPPY_HOUR,
+    }
+}
+
+// Registers and links the seven contracts of a deployment, each initialized with
+// the same admin and default token
+pub fn deploy(env: &Env, admin: &Address, token: &Address) -> Links {
+    let links = Links {
+        catalog: env.register(JukeCatalog, ()),
+        hub: env.register(MetaJuke, ()),
+        payouts: env.register(JukePayouts, ()),
+        market: env.register(JukeMarket, ()),
+        community: env.register(JukeCommunity, ()),
+        venue: env.register(JukeVenue, ()),
+        governance: env.register(JukeGovernance, ()),
+    };
+
+    let client = Clients::new(env, &links);
+    client.hub.initialize(admin, token, &default_profile(env), &links);
+    client.catalog.initialize(admin, token, &links);
+    client.payouts.initialize(admin, token, &FIXTURE_PLATFORM_FEE, &links);
+    client.market.initialize(admin, token, &links);
+    client.community.initialize(admin, token, &links);
+    client.venue.initialize(admin, token, &links);
+    client.governance.initialize(admin, token, &links);
+
+    let wasm_hash = env.deployer().upload_contract_wasm(track_nft::WASM);
+    client.catalog.set_track_nft_wasm(&wasm_hash);
+
+    links
+}
+
+// Canonical populated state: 3 artists with 20 tracks between them, 4 listeners,
+// 2 tables every listener has joined, and a few paid requests queued on each table.
+pub fn setup() -> Fixture {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|ledger| ledger.timestamp = FIXTURE_TIMESTAMP);
+
+    let admin = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    let links = deploy(&env, &admin, &token);
+    let client = Clients::new(&env, &links);
+
+    let mut artists = Vec::new(&env);
+    for i in 0..FIXTURE_ARTISTS {
+        let artist = register_user(&env, &client);
+        client.catalog.register_artist(&artist, &String::from_str(&env, ARTIST_NAMES[i as usize]));
+        artists.push_back(artist);
+    }
+
+    let mut listeners = Vec::new(&env);
+    for _ in 0..FIXTURE_LISTENERS {
+        let listener = register_user(&env, &client);
+        StellarAssetClient::new(&env, &token).mint(&listener, &FIXTURE_BALANCE);
+        listeners.push_back(listener);
+    }
+
+    let mut tracks = Vec::new(&env);
+    for i in 0..FIXTURE_TRACKS {
+        let artist = artists.get(i % FIXTURE_ARTISTS).unwrap();
+        let track_id = client.catalog.mint_track(
+            &artist,
+            &String::from_str(&env, "Fixture Track"),
+            &FIXTURE_TRACK_PRICE,
+            &100,
+            &String::from_str(&env, "ipfs://fixture-track"),
+            &Vec::new(&env),
+            &vec![&env, (artist.clone(), 100u32)],
+            &None,
+            &None,
+            &Vec::new(&env),
+        );
+        tracks.push_back(track_id);
+    }
+
+    let mut tables = Vec::new(&env);
+    for i in 0..FIXTURE_TABLES {
+        let owner = artists.get(i).unwrap();
+        let table_id = fixture_id(&env, b"fixture_table", i);
+        let table = JukeboxTable {
+            table_id: table_id.clone(),
+            name: String::from_str(&env, "Fixture Table"),
+            owner,
+            skip_threshold: 2,
+            price_multiplier: 10000,
+            member_count: 0,
+            is_active: true,
+            free_requests_per_day: 0,
+            skip_fee: 0,
+            skip_fee_requester_bps: 0,
+            category: Symbol::new(&env, FIXTURE_TABLE_CATEGORY),
+            tags: Vec::new(&env),
+        };
+        env.as_contract(&links.hub, || {
+            env.storage().instance().set(&DataKey::Tables(table_id.clone()), &table);
+            MetaJuke::index_table_category(&env, &table.category, &table_id, true);
+        });
+        for listener in listeners.iter() {
+            client.hub.join_table(&listener, &table_id);
+        }
+        tables.push_back(table_id);
+    }
+    env.as_contract(&links.hub, || {
+        env.storage().instance().set(&DataKey::TableIdCounter, &FIXTURE_TABLES);
+    });
+
+    let mut requests = Vec::new(&env);
+    for (t, table_id) in tables.iter().enumerate() {
+        for r in 0..FIXTURE_REQUESTS_PER_TABLE {
+            let n = t as u32 * FIXTURE_REQUESTS_PER_TABLE + r;
+            let requester = listeners.get(n % FIXTURE_LISTENERS).unwrap();
+            let track_id = tracks.get(n % FIXTURE_TRACKS).unwrap();
+            requests.push_back(client.hub.request_track(&requester, &track_id, &table_id));
+        }
+    }
+
+    env.cost_estimate().budget().reset_default();
+
+    Fixture {
+        env,
+        links,
+        admin,
+        token,
+        artists,
+        listeners,
+        tracks,
+        tables,
+        requests,
+    }
+}
+
+// Prebuilt trackNft contract. Rebuild it after changing that crate with
+// `cargo build -p track-nft --target wasm32v1-none --release` and copy the output here.
+mod track_nft {
+    soroban_sdk::contractimport!(file = "testdata/track_nft.wasm");
+}
+
+const ARTIST_NAMES: [&str; 3] = ["Aurora Lane", "Basslight", "Cobalt Choir"];
+
+pub fn fixture_id(env: &Env, tag: &[u8], n: u32) -> BytesN<32> {
+    let mut bytes = Bytes::from_slice(env, tag);
+    bytes.extend_from_array(&n.to_be_bytes());
+    env.crypto().sha256(&bytes).into()
+}
+
+fn register_user(env: &Env, client: &Clients) -> Address {
+    let user = Address::generate(env);
+    client.catalog.register_user(&user, &String::from_str(env, "ipfs://fixture-avatar"), &None);
+    user
+}
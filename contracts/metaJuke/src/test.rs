@@ -1,21 +1,3018 @@
 #![cfg(test)]
 
-use super::*;
-use soroban_sdk::{vec, Env, String};
+use crate::fixtures::{
+    self, Clients, FIXTURE_PLATFORM_FEE, FIXTURE_REQUESTS_PER_TABLE, FIXTURE_TABLES, FIXTURE_TRACKS, FIXTURE_TRACK_PRICE,
+};
+use crate::{token, DataKey, KeeperConfig, MetaJuke, MetaJukeClient, NowPlaying, Symbol, Vec};
+use juke_catalog::{ImportEntry, LegacyArtist, LegacyTrack, LegacyUser, OracleAsset, OracleConfig, PriceData, SlashingConfig};
+use juke_common::{
+    AuditTarget, DeploymentProfile, FeeClass, FeeTier, PriceCurve, TrackMintParams, TreasuryTarget, TtlPolicy, ValidationError, EVENT_SCHEMA_VERSION,
+    FEATURE_FREE_REQUESTS, FEATURE_HAPPY_HOUR, FEATURE_SKIP_FEES, PAUSE_REQUESTS, PAUSE_WITHDRAWALS,
+};
+use juke_community::{CompetitionMetric, LeaderboardKind, LeaderboardSubject, OutboxMessage, TableAdConfig};
+use juke_governance::{AdminAction, Council, ReportCategory, ReportStatus};
+use juke_market::{LicenseClass, LoyaltyConfig, SubscriptionTier, TourStop};
+use juke_payouts::{ArtistAwardConfig, FeeRouting, ReferralConfig};
+use juke_venue::{PromoReward, VoucherPayload};
+use soroban_sdk::testutils::storage::{Persistent as _, Temporary as _};
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{contract, contractimpl, vec, Address, Bytes, BytesN, Env, IntoVal, Map, String, TryFromVal};
+
+// Plays out everything queued on the table, settling each request's escrowed payment
+fn play_out(client: &MetaJukeClient, owner: &Address, table_id: &BytesN<32>) {
+    while client.advance_queue_public(owner, table_id).is_some() {}
+}
 
 #[test]
 fn test() {
     let env = Env::default();
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    let client = Clients::new(&env, &fixtures::deploy(&env, &admin, &token));
 
-    let words = client.hello(&String::from_str(&env, "Dev"));
-    assert_eq!(
-        words,
-        vec![
-            &env,
-            String::from_str(&env, "Hello"),
-            String::from_str(&env, "Dev"),
-        ]
+    let user = Address::generate(&env);
+    client.catalog.register_user(&user, &String::from_str(&env, "ipfs://dev"), &None);
+    assert!(client.catalog.get_user(&user).is_some());
+    assert_eq!(client.catalog.get_platform_stats(), (0, 0, 0));
+}
+
+#[test]
+fn fixture_state() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+
+    assert_eq!(client.catalog.get_total_tracks(), FIXTURE_TRACKS);
+    assert_eq!(client.catalog.get_platform_stats(), (FIXTURE_TRACKS, 2, 6));
+    let stats = client.catalog.get_extended_stats();
+    assert_eq!(stats.total_users, fixtures::FIXTURE_ARTISTS + fixtures::FIXTURE_LISTENERS);
+    assert_eq!(stats.total_artists, fixtures::FIXTURE_ARTISTS);
+
+    let bars = client.hub.list_tables_by_category(&Symbol::new(&fixture.env, fixtures::FIXTURE_TABLE_CATEGORY), &0, &10);
+    assert_eq!(bars, fixture.tables);
+
+    for table_id in fixture.tables.iter() {
+        assert_eq!(client.hub.get_queue(&table_id).len(), FIXTURE_REQUESTS_PER_TABLE);
+        assert_eq!(client.hub.get_table_member_count(&table_id), fixture.listeners.len());
+    }
+
+    for artist in fixture.artists.iter() {
+        assert!(client.catalog.get_artist(&artist).is_some());
+    }
+    assert_eq!(client.catalog.list_artists(&0, &10), fixture.artists);
+    assert_eq!(client.catalog.list_artists(&2, &10), vec![&fixture.env, fixture.artists.get(2).unwrap()]);
+    assert!(client.catalog.list_verified_artists(&0, &10).is_empty());
+
+    let missing = fixtures::fixture_id(&fixture.env, b"missing", 0);
+    let tracks = client.catalog.get_tracks(&vec![&fixture.env, fixture.tracks.get(0).unwrap(), missing]);
+    assert_eq!(tracks.get(0).unwrap().unwrap().track_id, fixture.tracks.get(0).unwrap());
+    assert!(tracks.get(1).unwrap().is_none());
+    assert_eq!(client.hub.get_tables(&fixture.tables).len(), FIXTURE_TABLES);
+    assert_eq!(client.catalog.get_users(&fixture.listeners).iter().filter(|u| u.is_some()).count(), 4);
+
+    let listener = fixture.listeners.get(0).unwrap();
+    let requests = client.community.get_user_requests(&listener, &0, &10);
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests.get(0).unwrap().request_id, fixture.requests.get(0).unwrap());
+    assert_eq!(client.community.get_user_request_count(&listener), 2);
+    assert_eq!(client.community.get_user_requests(&listener, &1, &10).len(), 1);
+    assert_eq!(client.community.get_user_spend(&listener), 2 * FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn advance_queue_plays_requests_in_order() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let table_id = fixture.tables.get(0).unwrap();
+    let owner = fixture.artists.get(0).unwrap();
+
+    let first = client.hub.get_queue(&table_id).get(0).unwrap();
+    assert_eq!(client.hub.advance_queue_public(&owner, &table_id), Some(first.clone()));
+    assert_eq!(client.hub.get_current_track(&table_id), Some(first.clone()));
+    assert_eq!(client.hub.get_queue(&table_id).len(), FIXTURE_REQUESTS_PER_TABLE - 1);
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    let history = client.community.get_play_history(&table_id, &0, &10);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.get(0).unwrap().track_id, first);
+    assert!(!history.get(0).unwrap().skipped);
+}
+
+#[test]
+fn dutch_auction_price_decays_until_bought() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let buyer = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+
+    client.catalog.set_feature_flag(&Symbol::new(env, "auctions"), &true, &Vec::new(env));
+    let auction_id = client.market.create_license_auction(&artist, &track_id, &1, &true, &10_000, &2_000, &500);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+    assert_eq!(client.market.get_auction_price(&auction_id), 5_000);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 100);
+    assert_eq!(client.market.buy_auction_license(&buyer, &auction_id), 2_000);
+
+    assert_eq!(client.market.get_exclusive_holder(&track_id), Some(buyer.clone()));
+    assert_eq!(client.catalog.get_track(&track_id).unwrap().licenses_remaining, 0);
+    assert!(client.market.try_buy_auction_license(&buyer, &auction_id).is_err());
+}
+
+#[test]
+fn exclusive_auction_winner_plays_on_won_licenses() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let winner = fixture.listeners.get(0).unwrap();
+    let other = fixture.listeners.get(1).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let token = token::Client::new(env, &fixture.token);
+
+    client.catalog.set_feature_flag(&Symbol::new(env, "auctions"), &true, &Vec::new(env));
+    let licenses = client.catalog.get_track(&track_id).unwrap().licenses_remaining;
+    let auction_id = client.market.create_license_auction(&artist, &track_id, &1, &true, &2_000, &2_000, &0);
+    client.market.buy_auction_license(&winner, &auction_id);
+    assert_eq!(client.catalog.get_track(&track_id).unwrap().licenses_remaining, 0);
+    assert_eq!(client.market.get_auction_licenses(&winner, &track_id), licenses);
+
+    // The open supply went to the auction, so the winner spends a won license
+    // and pays nothing more for the play
+    let balance = token.balance(&winner);
+    client.hub.request_track(&winner, &track_id, &table_id);
+    assert_eq!(token.balance(&winner), balance);
+    assert_eq!(client.market.get_auction_licenses(&winner, &track_id), licenses - 1);
+
+    assert!(client.hub.try_request_track(&other, &track_id, &table_id).is_err());
+}
+
+#[test]
+fn track_proof_verifies_on_another_deployment() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let track_id = fixture.tracks.get(0).unwrap();
+
+    let mut proof = client.market.export_track_proof(&track_id);
+    assert!(client.market.verify_track_proof(&proof));
+
+    let other = Clients::new(env, &fixtures::deploy(env, &fixture.admin, &fixture.token));
+    assert!(!other.market.verify_track_proof(&proof));
+    other.market.set_proof_source_trusted(&fixture.links.market, &true);
+    assert!(other.market.verify_track_proof(&proof));
+
+    proof.play_count += 1;
+    assert!(!other.market.verify_track_proof(&proof));
+}
+
+#[test]
+fn claim_all_pages_through_track_accruals() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let buyer = fixture.listeners.get(0).unwrap();
+    let token = token::Client::new(env, &fixture.token);
+
+    for track_id in [fixture.tracks.get(0).unwrap(), fixture.tracks.get(3).unwrap()] {
+        client.market.set_license_class(&artist, &track_id, &LicenseClass::Sync, &10_000, &5);
+        client.market.purchase_license(&buyer, &track_id, &LicenseClass::Sync);
+    }
+
+    assert_eq!(client.payouts.get_accrual_tracks(&artist).len(), 2);
+    let breakdown = client.payouts.get_artist_earnings_breakdown(&artist, &0, &2);
+    assert_eq!(breakdown.len(), 2);
+    assert_eq!(breakdown.get(1).unwrap().0, fixture.tracks.get(3).unwrap());
+    assert_eq!(client.payouts.get_track_earnings(&fixture.tracks.get(3).unwrap()).plays_paid, 1);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), 19_000);
+
+    assert_eq!(client.payouts.claim_all(&artist, &1), 9_500);
+    assert_eq!(client.payouts.get_accrual_tracks(&artist).len(), 1);
+    assert_eq!(client.payouts.claim_all(&artist, &10), 9_500);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), 0);
+    assert_eq!(token.balance(&artist), 19_000);
+}
+
+#[test]
+fn license_resale_accrues_royalty_to_the_artist() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let seller = fixture.listeners.get(0).unwrap();
+    let buyer = fixture.listeners.get(1).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+
+    client.market.set_resale_royalty(&1_000);
+    client.market.set_license_class(&artist, &track_id, &LicenseClass::Sync, &10_000, &5);
+    let license_id = client.market.purchase_license(&seller, &track_id, &LicenseClass::Sync);
+    let claimable = client.payouts.get_claimable_revenue(&artist);
+    let treasury = client.payouts.get_treasury_balance();
+    let plays_paid = client.payouts.get_track_earnings(&track_id).plays_paid;
+
+    client.market.list_license_for_sale(&seller, &license_id, &5_000);
+    client.market.buy_license(&buyer, &license_id);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), claimable + 500);
+    assert_eq!(client.payouts.get_treasury_balance(), treasury);
+    assert_eq!(client.payouts.get_track_earnings(&track_id).plays_paid, plays_paid);
+}
+
+#[test]
+fn exclusive_license_blocks_other_requesters() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let holder = fixture.listeners.get(0).unwrap();
+    let other = fixture.listeners.get(1).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    client.market.set_license_class(&artist, &track_id, &LicenseClass::Exclusive, &50_000, &1);
+    client.market.purchase_license(&holder, &track_id, &LicenseClass::Exclusive);
+    assert_eq!(client.market.get_exclusive_holder(&track_id), Some(holder.clone()));
+
+    assert!(client.hub.try_request_track(&other, &track_id, &table_id).is_err());
+    client.hub.request_track(&holder, &track_id, &table_id);
+}
+
+#[test]
+fn vested_share_unlocks_linearly_after_cliff() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let buyer = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+
+    client.payouts.set_vesting_schedule(&artist, &artist, &track_id, &5_000, &100, &1_000);
+    client.market.set_license_class(&artist, &track_id, &LicenseClass::Sync, &10_000, &5);
+    client.market.purchase_license(&buyer, &track_id, &LicenseClass::Sync);
+
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), 4_750);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 50);
+    assert!(client.payouts.try_claim_vested(&artist, &track_id).is_err());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 450);
+    assert_eq!(client.payouts.claim_vested(&artist, &track_id), 2_375);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 5_000);
+    assert_eq!(client.payouts.claim_vested(&artist, &track_id), 2_375);
+}
+
+#[test]
+fn council_proposal_executes_at_threshold() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let members = vec![env, Address::generate(env), Address::generate(env), Address::generate(env)];
+
+    client.governance.set_council(&Council { members: members.clone(), threshold: 2 });
+    assert!(client.governance.try_update_platform_fee(&100).is_err());
+
+    let proposal_id = client.governance.propose_admin_action(&members.get(0).unwrap(), &AdminAction::UpdatePlatformFee(100));
+    assert!(!client.governance.get_proposal(&proposal_id).unwrap().executed);
+    assert!(client.governance.try_approve_proposal(&Address::generate(env), &proposal_id).is_err());
+
+    client.governance.approve_proposal(&members.get(2).unwrap(), &proposal_id);
+    assert!(client.governance.get_proposal(&proposal_id).unwrap().executed);
+    assert!(client.governance.try_approve_proposal(&members.get(1).unwrap(), &proposal_id).is_err());
+}
+
+#[test]
+fn council_can_cancel_queued_changes() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let member = Address::generate(env);
+
+    client.governance.set_council(&Council { members: vec![env, member.clone()], threshold: 1 });
+    assert!(client.governance.try_set_timelock_delay(&0).is_err());
+
+    client.governance.propose_admin_action(&member, &AdminAction::SetTimelockDelay(0));
+    let change_id = 1;
+    assert!(client.governance.get_param_change(&change_id).is_some());
+    assert!(client.governance.try_cancel_param_change(&change_id).is_err());
+
+    client.governance.propose_admin_action(&member, &AdminAction::CancelParamChange(change_id));
+    assert!(client.governance.get_param_change(&change_id).is_none());
+}
+
+#[test]
+fn fee_change_waits_for_timelock() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+
+    let change_id = client.governance.update_platform_fee(&100);
+    assert!(client.governance.try_execute_param_change(&change_id).is_err());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += client.governance.get_timelock_delay());
+    client.governance.execute_param_change(&change_id);
+    assert!(client.governance.get_param_change(&change_id).is_none());
+
+    let cancelled = client.governance.update_platform_fee(&200);
+    client.governance.cancel_param_change(&cancelled);
+    assert!(client.governance.try_execute_param_change(&cancelled).is_err());
+}
+
+#[test]
+fn category_fee_override_replaces_platform_fee() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let class = FeeClass::Category(Symbol::new(env, fixtures::FIXTURE_TABLE_CATEGORY));
+
+    let change_id = client.governance.set_fee_override(&class, &Some(1_000));
+    env.ledger().with_mut(|ledger| ledger.timestamp += client.governance.get_timelock_delay());
+    client.governance.execute_param_change(&change_id);
+    assert_eq!(client.payouts.get_fee_override(&class), Some(1_000));
+
+    let owner = fixture.artists.get(0).unwrap();
+    play_out(&client.hub, &owner, &table_id);
+    let before = client.payouts.get_treasury_balance();
+    client.hub.request_track(&listener, &fixture.tracks.get(0).unwrap(), &table_id);
+    assert_eq!(client.payouts.get_treasury_balance(), before);
+    play_out(&client.hub, &owner, &table_id);
+    assert_eq!(client.payouts.get_treasury_balance(), before + FIXTURE_TRACK_PRICE / 10);
+}
+
+#[test]
+fn council_fee_override_sets_the_referral_base() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let member = Address::generate(env);
+    let referrer = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let class = FeeClass::Category(Symbol::new(env, fixtures::FIXTURE_TABLE_CATEGORY));
+
+    client.governance.set_council(&Council { members: vec![env, member.clone()], threshold: 1 });
+    assert!(client.governance.try_set_fee_override(&class, &Some(1_000)).is_err());
+    client.governance.propose_admin_action(&member, &AdminAction::SetFeeOverride(class.clone(), Some(1_000)));
+    env.ledger().with_mut(|ledger| ledger.timestamp += client.governance.get_timelock_delay());
+    client.governance.execute_param_change(&1);
+    assert_eq!(client.payouts.get_fee_override(&class), Some(1_000));
+
+    client.payouts.set_referral_config(&ReferralConfig { reward_bps: 5_000, rewarded_requests: 1 });
+    let user = Address::generate(env);
+    client.catalog.register_user(&user, &String::from_str(env, "ipfs://fixture-avatar"), &Some(referrer.clone()));
+    client.hub.join_table(&user, &table_id);
+    StellarAssetClient::new(env, &fixture.token).mint(&user, &FIXTURE_TRACK_PRICE);
+    client.hub.request_track(&user, &fixture.tracks.get(1).unwrap(), &table_id);
+    play_out(&client.hub, &fixture.artists.get(0).unwrap(), &table_id);
+    // Half of the overridden 10% fee on 1_000
+    assert_eq!(client.payouts.get_referral_rewards(&referrer), 50);
+}
+
+#[test]
+fn fee_routing_splits_requests_with_the_table() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let owner = fixture.artists.get(0).unwrap();
+    let routing = FeeRouting { platform_bps: 1_000, owner_bps: 2_000, table_treasury_bps: 1_000, artist_bps: 6_000 };
+    play_out(&client.hub, &owner, &table_id);
+
+    assert!(client.payouts.try_set_fee_routing(&owner, &table_id, &Some(FeeRouting { artist_bps: 5_000, ..routing.clone() })).is_err());
+    client.payouts.set_fee_routing(&owner, &table_id, &Some(routing));
+
+    let (treasury, owner_revenue) = (client.payouts.get_treasury_balance(), client.payouts.get_claimable_revenue(&owner));
+    client.hub.request_track(&listener, &fixture.tracks.get(1).unwrap(), &table_id);
+    play_out(&client.hub, &owner, &table_id);
+    assert_eq!(client.payouts.get_treasury_balance(), treasury + 100);
+    assert_eq!(client.payouts.get_claimable_revenue(&owner), owner_revenue + 200);
+    assert_eq!(client.payouts.get_table_treasury(&table_id), 100);
+
+    client.payouts.withdraw_table_treasury(&owner, &table_id, &100, &owner);
+    assert_eq!(client.payouts.get_table_treasury(&table_id), 0);
+}
+
+#[test]
+fn takedown_purges_queue_and_refunds() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let table_id = fixture.tables.get(0).unwrap();
+    let track_id = fixture.tracks.get(1).unwrap();
+    let requester = fixture.listeners.get(1).unwrap();
+    let balance = token.balance(&requester);
+
+    client.catalog.takedown_track(&fixture.admin, &track_id, &fixtures::fixture_id(env, b"reason", 0));
+
+    let queue = client.hub.get_queue(&table_id);
+    assert_eq!(queue.len(), FIXTURE_REQUESTS_PER_TABLE - 1);
+    assert!(!queue.contains(&track_id));
+    assert_eq!(token.balance(&requester), balance + fixtures::FIXTURE_TRACK_PRICE);
+    assert!(client.catalog.is_track_taken_down(&track_id));
+}
+
+#[test]
+fn closing_a_table_refunds_unplayed_requests() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(1).unwrap();
+    let balance = token.balance(&requester);
+    let revenue = client.payouts.get_claimable_revenue(&owner);
+
+    assert!(client.hub.try_set_table_status(&requester, &table_id, &false).is_err());
+    client.hub.set_table_status(&owner, &table_id, &false);
+
+    assert!(client.hub.get_queue(&table_id).is_empty());
+    assert_eq!(token.balance(&requester), balance + FIXTURE_TRACK_PRICE);
+    assert_eq!(client.payouts.get_claimable_revenue(&owner), revenue);
+    assert_eq!(client.payouts.get_treasury_balance(), 0);
+}
+
+#[test]
+fn takedown_returns_escrowed_payment_to_its_source() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let table_id = fixture.tables.get(1).unwrap();
+    let track_id = fixture.tracks.get(2).unwrap();
+    let (artist, requester) = (fixture.artists.get(2).unwrap(), fixture.listeners.get(0).unwrap());
+
+    client.hub.deposit(&requester, &FIXTURE_TRACK_PRICE);
+    let (balance, revenue) = (token.balance(&requester), client.payouts.get_claimable_revenue(&artist));
+    client.hub.request_track(&requester, &track_id, &table_id);
+    assert_eq!(client.hub.get_wallet_balance(&requester), 0);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), revenue);
+
+    client.catalog.takedown_track(&fixture.admin, &track_id, &fixtures::fixture_id(env, b"reason", 0));
+    assert_eq!(client.hub.get_wallet_balance(&requester), FIXTURE_TRACK_PRICE);
+    assert_eq!(token.balance(&requester), balance);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), revenue);
+}
+
+#[test]
+fn dispute_claws_back_only_the_settled_request() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let (owner, table_id) = (fixture.artists.get(0).unwrap(), fixture.tables.get(0).unwrap());
+    let (requester, other) = (fixture.listeners.get(0).unwrap(), fixture.listeners.get(1).unwrap());
+    let track_id = fixture.tracks.get(3).unwrap();
+    let (arbitrator, reason) = (Address::generate(env), fixtures::fixture_id(env, b"reason", 0));
+
+    client.governance.set_arbitrator(&arbitrator);
+    play_out(&client.hub, &owner, &table_id);
+    let first = client.hub.request_track(&requester, &track_id, &table_id);
+    assert!(client.governance.try_open_dispute(&requester, &first, &reason).is_err());
+    let second = client.hub.request_track(&other, &track_id, &table_id);
+    play_out(&client.hub, &owner, &table_id);
+
+    let (balance, revenue) = (token.balance(&requester), client.payouts.get_claimable_revenue(&owner));
+    client.governance.open_dispute(&requester, &first, &reason);
+    client.governance.rule_dispute(&arbitrator, &first, &true);
+    assert_eq!(token.balance(&requester), balance + FIXTURE_TRACK_PRICE);
+    assert_eq!(client.payouts.get_claimable_revenue(&owner), revenue - FIXTURE_TRACK_PRICE * 95 / 100);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 15 * 86400);
+    assert!(client.governance.try_open_dispute(&other, &second, &reason).is_err());
+}
+
+#[test]
+fn reputation_rewards_completed_plays_and_decays() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let table_id = fixture.tables.get(0).unwrap();
+    let owner = fixture.artists.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    client.hub.advance_queue_public(&owner, &table_id);
+    assert_eq!(client.catalog.get_reputation(&requester), 102);
+    assert_eq!(client.catalog.get_reputation_history(&requester).len(), 1);
+    let receipts = client.community.get_receipts(&requester, &0, &10);
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(client.community.get_receipt_count(&requester), 1);
+    assert_eq!(receipts.get(0).unwrap().request_id, fixture.requests.get(0).unwrap());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 30 * 86400);
+    assert_eq!(client.catalog.get_reputation(&requester), 100);
+}
+
+#[test]
+fn subscription_pays_artist_and_expires() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let fan = fixture.listeners.get(0).unwrap();
+
+    let tier = SubscriptionTier {
+        price: 2_000,
+        period_secs: 30 * 86400,
+        benefits_hash: fixtures::fixture_id(env, b"benefits", 0),
+    };
+    client.market.set_subscription_tiers(&artist, &vec![env, tier.clone(), tier]);
+
+    let expires_at = client.market.subscribe(&fan, &artist, &0);
+    assert_eq!(expires_at, fixtures::FIXTURE_TIMESTAMP + 30 * 86400);
+    assert_eq!(client.market.subscribe(&fan, &artist, &0), expires_at + 30 * 86400);
+    assert!(client.market.is_subscriber(&fan, &artist));
+    // Switching tiers has to wait out the paid period
+    assert!(client.market.try_subscribe(&fan, &artist, &1).is_err());
+
+    // 5% platform fee on each payment
+    assert_eq!(client.payouts.withdraw_revenue(&artist), 3_800);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 60 * 86400);
+    assert!(!client.market.is_subscriber(&fan, &artist));
+    client.market.subscribe(&fan, &artist, &1);
+    assert_eq!(client.market.get_subscription(&fan, &artist).unwrap().tier, 1);
+}
+
+#[test]
+fn deregistering_leaves_tables_and_keeps_reputation() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let user = fixture.listeners.get(3).unwrap();
+    let first = fixture.tables.get(0).unwrap();
+    let second = fixture.tables.get(1).unwrap();
+    let members = client.hub.get_table_member_count(&second);
+
+    // Reported the way the hub reports a played request
+    client.catalog.adjust_reputation(&fixture.links.hub, &user, &40, &Symbol::new(env, "played"));
+    let reputation = client.catalog.get_reputation(&user);
+    client.hub.leave_table(&user, &first);
+    client.catalog.deregister_user(&user);
+    assert!(!client.hub.is_table_member(&user, &second));
+    assert_eq!(client.hub.get_table_member_count(&first), members - 1);
+    assert_eq!(client.hub.get_table_member_count(&second), members - 1);
+
+    client.catalog.register_user(&user, &String::from_str(env, "ipfs://fixture-avatar"), &None);
+    assert_eq!(client.catalog.get_reputation(&user), reputation);
+    client.hub.join_table(&user, &second);
+    assert_eq!(client.hub.get_table_member_count(&second), members);
+}
+
+#[test]
+fn rejoining_a_table_does_not_count_towards_ten_tables() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let user = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    for _ in 0..10 {
+        client.hub.leave_table(&user, &table_id);
+        client.hub.join_table(&user, &table_id);
+    }
+    assert!(!client.community.get_achievements(&user).contains(Symbol::new(env, "ten_tables")));
+}
+
+#[test]
+fn failed_campaign_refunds_pledges() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let artist = fixture.artists.get(0).unwrap();
+    let backer = fixture.listeners.get(0).unwrap();
+    let balance = token.balance(&backer);
+
+    let deadline = fixtures::FIXTURE_TIMESTAMP + 7 * 86400;
+    let campaign_id = client.venue.open_campaign(&artist, &50_000, &deadline, &20);
+    client.venue.pledge(&backer, &campaign_id, &10_000);
+    assert_eq!(token.balance(&backer), balance - 10_000);
+    assert!(client.venue.try_refund_pledge(&backer, &campaign_id).is_err());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp = deadline);
+    assert!(client.venue.try_pledge(&backer, &campaign_id, &1_000).is_err());
+    assert_eq!(client.venue.refund_pledge(&backer, &campaign_id), 10_000);
+    assert_eq!(token.balance(&backer), balance);
+    assert_eq!(client.venue.get_pledge(&campaign_id, &backer), 0);
+}
+
+#[test]
+fn funded_campaign_track_needs_no_backer_cosign() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let backer = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    let deadline = fixtures::FIXTURE_TIMESTAMP + 7 * 86400;
+    let campaign_id = client.venue.open_campaign(&artist, &10_000, &deadline, &20);
+    client.venue.pledge(&backer, &campaign_id, &10_000);
+    let track_id = client.venue.mint_campaign_track(
+        &campaign_id,
+        &String::from_str(env, "Backed"),
+        &FIXTURE_TRACK_PRICE,
+        &10,
+        &String::from_str(env, "ipfs://backed"),
+    );
+    assert!(client.catalog.get_pending_collaborators(&track_id).is_empty());
+    client.hub.request_track(&backer, &track_id, &table_id);
+}
+
+#[test]
+fn unminted_funded_campaign_refunds_after_the_window() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let artist = fixture.artists.get(0).unwrap();
+    let backer = fixture.listeners.get(0).unwrap();
+    let balance = token.balance(&backer);
+
+    let deadline = fixtures::FIXTURE_TIMESTAMP + 7 * 86400;
+    let campaign_id = client.venue.open_campaign(&artist, &5_000, &deadline, &20);
+    client.venue.pledge(&backer, &campaign_id, &5_000);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp = deadline);
+    assert!(client.venue.try_refund_pledge(&backer, &campaign_id).is_err());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp = deadline + 30 * 86400);
+    assert!(client
+        .venue
+        .try_mint_campaign_track(
+            &campaign_id,
+            &String::from_str(env, "Never"),
+            &1000,
+            &10,
+            &String::from_str(env, "ipfs://never"),
+        )
+        .is_err());
+    assert_eq!(client.venue.refund_pledge(&backer, &campaign_id), 5_000);
+    assert_eq!(token.balance(&backer), balance);
+}
+
+#[test]
+fn expired_credit_promo_returns_unused_escrow() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let listener = fixture.listeners.get(0).unwrap();
+    let code = Bytes::from_slice(env, b"SPRING");
+    let code_hash: BytesN<32> = env.crypto().sha256(&code).into();
+    let expires_at = env.ledger().timestamp() + 86400;
+
+    StellarAssetClient::new(env, &fixture.token).mint(&fixture.admin, &300);
+    client.venue.register_promo(&code_hash, &PromoReward::Credit(100), &3, &expires_at);
+    client.venue.redeem_promo(&listener, &code);
+    assert!(client.venue.try_reclaim_promo(&code_hash).is_err());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp = expires_at);
+    assert_eq!(client.venue.reclaim_promo(&code_hash), 200);
+    assert_eq!(token.balance(&fixture.admin), 200);
+    assert!(client.venue.try_reclaim_promo(&code_hash).is_err());
+}
+
+#[test]
+fn airdrop_claims_once_with_valid_proof() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let alice = fixture.listeners.get(0).unwrap();
+    let bob = fixture.listeners.get(1).unwrap();
+
+    let leaf = |user: &Address, amount: i128| -> BytesN<32> {
+        let mut bytes = user.clone().to_xdr(env);
+        bytes.extend_from_array(&amount.to_be_bytes());
+        env.crypto().sha256(&bytes).into()
+    };
+    let alice_leaf = leaf(&alice, 500);
+    let bob_leaf = leaf(&bob, 700);
+    let (first, second) = if alice_leaf.to_array() <= bob_leaf.to_array() {
+        (alice_leaf.clone(), bob_leaf.clone())
+    } else {
+        (bob_leaf.clone(), alice_leaf.clone())
+    };
+    let mut pair = Bytes::from_array(env, &first.to_array());
+    pair.extend_from_array(&second.to_array());
+    let root: BytesN<32> = env.crypto().sha256(&pair).into();
+
+    let token = token::Client::new(env, &fixture.token);
+    StellarAssetClient::new(env, &fixture.token).mint(&fixture.admin, &2_200);
+    let round = client.venue.set_airdrop_root(&root, &1_000);
+    assert!(client.venue.try_claim_airdrop(&alice, &vec![env, bob_leaf.clone()], &700).is_err());
+    client.venue.claim_airdrop(&alice, &vec![env, bob_leaf.clone()], &500);
+    assert_eq!(client.hub.get_token_credit(&alice), 500);
+    assert!(client.venue.is_airdrop_claimed(&round, &alice));
+    assert!(client.venue.try_claim_airdrop(&alice, &vec![env, bob_leaf], &500).is_err());
+    // Only 500 of the escrowed 1_000 is left
+    assert!(client.venue.try_claim_airdrop(&bob, &vec![env, alice_leaf.clone()], &700).is_err());
+
+    // The next round hands the first round's remainder back
+    client.venue.set_airdrop_root(&root, &1_200);
+    assert_eq!(token.balance(&fixture.admin), 500);
+    client.venue.claim_airdrop(&bob, &vec![env, alice_leaf], &700);
+    assert_eq!(client.hub.get_token_credit(&bob), 700);
+}
+
+#[contract]
+struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData> {
+        // $0.10 per token unless set_price says otherwise
+        let price = env.storage().instance().get(&asset).unwrap_or(10i128.pow(13));
+        Some(PriceData { price, timestamp: env.ledger().timestamp() })
+    }
+
+    pub fn set_price(env: Env, asset: OracleAsset, price: i128) {
+        env.storage().instance().set(&asset, &price);
+    }
+
+    pub fn decimals(_env: Env) -> u32 {
+        14
+    }
+}
+
+#[test]
+fn usd_priced_track_converts_at_oracle_price() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+
+    assert!(client.catalog.try_set_track_pricing(&artist, &track_id, &150, &true).is_err());
+
+    let oracle = env.register(MockOracle, ());
+    client.catalog.set_price_oracle(&OracleConfig { oracle, max_staleness_secs: 300 });
+    client.catalog.set_track_pricing(&artist, &track_id, &150, &true);
+
+    // $1.50 at $0.10 is 15 tokens of 7 decimals
+    assert_eq!(client.catalog.get_current_price(&track_id), 150_000_000);
+
+    client.catalog.set_track_pricing(&artist, &track_id, &fixtures::FIXTURE_TRACK_PRICE, &false);
+    assert_eq!(client.catalog.get_current_price(&track_id), fixtures::FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn token_requests_pay_the_oracle_equivalent() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let (owner, table_id) = (fixture.artists.get(0).unwrap(), fixture.tables.get(0).unwrap());
+    let (artist, track_id) = (fixture.artists.get(1).unwrap(), fixture.tracks.get(1).unwrap());
+    let listener = fixture.listeners.get(0).unwrap();
+    let other = env.register_stellar_asset_contract_v2(fixture.admin.clone()).address();
+    let other_token = token::Client::new(env, &other);
+    let oracle = env.register(MockOracle, ());
+
+    StellarAssetClient::new(env, &other).mint(&listener, &fixtures::FIXTURE_BALANCE);
+    client.catalog.set_token_allowed(&other, &true);
+    client.catalog.set_table_tokens(&owner, &table_id, &vec![env, other.clone()]);
+    client.catalog.set_price_oracle(&OracleConfig { oracle: oracle.clone(), max_staleness_secs: 300 });
+    // The other token is worth twice the default one
+    MockOracleClient::new(env, &oracle).set_price(&OracleAsset::Stellar(other.clone()), &(2 * 10i128.pow(13)));
+    client.payouts.set_fee_routing(&owner, &table_id, &Some(FeeRouting { platform_bps: 1_000, owner_bps: 2_000, table_treasury_bps: 1_000, artist_bps: 6_000 }));
+    play_out(&client.hub, &owner, &table_id);
+
+    let balance = other_token.balance(&listener);
+    client.hub.request_track_with_token(&listener, &track_id, &table_id, &other);
+    assert_eq!(other_token.balance(&listener), balance - FIXTURE_TRACK_PRICE / 2);
+    play_out(&client.hub, &owner, &table_id);
+    assert_eq!(client.payouts.get_token_treasury_balance(&other), 50);
+    assert_eq!(client.payouts.get_token_revenue(&owner, &other), 100);
+    assert_eq!(client.payouts.get_table_token_treasury(&table_id, &other), 50);
+    assert_eq!(client.payouts.get_token_revenue(&artist, &other), 300);
+
+    client.payouts.set_track_splitter(&artist, &track_id, &Some(env.register(MockSplitter, ())));
+    assert!(client.hub.try_request_track_with_token(&listener, &track_id, &table_id, &other).is_err());
+}
+
+// Fills at whatever rate it's set to and leaves the slippage check to the caller
+#[contract]
+struct MockRouter;
+
+#[contractimpl]
+impl MockRouter {
+    pub fn set_rate_bps(env: Env, rate_bps: i128) {
+        env.storage().instance().set(&Symbol::new(&env, "rate"), &rate_bps);
+    }
+
+    pub fn swap_exact_in(
+        env: Env,
+        from: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        _min_amount_out: i128,
+        to: Address,
+    ) -> i128 {
+        let router = env.current_contract_address();
+        token::Client::new(&env, &token_in).transfer_from(&router, &from, &router, &amount_in);
+        let rate: i128 = env.storage().instance().get(&Symbol::new(&env, "rate")).unwrap_or(10000);
+        let amount_out = amount_in * rate / 10000;
+        token::Client::new(&env, &token_out).transfer(&router, &to, &amount_out);
+        amount_out
+    }
+}
+
+#[test]
+fn swap_request_rejects_a_short_fill() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let other = env.register_stellar_asset_contract_v2(fixture.admin.clone()).address();
+    let (default_token, other_token) = (token::Client::new(env, &fixture.token), token::Client::new(env, &other));
+    let router = env.register(MockRouter, ());
+    let router_client = MockRouterClient::new(env, &router);
+    StellarAssetClient::new(env, &other).mint(&listener, &10_000);
+    StellarAssetClient::new(env, &fixture.token).mint(&router, &10_000);
+    client.payouts.set_swap_router(&router);
+
+    let queued = client.hub.get_queue(&table_id).len();
+    let balance = default_token.balance(&listener);
+    client.venue.request_track_with_swap(&listener, &other, &FIXTURE_TRACK_PRICE, &track_id, &table_id);
+    assert_eq!(other_token.balance(&listener), 10_000 - FIXTURE_TRACK_PRICE);
+    assert_eq!(default_token.balance(&listener), balance);
+    assert_eq!(client.hub.get_queue(&table_id).len(), queued + 1);
+
+    // Half a fill doesn't cover the price, so the whole request reverts
+    router_client.set_rate_bps(&5_000);
+    assert!(client
+        .venue
+        .try_request_track_with_swap(&listener, &other, &FIXTURE_TRACK_PRICE, &track_id, &table_id)
+        .is_err());
+    assert_eq!(other_token.balance(&listener), 10_000 - FIXTURE_TRACK_PRICE);
+    assert_eq!(client.hub.get_queue(&table_id).len(), queued + 1);
+}
+
+#[contract]
+struct MockSplitter;
+
+#[contractimpl]
+impl MockSplitter {
+    pub fn distribute(env: Env, amount: i128) {
+        let total: i128 = env.storage().instance().get(&Symbol::new(&env, "total")).unwrap_or(0);
+        env.storage().instance().set(&Symbol::new(&env, "total"), &(total + amount));
+    }
+
+    pub fn total(env: Env) -> i128 {
+        env.storage().instance().get(&Symbol::new(&env, "total")).unwrap_or(0)
+    }
+}
+
+#[test]
+fn splitter_contract_receives_track_royalties() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let artist = fixture.artists.get(1).unwrap();
+    let track_id = fixture.tracks.get(1).unwrap();
+    let splitter = env.register(MockSplitter, ());
+    play_out(&client.hub, &fixture.artists.get(0).unwrap(), &table_id);
+
+    client.payouts.set_track_splitter(&artist, &track_id, &Some(splitter.clone()));
+    assert_eq!(client.payouts.get_track_splitter(&track_id), Some(splitter.clone()));
+    assert!(client.payouts.try_propose_split_change(&artist, &track_id, &vec![env, (artist.clone(), 100u32)]).is_err());
+
+    let (treasury, revenue) = (client.payouts.get_treasury_balance(), client.payouts.get_claimable_revenue(&artist));
+    client.hub.request_track(&listener, &track_id, &table_id);
+    play_out(&client.hub, &fixture.artists.get(0).unwrap(), &table_id);
+    let royalty = FIXTURE_TRACK_PRICE - (client.payouts.get_treasury_balance() - treasury);
+    assert_eq!(client.payouts.get_claimable_revenue(&splitter), royalty);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), revenue);
+
+    assert_eq!(client.payouts.push_splitter_revenue(&track_id), royalty);
+    assert_eq!(token::Client::new(env, &fixture.token).balance(&splitter), royalty);
+    assert_eq!(MockSplitterClient::new(env, &splitter).total(), royalty);
+    assert!(client.payouts.try_push_splitter_revenue(&track_id).is_err());
+}
+
+#[test]
+fn charity_share_comes_out_before_the_split() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let artist = fixture.artists.get(1).unwrap();
+    let track_id = fixture.tracks.get(1).unwrap();
+    let charity = Address::generate(env);
+    play_out(&client.hub, &fixture.artists.get(0).unwrap(), &table_id);
+
+    assert!(client.payouts.try_set_track_charity(&artist, &track_id, &Some((charity.clone(), 1_000))).is_err());
+    client.payouts.set_charity_allowed(&charity, &true);
+    client.payouts.set_track_charity(&artist, &track_id, &Some((charity.clone(), 1_000)));
+
+    let (treasury, revenue) = (client.payouts.get_treasury_balance(), client.payouts.get_claimable_revenue(&artist));
+    client.hub.request_track(&listener, &track_id, &table_id);
+    play_out(&client.hub, &fixture.artists.get(0).unwrap(), &table_id);
+    let royalty = FIXTURE_TRACK_PRICE - (client.payouts.get_treasury_balance() - treasury);
+    assert_eq!(client.payouts.get_claimable_revenue(&charity), royalty / 10);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), revenue + royalty - royalty / 10);
+}
+
+#[test]
+fn isrc_belongs_to_one_track() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let first = fixture.tracks.get(0).unwrap();
+    let second = fixture.tracks.get(3).unwrap();
+    let isrc = String::from_str(env, "USRC17607839");
+
+    client.market.set_track_identifiers(&artist, &first, &Some(isrc.clone()), &None);
+    assert_eq!(client.market.get_track_by_isrc(&isrc), Some(first.clone()));
+    assert!(client.market.try_set_track_identifiers(&artist, &second, &Some(isrc.clone()), &None).is_err());
+
+    client.market.set_track_identifiers(&artist, &first, &None, &None);
+    client.market.set_track_identifiers(&artist, &second, &Some(isrc.clone()), &None);
+    assert_eq!(client.market.get_track_by_isrc(&isrc), Some(second));
+    assert_eq!(client.market.get_isrc(&first), None);
+}
+
+#[test]
+fn retagging_moves_track_between_tag_indexes() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let jazz = Symbol::new(env, "jazz");
+    let house = Symbol::new(env, "house");
+
+    client.catalog.set_track_tags(&artist, &track_id, &vec![env, jazz.clone(), house.clone(), jazz.clone()]);
+    assert_eq!(client.catalog.list_tracks_by_tag(&jazz, &0, &10), vec![env, track_id.clone()]);
+    assert_eq!(client.catalog.get_track(&track_id).unwrap().tags.len(), 2);
+
+    client.catalog.set_track_tags(&artist, &track_id, &vec![env, house.clone()]);
+    assert_eq!(client.catalog.list_tracks_by_tag(&jazz, &0, &10).len(), 0);
+    assert_eq!(client.catalog.list_tracks_by_tag(&house, &0, &10), vec![env, track_id]);
+}
+
+#[test]
+fn register_user_rejects_unsupported_uri() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let user = Address::generate(env);
+
+    let result = client.catalog.try_register_user(&user, &String::from_str(env, "ftp://avatar"), &None);
+    assert_eq!(result.unwrap_err(), Ok(ValidationError::UnsupportedUriScheme.into()));
+    let result = client.catalog.try_register_user(&user, &String::from_str(env, ""), &None);
+    assert_eq!(result.unwrap_err(), Ok(ValidationError::EmptyUri.into()));
+
+    client.catalog.register_user(&user, &String::from_str(env, "ar://avatar"), &None);
+    assert!(client.catalog.get_user(&user).is_some());
+}
+
+#[test]
+fn relabeled_table_moves_category() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let bar = Symbol::new(env, fixtures::FIXTURE_TABLE_CATEGORY);
+    let study = Symbol::new(env, "study");
+
+    client.hub.set_table_labels(&owner, &table_id, &study, &vec![env, Symbol::new(env, "quiet")]);
+    assert_eq!(client.hub.list_tables_by_category(&study, &0, &10), vec![env, table_id.clone()]);
+    assert_eq!(client.hub.list_tables_by_category(&bar, &0, &10), vec![env, fixture.tables.get(1).unwrap()]);
+    assert_eq!(client.hub.get_table(&table_id).unwrap().tags.len(), 1);
+}
+
+#[test]
+fn competition_pays_top_requesters() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let first = fixture.listeners.get(0).unwrap();
+    let second = fixture.listeners.get(1).unwrap();
+    let third = fixture.listeners.get(2).unwrap();
+    let balance = token::Client::new(env, &fixture.token);
+
+    StellarAssetClient::new(env, &fixture.token).mint(&owner, &10_000);
+    let ends_at = fixtures::FIXTURE_TIMESTAMP + 86400;
+    let competition_id = client.community.open_competition(
+        &owner,
+        &Some(table_id.clone()),
+        &CompetitionMetric::TopRequester,
+        &10_000,
+        &vec![env, 7_000, 3_000],
+        &fixtures::FIXTURE_TIMESTAMP,
+        &ends_at,
+    );
+
+    client.hub.request_track(&second, &track_id, &table_id);
+    client.hub.request_track(&first, &track_id, &table_id);
+    client.hub.request_track(&first, &track_id, &table_id);
+    // Free requests don't score
+    client.hub.credit_requests(&fixture.links.venue, &third, &3);
+    for _ in 0..3 {
+        client.hub.request_track(&third, &track_id, &table_id);
+    }
+    assert_eq!(client.community.get_competition_standings(&competition_id).len(), 2);
+    assert_eq!(client.community.get_competition(&competition_id).unwrap().prize_pool, 10_000);
+
+    assert!(client.community.try_finalize_competition(&competition_id).is_err());
+    env.ledger().with_mut(|ledger| ledger.timestamp = ends_at);
+
+    let before = (balance.balance(&first), balance.balance(&second), balance.balance(&third));
+    client.community.finalize_competition(&competition_id);
+    assert_eq!(balance.balance(&first), before.0 + 7_000);
+    assert_eq!(balance.balance(&second), before.1 + 3_000);
+    assert_eq!(balance.balance(&third), before.2);
+    assert!(client.community.get_active_competitions().is_empty());
+}
+
+#[test]
+fn artist_of_the_period_takes_fee_share() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let winner = fixture.artists.get(1).unwrap();
+
+    let owner = fixture.artists.get(0).unwrap();
+
+    play_out(&client.hub, &owner, &table_id);
+    client.payouts.set_artist_award_config(&ArtistAwardConfig { share_bps: 5_000, period_secs: 30 * 86400 });
+    let period = client.payouts.get_current_award_period();
+
+    client.hub.request_track(&listener, &fixture.tracks.get(0).unwrap(), &table_id);
+    client.hub.request_track(&listener, &fixture.tracks.get(1).unwrap(), &table_id);
+    client.hub.request_track(&listener, &fixture.tracks.get(4).unwrap(), &table_id);
+    play_out(&client.hub, &owner, &table_id);
+    assert_eq!(client.payouts.get_period_leader(&period), Some((winner.clone(), 2)));
+    assert!(client.payouts.try_close_period(&period).is_err());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 30 * 86400);
+    let before = client.payouts.get_claimable_revenue(&winner);
+    // Half of three 5% fees on 1_000
+    assert_eq!(client.payouts.close_period(&period), 75);
+    assert_eq!(client.payouts.get_claimable_revenue(&winner), before + 75);
+    assert!(client.payouts.try_close_period(&period).is_err());
+}
+
+#[test]
+fn ad_slots_play_between_tracks() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let sponsor = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let balance = token::Client::new(env, &fixture.token);
+
+    client.community.set_table_ads(&owner, &table_id, &TableAdConfig { cadence: 2, slot_price: 100 });
+    let booking_id = client.community.book_ad_slots(
+        &sponsor,
+        &table_id,
+        &fixtures::fixture_id(env, b"ad", 0),
+        &String::from_str(env, "ipfs://ad"),
+        &3,
+    );
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    client.hub.advance_queue_public(&owner, &table_id);
+    assert!(client.community.get_current_ad(&table_id).is_none());
+
+    let before = balance.balance(&owner);
+    client.hub.advance_queue_public(&owner, &table_id);
+    assert_eq!(client.community.get_current_ad(&table_id).unwrap().slots_remaining, 2);
+    // 5% platform fee on the slot price
+    assert_eq!(balance.balance(&owner), before + 95);
+
+    let before = balance.balance(&sponsor);
+    assert_eq!(client.community.cancel_ad_booking(&sponsor, &booking_id), 200);
+    assert_eq!(balance.balance(&sponsor), before + 200);
+    assert!(client.community.get_ad_bookings(&table_id).is_empty());
+}
+
+#[test]
+fn sponsorship_streams_and_penalizes_early_exit() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let sponsor = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let balance = token::Client::new(env, &fixture.token);
+
+    let sponsorship_id = client.community.propose_sponsorship(
+        &sponsor,
+        &table_id,
+        &10_000,
+        &(100 * 86400),
+        &String::from_str(env, "https://brand.example"),
+        &2_000,
+    );
+    client.community.accept_sponsorship(&owner, &sponsorship_id);
+    assert!(client.community.get_table_sponsorship(&table_id).is_some());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 25 * 86400);
+    // 2_500 streamed, less the 20% penalty on the 7_500 still to come
+    assert_eq!(client.community.claim_sponsorship(&owner, &sponsorship_id), 1_000);
+    assert_eq!(client.payouts.get_table_treasury(&table_id), 1_000);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 25 * 86400);
+    let before = balance.balance(&sponsor);
+    client.community.terminate_sponsorship(&sponsor, &sponsorship_id);
+    // 4_000 streamed since the claim, plus 20% of the 5_000 never streamed
+    assert_eq!(client.payouts.get_table_treasury(&table_id), 6_000);
+    assert_eq!(balance.balance(&sponsor), before + 4_000);
+    assert!(client.community.get_table_sponsorship(&table_id).is_none());
+
+    // An owner who claims before walking away still pays the full penalty
+    let sponsorship_id = client.community.propose_sponsorship(
+        &sponsor,
+        &table_id,
+        &10_000,
+        &(100 * 86400),
+        &String::from_str(env, "https://brand.example"),
+        &2_000,
     );
+    client.community.accept_sponsorship(&owner, &sponsorship_id);
+    env.ledger().with_mut(|ledger| ledger.timestamp += 25 * 86400);
+    client.community.claim_sponsorship(&owner, &sponsorship_id);
+    let before = balance.balance(&sponsor);
+    client.community.terminate_sponsorship(&owner, &sponsorship_id);
+    assert_eq!(balance.balance(&sponsor), before + 9_000);
+    assert_eq!(client.payouts.get_table_treasury(&table_id), 7_000);
+}
+
+#[test]
+fn poke_advances_stalled_queue_for_a_reward() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let keeper = Address::generate(env);
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let balance = token::Client::new(env, &fixture.token);
+
+    client.hub.set_keeper_config(&KeeperConfig { reward: 10, play_window_secs: 600, vote_ttl_secs: 300, max_rewards_per_day: 2 });
+    client.hub.request_track(&listener, &fixture.tracks.get(0).unwrap(), &table_id);
+
+    // Starting a paid request pays the reward out of the fee it settles
+    let first = client.hub.get_queue(&table_id).get(0).unwrap();
+    assert_eq!(client.hub.poke(&keeper, &table_id), 10);
+    assert_eq!(client.hub.get_current_track(&table_id), Some(first));
+    assert!(client.hub.try_poke(&keeper, &table_id).is_err());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 600);
+    client.hub.poke(&keeper, &table_id);
+    assert_eq!(client.hub.get_queue(&table_id).len(), FIXTURE_REQUESTS_PER_TABLE - 1);
+    assert_eq!(balance.balance(&keeper), 20);
+
+    // The table's daily cap is spent
+    env.ledger().with_mut(|ledger| ledger.timestamp += 600);
+    assert_eq!(client.hub.poke(&keeper, &table_id), 0);
+    assert_eq!(balance.balance(&keeper), 20);
+}
+
+#[test]
+fn poke_pays_nothing_for_free_requests() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let keeper = Address::generate(env);
+    let (owner, table_id) = (fixture.artists.get(0).unwrap(), fixture.tables.get(0).unwrap());
+    let listener = fixture.listeners.get(0).unwrap();
+
+    client.hub.set_keeper_config(&KeeperConfig { reward: 10, play_window_secs: 600, vote_ttl_secs: 300, max_rewards_per_day: 100 });
+    play_out(&client.hub, &owner, &table_id);
+    client.hub.update_table(&owner, &table_id, &String::from_str(env, "Fixture Table"), &2, &0);
+    client.hub.request_track(&listener, &fixture.tracks.get(0).unwrap(), &table_id);
+    assert_eq!(client.hub.poke(&keeper, &table_id), 0);
+}
+
+#[test]
+fn update_table_publishes_new_values() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let name = String::from_str(env, "Late Show");
+
+    client.hub.update_table(&owner, &table_id, &name, &3, &150);
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, fixture.links.hub);
+    assert_eq!(topics, (Symbol::new(env, "table_updated"), EVENT_SCHEMA_VERSION, table_id).into_val(env));
+    assert_eq!(<(String, u32, u32)>::try_from_val(env, &data).unwrap(), (name, 3, 150));
+}
+
+#[test]
+fn request_events_carry_the_requester() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+
+    let (owner, table_id) = (fixture.artists.get(0).unwrap(), fixture.tables.get(0).unwrap());
+
+    play_out(&client.hub, &owner, &table_id);
+    let track_id = fixture.tracks.get(0).unwrap();
+    let request_id = client.hub.request_track(&listener, &track_id, &table_id);
+
+    let requested = (Symbol::new(env, "track_requested"), EVENT_SCHEMA_VERSION, request_id, listener).into_val(env);
+    assert!(env.events().all().iter().any(|(_, topics, _)| topics == requested));
+    // Royalties are paid once the request starts playing
+    client.hub.advance_queue_public(&owner, &table_id);
+    let royalty = (Symbol::new(env, "royalty_paid"), EVENT_SCHEMA_VERSION, owner, track_id).into_val(env);
+    assert!(env.events().all().iter().any(|(_, topics, _)| topics == royalty));
+}
+
+#[test]
+fn privileged_actions_append_to_audit_log() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+
+    // Installing the track NFT wasm at deploy is already on the catalog's log
+    let start = client.catalog.get_audit_count();
+    client.catalog.suspend_user(&listener, &BytesN::from_array(env, &[1; 32]));
+    client.catalog.takedown_track(&fixture.admin, &track_id, &BytesN::from_array(env, &[2; 32]));
+    client.catalog.pause(&PAUSE_REQUESTS);
+
+    assert_eq!(client.catalog.get_audit_count(), start + 3);
+    let log = client.catalog.get_audit_log(&(start + 1), &10);
+    assert_eq!(log.len(), 2);
+    let takedown = log.get(0).unwrap();
+    assert_eq!(takedown.entry_id, start + 1);
+    assert_eq!(takedown.actor, fixture.admin);
+    assert_eq!(takedown.action, Symbol::new(env, "takedown"));
+    assert_eq!(takedown.target, AuditTarget::Track(track_id));
+    assert_eq!(log.get(1).unwrap().target, AuditTarget::Platform);
+
+    // Config setters are logged too, on the contract that holds the config
+    let arbitrator = Address::generate(env);
+    client.governance.set_arbitrator(&arbitrator);
+    let entry = client.governance.get_audit_log(&(client.governance.get_audit_count() - 1), &1).get(0).unwrap();
+    assert_eq!(entry.action, Symbol::new(env, "arbitrator_set"));
+    assert_eq!(entry.target, AuditTarget::Account(arbitrator));
+
+    let history = client.catalog.get_ttl_policy().history;
+    env.as_contract(&fixture.links.catalog, || {
+        // juke_common's AuditLog(id) key, as the tuple it encodes to
+        let key = (Symbol::new(env, "AuditLog"), start + 2);
+        assert!(!env.storage().instance().has(&key));
+        assert_eq!(env.storage().persistent().get_ttl(&key), history);
+    });
+}
+
+#[test]
+fn table_snapshot_bundles_room_state() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    let idle = client.hub.get_table_snapshot(&table_id);
+    assert!(matches!(idle.now_playing, NowPlaying::Idle));
+    assert_eq!(idle.queue.len(), FIXTURE_REQUESTS_PER_TABLE);
+
+    let playing = client.hub.advance_queue_public(&owner, &table_id).unwrap();
+    client.hub.vote_to_skip(&listener, &table_id);
+
+    let snapshot = client.hub.get_table_snapshot(&table_id);
+    let NowPlaying::Playing(request, track) = snapshot.now_playing else {
+        panic!("Nothing playing");
+    };
+    assert_eq!(request.track_id, playing);
+    assert_eq!(track.track_id, playing);
+    assert_eq!(snapshot.queue.len(), FIXTURE_REQUESTS_PER_TABLE - 1);
+    assert_eq!(snapshot.skip_votes, 1);
+    assert_eq!(snapshot.member_count, fixture.listeners.len());
+}
+
+#[test]
+fn daily_stats_bucket_by_epoch_day() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let today = env.ledger().timestamp() / 86400;
+    let before = client.catalog.get_daily_stats(&today);
+
+    client.hub.request_track(&listener, &track_id, &table_id);
+    env.ledger().with_mut(|ledger| ledger.timestamp += 86400);
+    client.hub.request_track(&listener, &track_id, &table_id);
+    client.hub.request_track(&listener, &track_id, &table_id);
+    play_out(&client.hub, &fixture.artists.get(0).unwrap(), &table_id);
+
+    let range = client.catalog.get_stats_range(&today, &(today + 1));
+    assert_eq!(range.len(), 2);
+    assert_eq!(range.get(0).unwrap().requests, before.requests + 1);
+    assert_eq!(range.get(0).unwrap().volume, before.volume + FIXTURE_TRACK_PRICE);
+    assert_eq!(range.get(1).unwrap().day, today + 1);
+    assert_eq!(range.get(1).unwrap().requests, 2);
+    assert!(range.get(1).unwrap().fees > 0);
+    assert!(client.catalog.try_get_stats_range(&(today + 1), &today).is_err());
+
+    env.as_contract(&fixture.links.catalog, || {
+        // The catalog's StatsKey::Daily(day), as the tuple it encodes to
+        let key = (Symbol::new(env, "Daily"), today + 1);
+        assert!(!env.storage().instance().has(&key));
+        assert_eq!(env.storage().temporary().get_ttl(&key), 91 * 17_280);
+    });
+}
+
+#[test]
+fn artist_earnings_rank_top_tracks() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let (first, second) = (fixture.tracks.get(0).unwrap(), fixture.tracks.get(3).unwrap());
+
+    play_out(&client.hub, &artist, &table_id);
+    let plays_before = client.payouts.get_artist_earnings(&artist).plays_paid;
+    client.hub.request_track(&listener, &second, &table_id);
+    client.hub.request_track(&listener, &first, &table_id);
+    client.hub.request_track(&listener, &first, &table_id);
+    play_out(&client.hub, &artist, &table_id);
+
+    let earnings = client.payouts.get_artist_earnings(&artist);
+    assert_eq!(earnings.plays_paid, plays_before + 3);
+    assert_eq!(earnings.gross_revenue, client.payouts.get_track_earnings(&first).gross_revenue
+        + client.payouts.get_track_earnings(&second).gross_revenue);
+    assert!(earnings.platform_fees_paid > 0);
+    assert_eq!(earnings.top_tracks.len(), 2);
+    assert_eq!(earnings.top_tracks.get(0).unwrap().0, first);
+    assert_eq!(earnings.claimable, client.payouts.get_claimable_revenue(&artist));
+
+    let top_tracks = client.community.get_top_tracks(&1);
+    assert_eq!(top_tracks.len(), 1);
+    assert!(top_tracks.get(0).unwrap().subject == LeaderboardSubject::Track(first.clone()));
+    assert_eq!(top_tracks.get(0).unwrap().score, client.catalog.get_track(&first).unwrap().play_count as i128);
+    let top_artists = client.community.get_top_artists(&10);
+    assert!(top_artists.get(0).unwrap().subject == LeaderboardSubject::Artist(artist));
+    assert_eq!(top_artists.get(0).unwrap().score, earnings.gross_revenue);
+}
+
+#[test]
+fn skip_votes_reset_with_each_request() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    assert!(!client.hub.vote_to_skip(&listener, &table_id));
+    assert!(!client.hub.vote_to_skip(&listener, &table_id));
+    assert!(client.hub.has_voted_to_skip(&listener, &table_id));
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    assert!(!client.hub.has_voted_to_skip(&listener, &table_id));
+}
+
+#[test]
+fn skip_votes_last_for_the_play_window() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let votes_key = crate::VoteKey::SkipVotes(table_id.clone(), fixture.requests.get(0).unwrap());
+
+    // No request playing yet, so there is nothing to vote on
+    assert!(client.hub.try_vote_to_skip(&listener, &table_id).is_err());
+
+    client.hub.set_keeper_config(&KeeperConfig { reward: 0, play_window_secs: 600, vote_ttl_secs: 300, max_rewards_per_day: 0 });
+    client.hub.advance_queue_public(&owner, &table_id);
+    client.hub.vote_to_skip(&listener, &table_id);
+    env.as_contract(&fixture.links.hub, || {
+        assert!(!env.storage().persistent().has(&votes_key));
+        assert_eq!(env.storage().temporary().get_ttl(&votes_key), 600 / 5);
+    });
+}
+
+#[test]
+fn queue_entries_carry_the_request_ttl() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let ttl = client.hub.get_ttl_policy().requests;
+
+    let current = fixture.requests.get(0).unwrap();
+    client.hub.advance_queue_public(&owner, &table_id);
+    client.hub.vote_to_skip(&listener, &table_id);
+    env.as_contract(&fixture.links.hub, || {
+        let storage = env.storage().persistent();
+        assert_eq!(storage.get_ttl(&crate::QueueKey::Slot(table_id.clone(), 1)), ttl);
+        assert_eq!(storage.get_ttl(&crate::QueueKey::Start(table_id.clone())), ttl);
+        assert!(env.storage().temporary().has(&crate::VoteKey::SkipVotes(table_id.clone(), current.clone())));
+    });
+
+    // Moving on drops the finished request's votes instead of leaving them to rent
+    client.hub.advance_queue_public(&owner, &table_id);
+    env.as_contract(&fixture.links.hub, || {
+        assert!(!env.storage().temporary().has(&crate::VoteKey::SkipVotes(table_id.clone(), current)));
+    });
+}
+
+#[test]
+fn full_trending_bucket_evicts_its_least_played_track() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let newcomer = fixture.tracks.get(0).unwrap();
+    let quiet = BytesN::from_array(env, &[42; 32]);
+    // The community's TrendingKey::Buckets(day), as the tuple it encodes to
+    let key = (Symbol::new(env, "Buckets"), env.ledger().timestamp() / 86400);
+
+    env.as_contract(&fixture.links.community, || {
+        let mut bucket = Map::new(env);
+        for i in 0..200u8 {
+            bucket.set(BytesN::from_array(env, &[i; 32]), if i == 42 { 1u32 } else { 3 });
+        }
+        env.storage().temporary().set(&key, &bucket);
+    });
+
+    client.hub.request_track(&listener, &newcomer, &table_id);
+    env.as_contract(&fixture.links.community, || {
+        let bucket: Map<BytesN<32>, u32> = env.storage().temporary().get(&key).unwrap();
+        assert_eq!(bucket.len(), 200);
+        assert_eq!(bucket.get(newcomer.clone()), Some(1));
+        assert!(!bucket.contains_key(quiet.clone()));
+    });
+
+    // A track already in the bucket just counts up without evicting anything
+    client.hub.request_track(&listener, &newcomer, &table_id);
+    env.as_contract(&fixture.links.community, || {
+        let bucket: Map<BytesN<32>, u32> = env.storage().temporary().get(&key).unwrap();
+        assert_eq!(bucket.len(), 200);
+        assert_eq!(bucket.get(newcomer.clone()), Some(2));
+        assert!(!env.storage().instance().has(&key));
+        assert_eq!(env.storage().temporary().get_ttl(&key), 8 * 17_280);
+    });
+    assert_eq!(client.community.get_trending(&1).get(0).unwrap().plays, 3);
+}
+
+#[test]
+fn favorites_are_capped_per_user() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    client.catalog.favorite_track(&listener, &track_id);
+    client.community.favorite_table(&listener, &table_id);
+    assert!(client.catalog.try_favorite_track(&listener, &track_id).is_err());
+    assert_eq!(client.catalog.get_track(&track_id).unwrap().favorite_count, 1);
+    assert_eq!(client.community.get_favorite_tables(&listener, &0, &10), vec![env, table_id.clone()]);
+
+    let users_ttl = client.catalog.get_ttl_policy().users;
+    env.as_contract(&fixture.links.catalog, || {
+        // The catalog's FavoriteKey::Tracks(user), as the tuple it encodes to
+        let key = (Symbol::new(env, "Tracks"), listener.clone());
+        assert_eq!(env.storage().persistent().get_ttl(&key), users_ttl);
+
+        let mut full = Vec::new(env);
+        for i in 0..100u8 {
+            full.push_back(BytesN::from_array(env, &[i; 32]));
+        }
+        env.storage().persistent().set(&key, &full);
+    });
+    assert!(client.catalog.try_favorite_track(&listener, &fixture.tracks.get(1).unwrap()).is_err());
+
+    client.catalog.unfavorite_track(&listener, &BytesN::from_array(env, &[7; 32]));
+    client.catalog.favorite_track(&listener, &fixture.tracks.get(1).unwrap());
+    assert_eq!(client.catalog.get_favorite_tracks(&listener, &99, &10), vec![env, fixture.tracks.get(1).unwrap()]);
+}
+
+#[test]
+fn unfollowing_moves_the_last_follow_into_the_gap() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let listener = fixture.listeners.get(0).unwrap();
+
+    for artist in fixture.artists.iter() {
+        client.catalog.follow_artist(&listener, &artist);
+    }
+    let first = fixture.artists.get(0).unwrap();
+    assert!(client.catalog.try_follow_artist(&listener, &first).is_err());
+    assert_eq!(client.catalog.get_artist(&first).unwrap().follower_count, 1);
+
+    client.catalog.unfollow_artist(&listener, &first);
+    assert!(client.catalog.try_unfollow_artist(&listener, &first).is_err());
+    assert!(!client.catalog.is_following(&listener, &first));
+    assert_eq!(client.catalog.get_artist(&first).unwrap().follower_count, 0);
+    assert_eq!(client.catalog.get_following_count(&listener), 2);
+    assert_eq!(
+        client.catalog.get_following(&listener, &0, &10),
+        vec![env, fixture.artists.get(2).unwrap(), fixture.artists.get(1).unwrap()]
+    );
+
+    let users_ttl = client.catalog.get_ttl_policy().users;
+    env.as_contract(&fixture.links.catalog, || {
+        // The catalog's FollowKey::Slot(user, artist), as the tuple it encodes to
+        let key = (Symbol::new(env, "Slot"), listener.clone(), fixture.artists.get(1).unwrap());
+        assert_eq!(env.storage().persistent().get_ttl(&key), users_ttl);
+    });
+}
+
+#[test]
+fn session_check_ins_are_stored_per_attendee() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let session_id = client.community.start_session(&owner, &table_id, &3600);
+
+    for listener in fixture.listeners.iter() {
+        client.community.check_in(&listener, &table_id, &session_id);
+    }
+    let first = fixture.listeners.get(0).unwrap();
+    assert!(client.community.try_check_in(&first, &table_id, &session_id).is_err());
+    assert!(client.community.try_check_in(&fixture.artists.get(1).unwrap(), &table_id, &session_id).is_err());
+
+    assert_eq!(client.community.get_session(&table_id, &session_id).unwrap().attendee_count, 4);
+    let page = client.community.get_session_attendees(&table_id, &session_id, &1, &2);
+    assert_eq!(page, vec![env, fixture.listeners.get(1).unwrap(), fixture.listeners.get(2).unwrap()]);
+    assert_eq!(client.community.get_attendance_count(&first), 1);
+    assert_eq!(client.community.get_attendance_badges(&first, &0, &10).get(0).unwrap().session_id, session_id);
+
+    let users_ttl = client.community.get_ttl_policy().users;
+    env.as_contract(&fixture.links.community, || {
+        // The community's SessionKey::Attended(user, slot), as the tuple it encodes to
+        let key = (Symbol::new(env, "Attended"), first.clone(), 0u32);
+        assert_eq!(env.storage().persistent().get_ttl(&key), users_ttl);
+    });
+}
+
+#[test]
+fn ttl_policy_sets_entry_targets_per_class() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    assert!(client.hub.try_set_ttl_policy(&TtlPolicy { users: 90_000, requests: 0, history: 50_000 }).is_err());
+    // Each contract keeps its own policy for the entries it stores
+    let policy = TtlPolicy { users: 90_000, requests: 20_000, history: 50_000 };
+    client.hub.set_ttl_policy(&policy);
+    client.catalog.set_ttl_policy(&policy);
+    client.community.set_ttl_policy(&policy);
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    client.hub.advance_queue_public(&owner, &table_id);
+    let newcomer = Address::generate(env);
+    client.catalog.register_user(&newcomer, &String::from_str(env, "ipfs://newcomer"), &None);
+    env.as_contract(&fixture.links.hub, || {
+        let storage = env.storage().persistent();
+        assert_eq!(storage.get_ttl(&crate::QueueKey::Start(table_id.clone())), 20_000);
+    });
+    env.as_contract(&fixture.links.catalog, || {
+        // The catalog's DataKey::Users(user), as the tuple it encodes to
+        let key = (Symbol::new(env, "Users"), newcomer.clone());
+        assert_eq!(env.storage().persistent().get_ttl(&key), 90_000);
+    });
+    env.as_contract(&fixture.links.community, || {
+        let storage = env.storage().persistent();
+        // The community's HistoryKey::Plays(table, slot), as the tuple it encodes to
+        let history = (Symbol::new(env, "Plays"), table_id.clone(), 0u32);
+        assert!(!storage.has(&history));
+        assert_eq!(env.storage().temporary().get_ttl(&history), 50_000);
+    });
+    assert_eq!(client.community.get_play_history(&table_id, &0, &10).len(), 1);
+}
+
+#[test]
+fn exponential_price_curve_stops_at_the_cap() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+
+    client.catalog.set_feature_flag(&Symbol::new(env, "bonding_curve"), &true, &Vec::new(env));
+    client.catalog.set_price_curve(&artist, &track_id, &PriceCurve::Exponential(10000));
+
+    // Written back the way the market records a sale
+    let set_sold = |licenses_sold: u32| {
+        let mut track = client.catalog.get_track(&track_id).unwrap();
+        track.licenses_sold = licenses_sold;
+        client.catalog.save_track(&fixture.links.market, &track);
+    };
+    set_sold(3);
+    assert_eq!(client.catalog.get_current_price(&track_id), 8 * FIXTURE_TRACK_PRICE);
+    // Doubling 200 times would overflow; the price settles at the cap instead
+    set_sold(200);
+    assert_eq!(client.catalog.get_current_price(&track_id), 1_000 * FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn stale_requests_expire_with_refund() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+    let balance = token::Client::new(env, &fixture.token);
+
+    client.hub.set_request_ttl(&owner, &table_id, &3600);
+    assert_eq!(client.hub.expire_requests(&table_id), 0);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 3600);
+    let licenses = client.catalog.get_track(&track_id).unwrap().licenses_remaining;
+    let before = balance.balance(&requester);
+    assert_eq!(client.hub.expire_requests(&table_id), FIXTURE_REQUESTS_PER_TABLE);
+    assert!(client.hub.get_queue(&table_id).is_empty());
+    assert_eq!(balance.balance(&requester), before + FIXTURE_TRACK_PRICE);
+    assert_eq!(client.catalog.get_track(&track_id).unwrap().licenses_remaining, licenses + 1);
+}
+
+#[test]
+fn expired_credit_request_goes_back_to_credit() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let (giver, requester) = (fixture.listeners.get(1).unwrap(), fixture.listeners.get(0).unwrap());
+
+    play_out(&client.hub, &owner, &table_id);
+    client.venue.gift_credit(&giver, &requester, &FIXTURE_TRACK_PRICE);
+    client.hub.set_request_ttl(&owner, &table_id, &3600);
+    client.hub.request_track(&requester, &fixture.tracks.get(0).unwrap(), &table_id);
+    assert_eq!(client.hub.get_token_credit(&requester), 0);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 3600);
+    let revenue = client.payouts.get_claimable_revenue(&owner);
+    assert_eq!(client.hub.expire_requests(&table_id), 1);
+    assert_eq!(client.hub.get_token_credit(&requester), FIXTURE_TRACK_PRICE);
+    assert_eq!(client.payouts.get_claimable_revenue(&owner), revenue);
+}
+
+#[test]
+fn idle_table_deactivates_and_refunds() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+    let balance = token::Client::new(env, &fixture.token);
+
+    client.hub.set_idle_window(&(7 * 86400));
+    assert!(client.hub.try_deactivate_if_idle(&table_id).is_err());
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 7 * 86400);
+    let before = balance.balance(&requester);
+    client.hub.deactivate_if_idle(&table_id);
+    assert!(!client.hub.get_table(&table_id).unwrap().is_active);
+    assert!(client.hub.get_queue(&table_id).is_empty());
+    assert_eq!(balance.balance(&requester), before + FIXTURE_TRACK_PRICE);
+
+    let bars = client.hub.list_tables_by_category(&Symbol::new(env, fixtures::FIXTURE_TABLE_CATEGORY), &0, &10);
+    assert_eq!(bars, vec![env, fixture.tables.get(1).unwrap()]);
+}
+
+#[test]
+fn idle_table_refund_skips_played_requests() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+
+    client.hub.set_idle_window(&(7 * 86400));
+    client.hub.deposit(&requester, &(2 * FIXTURE_TRACK_PRICE));
+    play_out(&client.hub, &owner, &table_id);
+    client.hub.request_track(&requester, &fixture.tracks.get(0).unwrap(), &table_id);
+    client.hub.request_track(&requester, &fixture.tracks.get(3).unwrap(), &table_id);
+    client.hub.advance_queue_public(&owner, &table_id);
+    let revenue = client.payouts.get_claimable_revenue(&owner);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 7 * 86400);
+    client.hub.deactivate_if_idle(&table_id);
+    // Only the request still waiting comes back; the played one stays paid out
+    assert_eq!(client.hub.get_wallet_balance(&requester), FIXTURE_TRACK_PRICE);
+    assert_eq!(client.payouts.get_claimable_revenue(&owner), revenue);
+}
+
+#[test]
+fn wallet_pays_for_requests_and_withdraws_the_rest() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let balance = token::Client::new(env, &fixture.token);
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+
+    let before = balance.balance(&requester);
+    client.hub.deposit(&requester, &(5 * FIXTURE_TRACK_PRICE));
+    assert_eq!(balance.balance(&requester), before - 5 * FIXTURE_TRACK_PRICE);
+
+    // The request is drawn from the wallet, not the token account
+    client.hub.request_track(&requester, &fixture.tracks.get(0).unwrap(), &table_id);
+    assert_eq!(client.hub.get_wallet_balance(&requester), 4 * FIXTURE_TRACK_PRICE);
+    assert_eq!(balance.balance(&requester), before - 5 * FIXTURE_TRACK_PRICE);
+
+    assert!(client.hub.try_withdraw_balance(&requester, &(5 * FIXTURE_TRACK_PRICE)).is_err());
+    client.hub.withdraw_balance(&requester, &(4 * FIXTURE_TRACK_PRICE));
+    assert_eq!(client.hub.get_wallet_balance(&requester), 0);
+    assert_eq!(balance.balance(&requester), before - FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn sponsored_request_is_paid_by_the_sponsor() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let balance = token::Client::new(env, &fixture.token);
+    let table_id = fixture.tables.get(0).unwrap();
+    let sponsor = fixture.listeners.get(0).unwrap();
+    let beneficiary = fixture.listeners.get(1).unwrap();
+
+    let sponsor_before = balance.balance(&sponsor);
+    let beneficiary_before = balance.balance(&beneficiary);
+    let requests_before = client.community.get_user_request_count(&beneficiary);
+    let request_id = client.venue.sponsor_request(&sponsor, &beneficiary, &fixture.tracks.get(0).unwrap(), &table_id);
+
+    assert_eq!(balance.balance(&sponsor), sponsor_before - FIXTURE_TRACK_PRICE);
+    assert_eq!(balance.balance(&beneficiary), beneficiary_before);
+    // The request belongs to the beneficiary; the sponsor only pays for it
+    assert_eq!(client.community.get_user_request_count(&beneficiary), requests_before + 1);
+    assert_eq!(client.venue.get_request_sponsor(&request_id), Some(sponsor.clone()));
+    assert_eq!(client.venue.get_sponsor_spend(&sponsor), FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn kiosk_spends_up_to_its_cap_until_expiry() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let table_id = fixture.tables.get(0).unwrap();
+    let user = fixture.listeners.get(0).unwrap();
+    let kiosk = Address::generate(env);
+    let expires_at = env.ledger().timestamp() + 3600;
+
+    // Nothing prepaid means nothing the kiosk may spend
+    client.venue.authorize_kiosk(&user, &kiosk, &(2 * FIXTURE_TRACK_PRICE), &expires_at);
+    assert!(client.venue.try_request_track_delegated(&kiosk, &user, &fixture.tracks.get(0).unwrap(), &table_id).is_err());
+
+    client.hub.deposit(&user, &(5 * FIXTURE_TRACK_PRICE));
+    client.venue.request_track_delegated(&kiosk, &user, &fixture.tracks.get(0).unwrap(), &table_id);
+    client.venue.request_track_delegated(&kiosk, &user, &fixture.tracks.get(3).unwrap(), &table_id);
+    assert_eq!(client.venue.get_kiosk_delegation(&user, &kiosk).unwrap().spent, 2 * FIXTURE_TRACK_PRICE);
+    assert!(client.venue.try_request_track_delegated(&kiosk, &user, &fixture.tracks.get(1).unwrap(), &table_id).is_err());
+    assert_eq!(client.hub.get_wallet_balance(&user), 3 * FIXTURE_TRACK_PRICE);
+
+    client.venue.authorize_kiosk(&user, &kiosk, &(10 * FIXTURE_TRACK_PRICE), &expires_at);
+    env.ledger().with_mut(|ledger| ledger.timestamp = expires_at);
+    assert!(client.venue.try_request_track_delegated(&kiosk, &user, &fixture.tracks.get(1).unwrap(), &table_id).is_err());
+
+    client.venue.revoke_kiosk(&user, &kiosk);
+    assert!(client.venue.get_kiosk_delegation(&user, &kiosk).is_none());
+}
+
+#[test]
+fn registered_device_advances_its_table_until_revoked() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let device = Address::generate(env);
+
+    assert!(client.venue.try_register_device(&fixture.listeners.get(0).unwrap(), &table_id, &device, &String::from_str(env, "bar")).is_err());
+    client.venue.register_device(&owner, &table_id, &device, &String::from_str(env, "bar"));
+    assert_eq!(client.venue.get_table_devices(&table_id), vec![env, device.clone()]);
+    assert_eq!(client.venue.get_device(&device).unwrap().table_id, table_id);
+
+    assert!(client.venue.device_advance_queue(&device).is_some());
+    assert_eq!(client.hub.get_queue(&table_id).len(), FIXTURE_REQUESTS_PER_TABLE - 1);
+
+    client.venue.revoke_device(&owner, &device);
+    assert!(client.venue.get_table_devices(&table_id).is_empty());
+    assert!(client.venue.try_device_advance_queue(&device).is_err());
+}
+
+#[test]
+fn voucher_needs_a_valid_signature_and_redeems_once() {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let table_id = fixture.tables.get(0).unwrap();
+    let user = fixture.listeners.get(0).unwrap();
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let sign = |payload: &VoucherPayload| {
+        let mut message = fixture.links.venue.clone().to_xdr(env);
+        message.append(&payload.clone().to_xdr(env));
+        let mut raw = [0u8; 1024];
+        let raw = &mut raw[..message.len() as usize];
+        message.copy_into_slice(raw);
+        BytesN::from_array(env, &signing_key.sign(raw).to_bytes())
+    };
+
+    client.venue.set_voucher_key(&user, &BytesN::from_array(env, &signing_key.verifying_key().to_bytes()));
+    client.hub.deposit(&user, &(2 * FIXTURE_TRACK_PRICE));
+    let payload = VoucherPayload {
+        user: user.clone(),
+        track_id: fixture.tracks.get(0).unwrap(),
+        table_id: table_id.clone(),
+        max_price: FIXTURE_TRACK_PRICE,
+        nonce: 1,
+        expires_at: env.ledger().timestamp() + 3600,
+    };
+    let signature = sign(&payload);
+
+    // A signature over one payload doesn't cover a tampered one
+    let mut tampered = payload.clone();
+    tampered.track_id = fixture.tracks.get(3).unwrap();
+    assert!(client.venue.try_redeem_voucher(&signature, &tampered).is_err());
+
+    client.venue.redeem_voucher(&signature, &payload);
+    assert!(client.venue.is_nonce_used(&user, &1));
+    assert_eq!(client.hub.get_wallet_balance(&user), FIXTURE_TRACK_PRICE);
+    assert!(client.venue.try_redeem_voucher(&signature, &payload).is_err());
+}
+
+#[test]
+fn batch_request_is_all_or_nothing() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let balance = token::Client::new(env, &fixture.token);
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+    let queued = client.hub.get_queue(&table_id).len();
+
+    client.hub.deposit(&requester, &(FIXTURE_TRACK_PRICE / 2));
+    let before = balance.balance(&requester);
+
+    // One unknown track sinks the whole batch, including the top-up transfer
+    let bad = vec![env, fixture.tracks.get(0).unwrap(), BytesN::from_array(env, &[9; 32])];
+    assert!(client.hub.try_request_tracks(&requester, &table_id, &bad).is_err());
+    assert_eq!(balance.balance(&requester), before);
+    assert_eq!(client.hub.get_queue(&table_id).len(), queued);
+
+    let good = vec![env, fixture.tracks.get(0).unwrap(), fixture.tracks.get(3).unwrap()];
+    assert_eq!(client.hub.request_tracks(&requester, &table_id, &good).len(), 2);
+    // The wallet covers what it can and only the shortfall is pulled in
+    assert_eq!(balance.balance(&requester), before - (2 * FIXTURE_TRACK_PRICE - FIXTURE_TRACK_PRICE / 2));
+    assert_eq!(client.hub.get_wallet_balance(&requester), 0);
+    assert_eq!(client.hub.get_queue(&table_id).len(), queued + 2);
+}
+
+#[test]
+fn batch_mint_fills_in_defaults_per_track() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let collaborator = fixture.artists.get(1).unwrap();
+
+    let params = |title: &str, licenses: u32, royalty_split: Vec<(Address, u32)>| TrackMintParams {
+        title: String::from_str(env, title),
+        base_price: FIXTURE_TRACK_PRICE,
+        licenses,
+        metadata_uri: String::from_str(env, "ipfs://batch"),
+        collaborators: Vec::new(env),
+        royalty_split,
+    };
+    let default_split = vec![env, (artist.clone(), 100)];
+    let total = client.catalog.get_total_tracks();
+
+    // A bad split on any track rejects the whole batch
+    let bad = vec![env, params("One", 0, Vec::new(env)), params("Two", 5, vec![env, (collaborator.clone(), 40)])];
+    assert!(client.catalog.try_mint_tracks(&artist, &bad, &20, &default_split).is_err());
+    assert_eq!(client.catalog.get_total_tracks(), total);
+
+    let split = vec![env, (artist.clone(), 60), (collaborator.clone(), 40)];
+    let batch = vec![env, params("One", 0, Vec::new(env)), params("Two", 5, split.clone())];
+    let track_ids = client.catalog.mint_tracks(&artist, &batch, &20, &default_split);
+    assert_eq!(client.catalog.get_total_tracks(), total + 2);
+
+    let first = client.catalog.get_track(&track_ids.get(0).unwrap()).unwrap();
+    assert_eq!(first.licenses_remaining, 20);
+    assert_eq!(first.royalty_split, default_split);
+    let second = client.catalog.get_track(&track_ids.get(1).unwrap()).unwrap();
+    assert_eq!(second.licenses_remaining, 5);
+    assert_eq!(second.royalty_split, split);
+}
+
+#[test]
+fn bulk_getters_keep_order_and_mark_unknown_ids() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let unknown = BytesN::from_array(env, &[9; 32]);
+
+    let tracks = client.catalog.get_tracks(&vec![env, fixture.tracks.get(1).unwrap(), unknown.clone(), fixture.tracks.get(0).unwrap()]);
+    assert_eq!(tracks.len(), 3);
+    assert_eq!(tracks.get(0).unwrap().unwrap().track_id, fixture.tracks.get(1).unwrap());
+    assert!(tracks.get(1).unwrap().is_none());
+    assert_eq!(tracks.get(2).unwrap().unwrap().track_id, fixture.tracks.get(0).unwrap());
+
+    let tables = client.hub.get_tables(&vec![env, unknown, fixture.tables.get(0).unwrap()]);
+    assert!(tables.get(0).unwrap().is_none());
+    assert_eq!(tables.get(1).unwrap().unwrap().owner, fixture.artists.get(0).unwrap());
+
+    let users = client.catalog.get_users(&vec![env, fixture.listeners.get(0).unwrap(), Address::generate(env)]);
+    assert!(users.get(0).unwrap().is_some());
+    assert!(users.get(1).unwrap().is_none());
+
+    let mut too_many = Vec::new(env);
+    for _ in 0..51 {
+        too_many.push_back(fixture.listeners.get(0).unwrap());
+    }
+    assert!(client.catalog.try_get_users(&too_many).is_err());
+}
+
+#[test]
+fn content_hash_can_only_be_minted_once() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let content_hash = BytesN::from_array(env, &[4; 32]);
+    let mint = |artist: &Address| {
+        client.catalog.try_mint_track(
+            artist,
+            &String::from_str(env, "Fingerprinted"),
+            &FIXTURE_TRACK_PRICE,
+            &10,
+            &String::from_str(env, "ipfs://fingerprinted"),
+            &Vec::new(env),
+            &vec![env, (artist.clone(), 100)],
+            &None,
+            &Some(content_hash.clone()),
+            &Vec::new(env),
+        )
+    };
+
+    let track_id = mint(&fixture.artists.get(0).unwrap()).unwrap().unwrap();
+    assert_eq!(client.catalog.find_track_by_content_hash(&content_hash), Some(track_id.clone()));
+    assert_eq!(client.catalog.get_content_hash(&track_id), Some(content_hash.clone()));
+
+    // Neither the original artist nor anyone else can upload it again
+    assert!(mint(&fixture.artists.get(0).unwrap()).is_err());
+    assert!(mint(&fixture.artists.get(1).unwrap()).is_err());
+}
+
+#[test]
+fn extended_stats_track_volume_fees_and_royalties() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+
+    play_out(&client.hub, &owner, &table_id);
+    let before = client.catalog.get_extended_stats();
+    client.hub.request_track(&requester, &fixture.tracks.get(0).unwrap(), &table_id);
+    let queued = client.catalog.get_extended_stats();
+    assert_eq!(queued.total_requests, before.total_requests + 1);
+    assert_eq!(queued.total_volume, before.total_volume + FIXTURE_TRACK_PRICE);
+    // Nothing is split until the request plays
+    assert_eq!(queued.total_fees, before.total_fees);
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    let played = client.catalog.get_extended_stats();
+    let fee = FIXTURE_TRACK_PRICE * 5 / 100;
+    assert_eq!(played.total_fees, before.total_fees + fee);
+    assert_eq!(played.total_royalties, before.total_royalties + FIXTURE_TRACK_PRICE - fee);
+}
+
+#[test]
+fn artist_directory_pages_in_registration_order() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let count = fixtures::FIXTURE_ARTISTS;
+
+    assert_eq!(client.catalog.list_artists(&0, &count), fixture.artists);
+    assert_eq!(client.catalog.list_artists(&0, &1), vec![env, fixture.artists.get(0).unwrap()]);
+    let rest = client.catalog.list_artists(&1, &100);
+    assert_eq!(rest.len(), count - 1);
+    assert_eq!(rest.get(0), fixture.artists.get(1));
+    assert!(client.catalog.list_artists(&count, &10).is_empty());
+    assert!(client.catalog.list_artists(&u32::MAX, &u32::MAX).is_empty());
+}
+
+#[test]
+fn every_event_carries_the_schema_version() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    let versions = [
+        (client.hub.get_version(), client.hub.get_event_schema_version()),
+        (client.catalog.get_version(), client.catalog.get_event_schema_version()),
+        (client.payouts.get_version(), client.payouts.get_event_schema_version()),
+        (client.market.get_version(), client.market.get_event_schema_version()),
+        (client.community.get_version(), client.community.get_event_schema_version()),
+        (client.venue.get_version(), client.venue.get_event_schema_version()),
+        (client.governance.get_version(), client.governance.get_event_schema_version()),
+    ];
+    for version in versions {
+        assert_eq!(version, ((0, 1, 0), EVENT_SCHEMA_VERSION));
+    }
+
+    client.hub.request_track(&fixture.listeners.get(0).unwrap(), &fixture.tracks.get(0).unwrap(), &table_id);
+    play_out(&client.hub, &owner, &table_id);
+    let events = env.events().all();
+    let links = &fixture.links;
+    let deployment = [&links.catalog, &links.hub, &links.payouts, &links.market, &links.community, &links.venue, &links.governance];
+    let ours = events.iter().filter(|(contract, _, _)| deployment.contains(&contract));
+    let mut seen = 0;
+    for (_, topics, _) in ours {
+        assert_eq!(u32::try_from_val(env, &topics.get(1).unwrap()).unwrap(), EVENT_SCHEMA_VERSION);
+        seen += 1;
+    }
+    assert!(seen > 0);
+}
+
+#[test]
+fn platform_boards_rerank_as_tracks_overtake() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+    let (first, second) = (fixture.tracks.get(0).unwrap(), fixture.tracks.get(3).unwrap());
+    let request = |track_id: &BytesN<32>, times: u32| {
+        for _ in 0..times {
+            client.hub.request_track(&requester, track_id, &table_id);
+            play_out(&client.hub, &owner, &table_id);
+        }
+    };
+
+    request(&first, 2);
+    request(&second, 1);
+    let board = client.community.get_top_tracks(&10);
+    assert!(board.get(0).unwrap().subject == LeaderboardSubject::Track(first.clone()));
+    assert!(board.get(1).unwrap().subject == LeaderboardSubject::Track(second.clone()));
+
+    // Overtaking moves the track up rather than adding a second entry
+    request(&second, 2);
+    let board = client.community.get_top_tracks(&10);
+    assert_eq!(board.iter().filter(|entry| entry.subject == LeaderboardSubject::Track(second.clone())).count(), 1);
+    assert!(board.get(0).unwrap().subject == LeaderboardSubject::Track(second.clone()));
+    assert_eq!(board.get(0).unwrap().score, client.catalog.get_track(&second).unwrap().play_count as i128);
+    assert_eq!(client.community.get_top_tracks(&1).len(), 1);
+
+    let artists = client.community.get_top_artists(&10);
+    for i in 1..artists.len() {
+        assert!(artists.get(i - 1).unwrap().score >= artists.get(i).unwrap().score);
+    }
+    assert!(client.community.get_top_artists(&0).is_empty());
+}
+
+#[test]
+fn track_earnings_count_paid_plays_only() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+
+    play_out(&client.hub, &owner, &table_id);
+    let before = client.payouts.get_track_earnings(&track_id);
+    client.hub.request_track(&requester, &track_id, &table_id);
+    play_out(&client.hub, &owner, &table_id);
+    let paid = client.payouts.get_track_earnings(&track_id);
+    assert_eq!(paid.gross_revenue, before.gross_revenue + FIXTURE_TRACK_PRICE);
+    assert_eq!(paid.platform_fees_paid, before.platform_fees_paid + FIXTURE_TRACK_PRICE * 5 / 100);
+    assert_eq!(paid.plays_paid, before.plays_paid + 1);
+
+    client.hub.credit_requests(&fixture.links.venue, &requester, &1);
+    client.hub.request_track(&requester, &track_id, &table_id);
+    play_out(&client.hub, &owner, &table_id);
+    let after_free = client.payouts.get_track_earnings(&track_id);
+    assert_eq!(after_free.gross_revenue, paid.gross_revenue);
+    assert_eq!(after_free.plays_paid, paid.plays_paid);
+}
+
+#[test]
+fn free_allowance_skips_payment_until_used_up() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let token = token::Client::new(env, &fixture.token);
+
+    assert!(client.hub.try_set_free_request_allowance(&listener, &table_id, &2).is_err());
+    client.hub.set_free_request_allowance(&owner, &table_id, &2);
+
+    let balance = token.balance(&listener);
+    let licenses = client.catalog.get_track(&track_id).unwrap().licenses_remaining;
+    let requests = client.community.get_user_request_count(&listener);
+    client.hub.request_track(&listener, &track_id, &table_id);
+    client.hub.request_track(&listener, &track_id, &table_id);
+    assert_eq!(token.balance(&listener), balance);
+    assert_eq!(client.catalog.get_track(&track_id).unwrap().licenses_remaining, licenses - 2);
+    assert_eq!(client.community.get_user_request_count(&listener), requests + 2);
+    assert_eq!(client.hub.get_free_requests_remaining(&listener, &table_id), 0);
+
+    client.hub.request_track(&listener, &track_id, &table_id);
+    assert_eq!(token.balance(&listener), balance - FIXTURE_TRACK_PRICE);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 86400);
+    assert_eq!(client.hub.get_free_requests_remaining(&listener, &table_id), 2);
+}
+
+#[test]
+fn skip_fees_compensate_the_skipped_requester_and_artist() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let requester = fixture.listeners.get(0).unwrap();
+    let first = fixture.listeners.get(1).unwrap();
+    let second = fixture.listeners.get(2).unwrap();
+    let latecomer = fixture.listeners.get(3).unwrap();
+    let token = token::Client::new(env, &fixture.token);
+
+    assert!(client.hub.try_set_skip_fee(&owner, &table_id, &100, &10_001).is_err());
+    client.hub.set_skip_fee(&owner, &table_id, &100, &4_000);
+    // The first request is listener 0's play of the owner's own track
+    client.hub.advance_queue_public(&owner, &table_id);
+
+    let before = (token.balance(&requester), token.balance(&owner), token.balance(&first), token.balance(&second));
+    assert!(!client.hub.vote_to_skip(&first, &table_id));
+    assert_eq!(token.balance(&first), before.2 - 100);
+    assert!(client.hub.vote_to_skip(&second, &table_id));
+    assert_eq!(token.balance(&requester), before.0 + 80);
+    assert_eq!(token.balance(&owner), before.1 + 120);
+    assert_eq!(token.balance(&second), before.3 - 100);
+
+    // A vote short of the threshold is handed back once the track moves on
+    let balance = token.balance(&latecomer);
+    client.hub.vote_to_skip(&latecomer, &table_id);
+    assert_eq!(token.balance(&latecomer), balance - 100);
+    client.hub.advance_queue_public(&owner, &table_id);
+    assert_eq!(token.balance(&latecomer), balance);
+}
+
+#[test]
+fn happy_hour_discounts_requests_inside_its_window() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(5).unwrap();
+    let token = token::Client::new(env, &fixture.token);
+
+    assert!(client.hub.try_set_happy_hour(&owner, &table_id, &22, &22, &5_000).is_err());
+    // The fixture ledger sits at 22:13 UTC, inside a window wrapping past midnight
+    client.hub.set_happy_hour(&owner, &table_id, &22, &2, &5_000);
+
+    let balance = token.balance(&listener);
+    client.hub.request_track(&listener, &track_id, &table_id);
+    assert_eq!(token.balance(&listener), balance - FIXTURE_TRACK_PRICE / 2);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 5 * 3600);
+    let balance = token.balance(&listener);
+    client.hub.request_track(&listener, &track_id, &table_id);
+    assert_eq!(token.balance(&listener), balance - FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn deployment_profile_sets_table_defaults_and_features() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(2).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    assert!(client.hub.get_profile().features & FEATURE_HAPPY_HOUR != 0);
+
+    let mut profile = fixtures::default_profile(env);
+    profile.default_skip_fee = -1;
+    let other = env.register(MetaJuke, ());
+    let other_client = MetaJukeClient::new(env, &other);
+    assert!(other_client.try_initialize(&fixture.admin, &fixture.token, &profile, &fixture.links).is_err());
+
+    // Whitelabel deployment without happy hours, queues capped one past the fixture's
+    let profile = DeploymentProfile {
+        branding: Symbol::new(env, "BarBox"),
+        default_free_requests: 2,
+        default_skip_fee: 50,
+        max_queue_length: FIXTURE_REQUESTS_PER_TABLE + 1,
+        features: FEATURE_FREE_REQUESTS | FEATURE_SKIP_FEES,
+    };
+    env.as_contract(&fixture.links.hub, || {
+        env.storage().instance().set(&DataKey::Profile, &profile);
+    });
+    assert_eq!(client.hub.get_profile().branding, Symbol::new(env, "BarBox"));
+
+    let new_table = client.hub.create_table(&owner, &String::from_str(env, "Whitelabel"), &2, &10000, &Symbol::new(env, "bar"));
+    let table = client.hub.get_table(&new_table).unwrap();
+    assert_eq!(table.free_requests_per_day, 2);
+    assert_eq!(table.skip_fee, 50);
+    assert!(client.hub.try_set_happy_hour(&owner, &new_table, &22, &2, &5_000).is_err());
+
+    client.hub.request_track(&listener, &fixture.tracks.get(5).unwrap(), &table_id);
+    assert!(client.hub.try_request_track(&listener, &fixture.tracks.get(6).unwrap(), &table_id).is_err());
+}
+
+#[test]
+fn feature_flags_gate_rollouts_until_enabled() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let flag = Symbol::new(env, "auctions");
+
+    assert!(!client.catalog.is_flag_enabled(&flag));
+    assert!(client.market.try_create_license_auction(&artist, &track_id, &1, &false, &10_000, &2_000, &500).is_err());
+    assert!(client.catalog.try_remove_feature_flag(&flag).is_err());
+
+    client.catalog.set_feature_flag(&flag, &true, &vec![env, 25]);
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, fixture.links.catalog);
+    assert_eq!(topics, (Symbol::new(env, "feature_flag_changed"), EVENT_SCHEMA_VERSION, flag.clone()).into_val(env));
+    assert!(bool::try_from_val(env, &data).unwrap());
+    assert_eq!(client.catalog.get_feature_flag(&flag).unwrap().params, vec![env, 25]);
+    client.market.create_license_auction(&artist, &track_id, &1, &false, &10_000, &2_000, &500);
+
+    client.catalog.remove_feature_flag(&flag);
+    assert!(client.catalog.get_feature_flags().is_empty());
+    assert!(client.market.try_create_license_auction(&artist, &track_id, &1, &false, &10_000, &2_000, &500).is_err());
+}
+
+#[test]
+fn legacy_import_is_idempotent_and_pausable() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let user = Address::generate(env);
+    let track_id = fixtures::fixture_id(env, b"legacy_track", 0);
+    let entries = vec![
+        env,
+        ImportEntry::User(LegacyUser {
+            user: user.clone(),
+            profile_nft: Address::generate(env),
+            avatar_uri: String::from_str(env, "ipfs://legacy-avatar"),
+            reputation: 42,
+        }),
+        ImportEntry::Artist(LegacyArtist {
+            user: user.clone(),
+            artist_name: String::from_str(env, "Old Guard"),
+            verified: true,
+        }),
+        ImportEntry::Track(LegacyTrack {
+            track_id: track_id.clone(),
+            track_nft: Address::generate(env),
+            title: String::from_str(env, "Beta Anthem"),
+            artist_id: user.clone(),
+            collaborators: Vec::new(env),
+            play_count: 7,
+            base_price: FIXTURE_TRACK_PRICE,
+            licenses_remaining: 10,
+            metadata_uri: String::from_str(env, "ipfs://legacy-track"),
+            royalty_split: vec![env, (user.clone(), 100u32)],
+        }),
+    ];
+
+    assert_eq!(client.catalog.import_legacy(&entries), 3);
+    assert_eq!(client.catalog.get_user(&user).unwrap().reputation, 42);
+    assert!(client.catalog.get_artist(&user).unwrap().verified);
+    assert_eq!(client.catalog.get_track(&track_id).unwrap().play_count, 7);
+    assert_eq!(client.catalog.get_artist_tracks(&user), vec![env, track_id]);
+
+    // Resubmitting the batch skips everything already imported
+    assert_eq!(client.catalog.import_legacy(&entries), 0);
+
+    client.catalog.set_import_paused(&true);
+    assert!(client.catalog.try_import_legacy(&entries).is_err());
+    client.catalog.set_import_paused(&false);
+    client.catalog.finish_import();
+    assert!(client.catalog.try_import_legacy(&entries).is_err());
+    assert!(client.catalog.try_set_import_paused(&false).is_err());
+}
+
+#[test]
+fn insured_requests_are_compensated_when_the_table_closes() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let listener = fixture.listeners.get(3).unwrap();
+    let track_id = fixture.tracks.get(5).unwrap();
+
+    assert!(client.hub.try_request_track_insured(&listener, &track_id, &table_id).is_err());
+    assert!(client.market.try_set_insurance_terms(&1_001, &1_000).is_err());
+    client.market.set_insurance_terms(&1_000, &1_000);
+
+    let balance = token.balance(&listener);
+    let request_id = client.hub.request_track_insured(&listener, &track_id, &table_id);
+    client.hub.request_track_insured(&listener, &track_id, &table_id);
+    assert_eq!(token.balance(&listener), balance - 2 * (FIXTURE_TRACK_PRICE + 100));
+    assert_eq!(client.market.get_insurance_pool(), 200);
+    assert_eq!(client.market.get_request_coverage(&request_id), 100);
+
+    // Closing refunds the plays and pays each claim out of the pool
+    client.hub.set_table_status(&owner, &table_id, &false);
+    assert_eq!(token.balance(&listener), balance);
+    assert_eq!(client.market.get_insurance_pool(), 0);
+    assert_eq!(client.market.get_request_coverage(&request_id), 0);
+}
+
+#[test]
+fn tour_takeovers_sell_at_the_promo_price_across_venues() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let artist = fixture.artists.get(1).unwrap();
+    let listener = fixture.listeners.get(3).unwrap();
+    let track_id = fixture.tracks.get(4).unwrap();
+    let now = env.ledger().timestamp();
+    let mut stops = Vec::new(env);
+    for (i, table_id) in fixture.tables.iter().enumerate() {
+        client.market.set_accepts_takeovers(&fixture.artists.get(i as u32).unwrap(), &table_id, &true);
+        stops.push_back(TourStop { table_id, starts_at: now, ends_at: now + 3600 });
+    }
+    let name = String::from_str(env, "Night Drive Tour");
+    client.catalog.set_feature_flag(&Symbol::new(env, "tours"), &true, &Vec::new(env));
+
+    assert!(client.market.try_book_tour(&artist, &name, &300, &stops).is_err());
+    client.governance.set_artist_verified(&artist, &true);
+    let tour_id = client.market.book_tour(&artist, &name, &300, &stops);
+    assert_eq!(client.market.get_table_takeovers(&fixture.tables.get(1).unwrap()).len(), 1);
+    // The slots are taken for the whole window
+    assert!(client.market.try_book_tour(&artist, &name, &300, &stops).is_err());
+
+    for table_id in fixture.tables.iter() {
+        let balance = token.balance(&listener);
+        client.hub.request_track(&listener, &track_id, &table_id);
+        assert_eq!(token.balance(&listener), balance - 300);
+    }
+    let tour = client.market.get_tour(&tour_id).unwrap();
+    assert_eq!((tour.total_plays, tour.total_revenue), (2, 600));
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 3600);
+    let balance = token.balance(&listener);
+    client.hub.request_track(&listener, &track_id, &fixture.tables.get(0).unwrap());
+    assert_eq!(token.balance(&listener), balance - FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn entry_badges_admit_badge_holders_to_a_table() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let issuer = Address::generate(env);
+    let newcomer = Address::generate(env);
+    let regular = Symbol::new(env, "regular");
+    client.catalog.register_user(&newcomer, &String::from_str(env, "ipfs://newcomer"), &None);
+
+    client.community.set_entry_badges(&owner, &table_id, &vec![env, Symbol::new(env, "founder"), regular.clone()]);
+    assert!(client.hub.try_join_table(&newcomer, &table_id).is_err());
+    assert!(client.community.try_award_badge(&issuer, &newcomer, &regular).is_err());
+
+    client.community.set_badge_issuer(&issuer, &true);
+    client.community.award_badge(&issuer, &newcomer, &regular);
+    client.hub.join_table(&newcomer, &table_id);
+    assert!(client.hub.is_table_member(&newcomer, &table_id));
+}
+
+#[test]
+fn time_license_covers_requests_until_it_expires() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let artist = fixture.artists.get(2).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(2).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let now = env.ledger().timestamp();
+
+    assert!(client.market.try_renew_license(&listener, &track_id).is_err());
+    client.market.set_time_license_terms(&artist, &track_id, &5_000, &3600);
+    assert_eq!(client.market.renew_license(&listener, &track_id), now + 3600);
+    // Renewing early extends from the current expiry
+    assert_eq!(client.market.renew_license(&listener, &track_id), now + 7200);
+    assert!(client.market.is_license_valid(&listener, &track_id));
+
+    let balance = token.balance(&listener);
+    client.hub.request_track(&listener, &track_id, &table_id);
+    assert_eq!(token.balance(&listener), balance);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp = now + 7200);
+    assert!(!client.market.is_license_valid(&listener, &track_id));
+    client.hub.request_track(&listener, &track_id, &table_id);
+    assert_eq!(token.balance(&listener), balance - FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn album_mints_its_tracks_together_and_sells_at_the_bundle_discount() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let artist = fixture.artists.get(1).unwrap();
+    let listener = fixture.listeners.get(3).unwrap();
+    let table_id = fixture.tables.get(1).unwrap();
+    let params = |title: &str, royalty_split: Vec<(Address, u32)>| TrackMintParams {
+        title: String::from_str(env, title),
+        base_price: FIXTURE_TRACK_PRICE,
+        licenses: 10,
+        metadata_uri: String::from_str(env, "ipfs://album-track"),
+        collaborators: Vec::new(env),
+        royalty_split,
+    };
+    let title = String::from_str(env, "Low Tide");
+    let uri = String::from_str(env, "ipfs://album");
+    let total = client.catalog.get_total_tracks();
+
+    // A bad split on any track rejects the whole album
+    let bad = vec![env, params("Side A", vec![env, (artist.clone(), 100)]), params("Side B", vec![env, (artist.clone(), 60)])];
+    assert!(client.venue.try_mint_album(&artist, &title, &uri, &2_000, &bad).is_err());
+    assert_eq!(client.catalog.get_total_tracks(), total);
+
+    let good = vec![env, params("Side A", vec![env, (artist.clone(), 100)]), params("Side B", vec![env, (artist.clone(), 100)])];
+    let track_ids = client.venue.mint_album(&artist, &title, &uri, &2_000, &good);
+    let (_, _, data) = env.events().all().last().unwrap();
+    assert_eq!(u32::try_from_val(env, &data).unwrap(), 2);
+    assert_eq!(client.catalog.get_total_tracks(), total + 2);
+    let album_id = client.venue.get_track_album(&track_ids.get(0).unwrap()).unwrap();
+    assert_eq!(client.venue.get_album(&album_id).unwrap().track_ids, track_ids);
+
+    let balance = token.balance(&listener);
+    assert_eq!(client.venue.request_album(&listener, &album_id, &table_id).len(), 2);
+    assert_eq!(token.balance(&listener), balance - 2 * FIXTURE_TRACK_PRICE * 8 / 10);
+}
+
+#[test]
+fn revenue_withdraws_in_the_artists_payout_token() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let usdc = env.register_stellar_asset_contract_v2(fixture.admin.clone()).address();
+    let router = env.register(MockRouter, ());
+    let router_client = MockRouterClient::new(env, &router);
+    StellarAssetClient::new(env, &usdc).mint(&router, &10_000);
+    client.payouts.set_swap_router(&router);
+    play_out(&client.hub, &artist, &table_id);
+    let revenue = client.payouts.get_claimable_revenue(&artist);
+    assert!(revenue > 0);
+
+    assert!(client.payouts.try_withdraw_revenue_swapped(&artist, &revenue).is_err());
+    client.payouts.set_payout_token(&artist, &Some(usdc.clone()));
+
+    // A fill short of the bound reverts and leaves the balance to withdraw later
+    router_client.set_rate_bps(&9_000);
+    assert!(client.payouts.try_withdraw_revenue_swapped(&artist, &revenue).is_err());
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), revenue);
+
+    assert_eq!(client.payouts.withdraw_revenue_swapped(&artist, &(revenue * 9 / 10)), revenue * 9 / 10);
+    assert_eq!(token::Client::new(env, &usdc).balance(&artist), revenue * 9 / 10);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), 0);
+}
+
+#[test]
+fn playlist_queues_every_track_or_none() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let curator = fixture.listeners.get(0).unwrap();
+    let listener = fixture.listeners.get(3).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let tracks = |ids: &[u32]| {
+        let mut track_ids = Vec::new(env);
+        for id in ids {
+            track_ids.push_back(fixture.tracks.get(*id).unwrap());
+        }
+        track_ids
+    };
+    let name = String::from_str(env, "Closing Set");
+
+    let playlist_id = client.venue.create_playlist(&curator, &name, &tracks(&[5, 6]));
+    assert!(client.venue.try_update_playlist(&listener, &playlist_id, &name, &tracks(&[5])).is_err());
+    client.venue.update_playlist(&curator, &playlist_id, &name, &tracks(&[5, 6, 7]));
+    assert_eq!(client.venue.get_playlist(&playlist_id).unwrap().track_ids, tracks(&[5, 6, 7]));
+
+    let queued = client.hub.get_queue(&table_id).len();
+    let balance = token.balance(&listener);
+    assert_eq!(client.venue.request_playlist(&listener, &playlist_id, &table_id).len(), 3);
+    assert_eq!(token.balance(&listener), balance - 3 * FIXTURE_TRACK_PRICE);
+    assert_eq!(client.hub.get_queue(&table_id).len(), queued + 3);
+
+    // Two free slots can't take the whole set, so nothing is queued or charged
+    let mut profile = fixtures::default_profile(env);
+    profile.max_queue_length = queued + 5;
+    env.as_contract(&fixture.links.hub, || {
+        env.storage().instance().set(&DataKey::Profile, &profile);
+    });
+    assert!(client.venue.try_request_playlist(&listener, &playlist_id, &table_id).is_err());
+    assert_eq!(token.balance(&listener), balance - 3 * FIXTURE_TRACK_PRICE);
+    assert_eq!(client.hub.get_queue(&table_id).len(), queued + 3);
+}
+
+#[test]
+fn outbox_keeps_entries_until_a_relayer_acknowledges_them() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let relayer = Address::generate(env);
+    let kind = Symbol::new(env, "lights");
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    client.community.post_outbox(&owner, &table_id, &kind, &Bytes::from_slice(env, b"dim"));
+    let outbox = client.community.get_outbox(&table_id, &10);
+    assert_eq!(outbox.len(), 2);
+    match outbox.get(0).unwrap().message {
+        OutboxMessage::TrackStarted(track_id, request_id) => {
+            assert_eq!(track_id, fixture.tracks.get(0).unwrap());
+            assert_eq!(request_id, fixture.requests.get(0).unwrap());
+        }
+        _ => panic!("expected the started track first"),
+    }
+
+    assert!(client.community.try_ack_outbox(&relayer, &table_id, &0).is_err());
+    client.community.set_outbox_relayer(&owner, &table_id, &relayer, &true);
+    assert!(client.community.try_ack_outbox(&relayer, &table_id, &2).is_err());
+
+    client.community.ack_outbox(&relayer, &table_id, &0);
+    let outbox = client.community.get_outbox(&table_id, &10);
+    assert_eq!(outbox.len(), 1);
+    assert_eq!(outbox.get(0).unwrap().seq, 1);
+    assert!(matches!(outbox.get(0).unwrap().message, OutboxMessage::Custom(posted, _) if posted == kind));
+}
+
+#[test]
+fn collaborators_cosign_before_a_track_is_requestable() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let (featured, producer) = (fixture.artists.get(1).unwrap(), fixture.artists.get(2).unwrap());
+    let listener = fixture.listeners.get(3).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+
+    let track_id = client.catalog.mint_track(
+        &artist,
+        &String::from_str(env, "Duet"),
+        &FIXTURE_TRACK_PRICE,
+        &10,
+        &String::from_str(env, "ipfs://duet"),
+        &vec![env, featured.clone()],
+        &vec![env, (artist.clone(), 70u32), (producer.clone(), 30u32)],
+        &None,
+        &None,
+        &Vec::new(env),
+    );
+    assert_eq!(client.catalog.get_pending_collaborators(&track_id), vec![env, featured.clone(), producer.clone()]);
+    assert!(client.catalog.try_accept_collaboration(&listener, &track_id).is_err());
+
+    client.catalog.accept_collaboration(&featured, &track_id);
+    assert!(client.hub.try_request_track(&listener, &track_id, &table_id).is_err());
+
+    client.catalog.accept_collaboration(&producer, &track_id);
+    assert!(client.catalog.get_pending_collaborators(&track_id).is_empty());
+    client.hub.request_track(&listener, &track_id, &table_id);
+}
+
+#[test]
+fn split_change_applies_once_every_recipient_approves() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let newcomer = fixture.artists.get(1).unwrap();
+    let listener = fixture.listeners.get(0).unwrap();
+    let track_id = fixture.tracks.get(0).unwrap();
+    let old_split = vec![env, (artist.clone(), 100u32)];
+    let new_split = vec![env, (artist.clone(), 60u32), (newcomer.clone(), 40u32)];
+
+    assert!(client.payouts.try_propose_split_change(&artist, &track_id, &vec![env, (artist.clone(), 90u32)]).is_err());
+    client.payouts.propose_split_change(&artist, &track_id, &new_split);
+    assert!(client.payouts.try_approve_split_change(&listener, &track_id).is_err());
+    assert_eq!(client.catalog.get_track(&track_id).unwrap().royalty_split, old_split);
+
+    client.payouts.approve_split_change(&newcomer, &track_id);
+    assert!(client.payouts.get_split_proposal(&track_id).is_none());
+    let track = client.catalog.get_track(&track_id).unwrap();
+    assert_eq!(track.royalty_split, new_split);
+    assert_eq!(track.split_history.len(), 1);
+    assert_eq!(track.split_history.get(0).unwrap().split, old_split);
+}
+
+#[test]
+fn partial_withdrawal_sweeps_to_another_wallet() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let artist = fixture.artists.get(0).unwrap();
+    let cold_wallet = Address::generate(env);
+    play_out(&client.hub, &artist, &fixture.tables.get(0).unwrap());
+    let revenue = client.payouts.get_claimable_revenue(&artist);
+
+    assert!(client.payouts.try_withdraw_revenue_amount(&artist, &(revenue + 1), &cold_wallet).is_err());
+    assert!(client.payouts.try_withdraw_revenue_amount(&artist, &0, &cold_wallet).is_err());
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), revenue);
+
+    assert_eq!(client.payouts.withdraw_revenue_amount(&artist, &100, &cold_wallet), revenue - 100);
+    assert_eq!(token.balance(&cold_wallet), 100);
+    assert_eq!(client.payouts.get_claimable_revenue(&artist), revenue - 100);
+}
+
+#[test]
+fn treasury_distributes_fees_to_its_targets() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let ops = Address::generate(env);
+    play_out(&client.hub, &fixture.artists.get(0).unwrap(), &fixture.tables.get(0).unwrap());
+    let balance = client.payouts.get_treasury_balance();
+    assert_eq!(balance, 3 * FIXTURE_TRACK_PRICE * FIXTURE_PLATFORM_FEE as i128 / 10000);
+
+    assert!(client.payouts.try_distribute_treasury().is_err());
+    assert!(client.governance.try_set_treasury_targets(&vec![env, (TreasuryTarget::Burn, 9_000u32)]).is_err());
+    client.governance.set_treasury_targets(&vec![
+        env,
+        (TreasuryTarget::Wallet(ops.clone()), 5_000u32),
+        (TreasuryTarget::GrantPool, 3_000u32),
+        (TreasuryTarget::Burn, 2_000u32),
+    ]);
+
+    let held = token.balance(&fixture.links.payouts);
+    assert_eq!(client.payouts.distribute_treasury(), balance);
+    assert_eq!(token.balance(&ops), balance / 2);
+    assert_eq!(client.payouts.get_grant_pool(), balance * 3 / 10);
+    // The grant pool stays on payouts and the burnt share leaves supply
+    assert_eq!(token.balance(&fixture.links.payouts), held - balance / 2 - balance / 5);
+    assert_eq!(client.payouts.get_treasury_balance(), 0);
+}
+
+#[test]
+fn fee_tiers_lower_the_fee_for_high_volume_artists() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(3).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let tier = |min_volume: u32, fee_bps: u32| FeeTier { min_volume, fee_bps };
+
+    assert!(client.governance.try_set_fee_tiers(&vec![env, tier(5, 300), tier(1, 100)]).is_err());
+    let change_id = client.governance.set_fee_tiers(&vec![env, tier(1, 100)]);
+    env.ledger().with_mut(|ledger| ledger.timestamp += client.governance.get_timelock_delay());
+    client.governance.execute_param_change(&change_id);
+
+    // No artist has a paid play yet, so each pays the full platform fee
+    play_out(&client.hub, &artist, &table_id);
+    let fee = FIXTURE_TRACK_PRICE * FIXTURE_PLATFORM_FEE as i128 / 10000;
+    assert_eq!(client.payouts.get_treasury_balance(), 3 * fee);
+    assert_eq!(client.payouts.get_artist_volume(&artist), 1);
+
+    client.hub.request_track(&listener, &fixture.tracks.get(3).unwrap(), &table_id);
+    play_out(&client.hub, &artist, &table_id);
+    assert_eq!(client.payouts.get_treasury_balance(), 3 * fee + FIXTURE_TRACK_PRICE / 100);
+}
+
+#[test]
+fn pause_halts_only_the_paused_scopes() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let listener = fixture.listeners.get(3).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let track_id = fixture.tracks.get(3).unwrap();
+    let newcomer = Address::generate(env);
+    client.catalog.register_user(&newcomer, &String::from_str(env, "ipfs://newcomer"), &None);
+    play_out(&client.hub, &artist, &table_id);
+
+    client.catalog.pause(&(PAUSE_REQUESTS | PAUSE_WITHDRAWALS));
+    assert!(client.hub.try_request_track(&listener, &track_id, &table_id).is_err());
+    assert!(client.payouts.try_withdraw_revenue_amount(&artist, &1, &artist).is_err());
+    client.hub.join_table(&newcomer, &table_id);
+
+    client.catalog.unpause(&PAUSE_WITHDRAWALS);
+    assert_eq!(client.catalog.get_paused(), PAUSE_REQUESTS);
+    client.payouts.withdraw_revenue_amount(&artist, &1, &artist);
+    assert!(client.hub.try_request_track(&listener, &track_id, &table_id).is_err());
+}
+
+#[test]
+fn suspended_accounts_are_locked_out_until_reinstated() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let artist = fixture.artists.get(2).unwrap();
+    let listener = fixture.listeners.get(3).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let track_id = fixture.tracks.get(3).unwrap();
+    let reason = BytesN::from_array(env, &[7; 32]);
+
+    assert!(client.catalog.try_suspend_user(&Address::generate(env), &reason).is_err());
+    client.catalog.suspend_user(&listener, &reason);
+    client.catalog.suspend_user(&artist, &reason);
+    assert!(!client.catalog.get_user(&listener).unwrap().is_active);
+
+    client.hub.advance_queue_public(&owner, &table_id);
+    assert!(client.hub.try_request_track(&listener, &track_id, &table_id).is_err());
+    assert!(client.hub.try_vote_to_skip(&listener, &table_id).is_err());
+    client.hub.leave_table(&listener, &table_id);
+    assert!(client.hub.try_join_table(&listener, &table_id).is_err());
+    assert!(client
+        .catalog
+        .try_mint_track(
+            &artist,
+            &String::from_str(env, "Suspended"),
+            &FIXTURE_TRACK_PRICE,
+            &10,
+            &String::from_str(env, "ipfs://suspended"),
+            &Vec::new(env),
+            &vec![env, (artist.clone(), 100u32)],
+            &None,
+            &None,
+            &Vec::new(env),
+        )
+        .is_err());
+
+    client.catalog.reinstate_user(&listener);
+    client.hub.join_table(&listener, &table_id);
+    client.hub.request_track(&listener, &track_id, &table_id);
+}
+
+#[test]
+fn accepted_reports_strike_the_artist_harder_each_time() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(0).unwrap();
+    let (first, second) = (fixture.listeners.get(0).unwrap(), fixture.listeners.get(1).unwrap());
+    let track_id = fixture.tracks.get(0).unwrap();
+    let moderator = Address::generate(env);
+    let evidence = String::from_str(env, "ipfs://evidence");
+    client.catalog.adjust_reputation(&fixture.links.hub, &artist, &100, &Symbol::new(env, "seed"));
+    let reputation = client.catalog.get_user(&artist).unwrap().reputation;
+
+    let report_id = client.governance.report_track(&first, &track_id, &ReportCategory::Copyright, &evidence);
+    assert!(client.governance.try_report_track(&first, &track_id, &ReportCategory::Spam, &evidence).is_err());
+    let repeat_id = client.governance.report_track(&second, &track_id, &ReportCategory::Copyright, &evidence);
+    assert!(client.governance.try_resolve_report(&moderator, &report_id, &true).is_err());
+
+    client.governance.set_moderator(&moderator, &true);
+    client.governance.resolve_report(&moderator, &report_id, &true);
+    assert!(client.governance.get_report(&report_id).unwrap().status == ReportStatus::Accepted);
+    assert!(client.governance.try_resolve_report(&moderator, &report_id, &false).is_err());
+    client.governance.resolve_report(&moderator, &repeat_id, &true);
+
+    // Ten for the first strike and twenty for the second
+    assert_eq!(client.governance.get_strikes(&artist), 2);
+    assert_eq!(client.catalog.get_user(&artist).unwrap().reputation, reputation - 30);
+}
+
+#[test]
+fn skips_and_kicks_slash_reputation_below_a_tables_minimum() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let strict_table = fixture.tables.get(1).unwrap();
+    let track_id = fixture.tracks.get(5).unwrap();
+    let skipped = fixture.listeners.get(0).unwrap();
+    let voter = fixture.listeners.get(1).unwrap();
+    let kicked = fixture.listeners.get(3).unwrap();
+    for listener in fixture.listeners.iter() {
+        client.catalog.adjust_reputation(&fixture.links.hub, &listener, &100, &Symbol::new(env, "seed"));
+    }
+    let reputation = |user: &Address| client.catalog.get_reputation(user);
+    let base = reputation(&kicked);
+
+    let mut config = SlashingConfig { skip_rate_bps: 10_001, min_requests: 1, skip_slash: 15, kick_slash: 25 };
+    assert!(client.catalog.try_set_slashing_config(&config).is_err());
+    config.skip_rate_bps = 5_000;
+    client.catalog.set_slashing_config(&config);
+
+    // Listener 0's request is playing, and two votes skip it
+    client.hub.advance_queue_public(&owner, &table_id);
+    let before = reputation(&skipped);
+    client.hub.vote_to_skip(&voter, &table_id);
+    client.hub.vote_to_skip(&fixture.listeners.get(2).unwrap(), &table_id);
+    // The usual three for a skip, then the slash for a skip rate over half
+    assert_eq!(reputation(&skipped), before - 3 - 15);
+
+    client.hub.kick_member(&owner, &table_id, &kicked);
+    assert_eq!(reputation(&kicked), base - 25);
+
+    client.hub.set_min_reputation(&fixture.artists.get(1).unwrap(), &strict_table, &(base - 20));
+    assert!(client.hub.try_request_track(&kicked, &track_id, &strict_table).is_err());
+    client.hub.request_track(&voter, &track_id, &strict_table);
+}
+
+#[test]
+fn registration_mints_a_soulbound_profile_burned_on_deregistration() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let user = Address::generate(env);
+    let avatar = String::from_str(env, "ipfs://avatar");
+
+    client.catalog.register_user(&user, &avatar, &None);
+    let profile_id = client.catalog.get_user(&user).unwrap().profile_id;
+    assert_eq!(client.catalog.get_profile_owner(&profile_id), Some(user.clone()));
+    assert!(client.catalog.try_register_user(&user, &avatar, &None).is_err());
+
+    client.catalog.deregister_user(&user);
+    assert_eq!(client.catalog.get_profile_owner(&profile_id), None);
+
+    // Coming back mints a fresh profile rather than reviving the old one
+    client.catalog.register_user(&user, &avatar, &None);
+    let profile_id_again = client.catalog.get_user(&user).unwrap().profile_id;
+    assert!(profile_id_again > profile_id);
+    assert_eq!(client.catalog.get_profile_owner(&profile_id_again), Some(user));
+}
+
+#[test]
+fn table_leaderboard_ranks_spend_and_plays_per_round() {
+    let fixture = fixtures::setup();
+    let client = fixture.client();
+    let owner = fixture.artists.get(0).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let regular = fixture.listeners.get(2).unwrap();
+    let track_id = fixture.tracks.get(1).unwrap();
+    client.hub.request_track(&regular, &track_id, &table_id);
+    client.hub.request_track(&regular, &track_id, &table_id);
+
+    let requesters = client.community.get_table_leaderboard(&table_id, &LeaderboardKind::TopRequesters, &2);
+    assert_eq!(requesters.len(), 2);
+    assert!(requesters.get(0).unwrap().subject == LeaderboardSubject::Requester(regular.clone()));
+    assert_eq!(requesters.get(0).unwrap().score, 3 * FIXTURE_TRACK_PRICE);
+    let tracks = client.community.get_table_leaderboard(&table_id, &LeaderboardKind::TopTracks, &10);
+    assert_eq!(tracks.len(), 3);
+    assert!(tracks.get(0).unwrap().subject == LeaderboardSubject::Track(track_id.clone()));
+    assert_eq!(tracks.get(0).unwrap().score, 3);
+
+    assert!(client.community.try_reset_table_leaderboard(&regular, &table_id).is_err());
+    assert_eq!(client.community.reset_table_leaderboard(&owner, &table_id), 1);
+    assert!(client.community.get_table_leaderboard(&table_id, &LeaderboardKind::TopTracks, &10).is_empty());
+    // The other table's board is untouched
+    assert_eq!(client.community.get_table_leaderboard(&fixture.tables.get(1).unwrap(), &LeaderboardKind::TopTracks, &10).len(), 3);
+}
+
+#[test]
+fn early_access_limits_a_drop_to_subscribers_until_release() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let artist = fixture.artists.get(1).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let fan = fixture.listeners.get(0).unwrap();
+    let subscriber = fixture.listeners.get(1).unwrap();
+    let invited = fixture.listeners.get(2).unwrap();
+    let release = env.ledger().timestamp() + 86400;
+    let track_id = client.catalog.mint_track(
+        &artist,
+        &String::from_str(env, "Exclusive Drop"),
+        &FIXTURE_TRACK_PRICE,
+        &10,
+        &String::from_str(env, "ipfs://drop"),
+        &Vec::new(env),
+        &vec![env, (artist.clone(), 100u32)],
+        &Some(release),
+        &None,
+        &Vec::new(env),
+    );
+    let tier = SubscriptionTier {
+        price: 2_000,
+        period_secs: 30 * 86400,
+        benefits_hash: fixtures::fixture_id(env, b"benefits", 0),
+    };
+    client.market.set_subscription_tiers(&artist, &vec![env, tier]);
+
+    assert!(client.hub.try_request_track(&fan, &track_id, &table_id).is_err());
+    client.market.subscribe(&subscriber, &artist, &0);
+    client.hub.request_track(&subscriber, &track_id, &table_id);
+    client.market.set_early_access(&artist, &track_id, &invited, &true);
+    client.hub.request_track(&invited, &track_id, &table_id);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp = release);
+    client.hub.request_track(&fan, &track_id, &table_id);
+}
+
+#[test]
+fn gifts_pay_for_another_members_requests() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let giver = fixture.listeners.get(0).unwrap();
+    let friend = fixture.listeners.get(3).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let track_id = fixture.tracks.get(5).unwrap();
+    let (giver_balance, friend_balance) = (token.balance(&giver), token.balance(&friend));
+
+    let request_id = client.hub.gift_request(&giver, &friend, &track_id, &table_id);
+    assert_eq!(client.hub.get_request(&request_id).unwrap().requester, friend);
+    assert_eq!(token.balance(&giver), giver_balance - FIXTURE_TRACK_PRICE);
+    assert_eq!(token.balance(&friend), friend_balance);
+
+    assert!(client.venue.try_gift_credit(&giver, &Address::generate(env), &1_500).is_err());
+    assert!(client.venue.try_gift_credit(&giver, &friend, &0).is_err());
+    client.venue.gift_credit(&giver, &friend, &1_500);
+    assert_eq!(token.balance(&giver), giver_balance - FIXTURE_TRACK_PRICE - 1_500);
+
+    // The credit covers the next request, and what's left carries over
+    client.hub.request_track(&friend, &track_id, &table_id);
+    assert_eq!(token.balance(&friend), friend_balance);
+    assert_eq!(client.hub.get_token_credit(&friend), 1_500 - FIXTURE_TRACK_PRICE);
+}
+
+#[test]
+fn loyalty_points_redeem_for_a_play_at_opted_in_tables() {
+    let fixture = fixtures::setup();
+    let env = &fixture.env;
+    let client = fixture.client();
+    let token = token::Client::new(env, &fixture.token);
+    let listener = fixture.listeners.get(0).unwrap();
+    let short = fixture.listeners.get(3).unwrap();
+    let table_id = fixture.tables.get(0).unwrap();
+    let track_id = fixture.tracks.get(5).unwrap();
+    client.market.set_loyalty_config(&LoyaltyConfig { earn_bps: 1_000, points_per_request: 200 });
+
+    // Listener 0 has a paid play on each table and listener 3 on only one
+    for (i, table_id) in fixture.tables.iter().enumerate() {
+        play_out(&client.hub, &fixture.artists.get(i as u32).unwrap(), &table_id);
+    }
+    assert_eq!(client.market.get_loyalty_points(&listener), 200);
+    assert_eq!(client.market.get_loyalty_points(&short), 100);
+
+    assert!(client.hub.try_redeem_points_for_request(&listener, &track_id, &table_id).is_err());
+    client.market.set_table_loyalty(&fixture.artists.get(0).unwrap(), &table_id, &true);
+    assert!(client.hub.try_redeem_points_for_request(&short, &track_id, &table_id).is_err());
+
+    let balance = token.balance(&listener);
+    let request_id = client.hub.redeem_points_for_request(&listener, &track_id, &table_id);
+    assert_eq!(token.balance(&listener), balance);
+    assert_eq!(client.market.get_loyalty_points(&listener), 0);
+    assert_eq!(client.hub.get_request(&request_id).unwrap().amount_paid, 0);
+    assert_eq!(client.hub.get_queue(&table_id), vec![env, track_id]);
 }
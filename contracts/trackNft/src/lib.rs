@@ -0,0 +1,67 @@
+#![no_std]
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Symbol};
+
+mod test;
+
+#[contracttype]
+enum DataKey {
+    Minter,
+    Owner,
+    Title,
+    MetadataUri,
+}
+
+// One contract per track, deployed by MetaJuke at mint time. The single token is
+// minted to the artist once and can then be transferred by its owner.
+#[contract]
+pub struct TrackNft;
+
+#[contractimpl]
+impl TrackNft {
+    pub fn __constructor(env: Env, minter: Address, title: String, metadata_uri: String) {
+        env.storage().instance().set(&DataKey::Minter, &minter);
+        env.storage().instance().set(&DataKey::Title, &title);
+        env.storage().instance().set(&DataKey::MetadataUri, &metadata_uri);
+    }
+
+    pub fn mint(env: Env, to: Address) {
+        let minter: Address = env.storage().instance().get(&DataKey::Minter).unwrap();
+        minter.require_auth();
+
+        if env.storage().instance().has(&DataKey::Owner) {
+            panic!("Already minted");
+        }
+
+        env.storage().instance().set(&DataKey::Owner, &to);
+
+        env.events().publish((Symbol::new(&env, "mint"), to), ());
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address) {
+        from.require_auth();
+
+        let owner: Address = env.storage().instance()
+            .get(&DataKey::Owner)
+            .unwrap_or_else(|| panic!("Not minted"));
+
+        if owner != from {
+            panic!("Not token owner");
+        }
+
+        env.storage().instance().set(&DataKey::Owner, &to);
+
+        env.events().publish((Symbol::new(&env, "transfer"), from, to), ());
+    }
+
+    pub fn owner_of(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Owner)
+    }
+
+    pub fn title(env: Env) -> String {
+        env.storage().instance().get(&DataKey::Title).unwrap()
+    }
+
+    pub fn metadata_uri(env: Env) -> String {
+        env.storage().instance().get(&DataKey::MetadataUri).unwrap()
+    }
+}
@@ -0,0 +1,30 @@
+#![cfg(test)]
+
+use crate::{TrackNft, TrackNftClient};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env, String};
+
+#[test]
+fn mints_once_and_transfers() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let minter = Address::generate(&env);
+    let artist = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let nft_id = env.register(
+        TrackNft,
+        (minter, String::from_str(&env, "Track"), String::from_str(&env, "ipfs://track")),
+    );
+    let nft = TrackNftClient::new(&env, &nft_id);
+
+    assert_eq!(nft.owner_of(), None);
+    nft.mint(&artist);
+    assert_eq!(nft.owner_of(), Some(artist.clone()));
+    assert!(nft.try_mint(&buyer).is_err());
+
+    assert!(nft.try_transfer(&buyer, &buyer).is_err());
+    nft.transfer(&artist, &buyer);
+    assert_eq!(nft.owner_of(), Some(buyer));
+    assert_eq!(nft.metadata_uri(), String::from_str(&env, "ipfs://track"));
+}